@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::hint::black_box;
+
+use gemini::parse::Response;
+
+/// A gemtext page of about `size` bytes mixing prose, links, lists and
+/// preformatted blocks.
+fn gemtext(size: usize) -> String {
+    let mut page = String::from("20 text/gemini; lang=en\r\n# A large page\n\n");
+    let mut n = 0;
+    while page.len() < size {
+        page.push_str(&format!(
+            "## Section {n}\n\
+             Some prose with enough words to look like a paragraph of a real page.\n\
+             => /page/{n}.gmi 2024-01-01 Link {n}\n\
+             * A list item\n\
+             ```\n\
+             => not a link\n\
+             ```\n"
+        ));
+        n += 1;
+    }
+    page
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for size in [10 * 1024, 1024 * 1024] {
+        let page = gemtext(size);
+        group.throughput(Throughput::Bytes(page.len() as u64));
+        group.bench_function(format!("gemtext {} KiB", size / 1024), |b| {
+            b.iter(|| Response::try_from(black_box(page.as_str())));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
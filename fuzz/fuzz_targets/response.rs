@@ -0,0 +1,25 @@
+//! Raw responses, as read from a server, must parse into a `Response` or a
+//! `ResponseError`, and everything built from a parsed `Response` must not panic.
+//!
+//! Run with `cargo +nightly fuzz run response fuzz/corpus/response`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use gemini::parse::Response;
+
+fuzz_target!(|data: &[u8]| {
+    let lossy = String::from_utf8_lossy(data);
+    for parsed in [Response::try_from(data), Response::try_from(lossy.as_ref())] {
+        let Ok(mut response) = parsed else {
+            continue;
+        };
+        response.headings();
+        response.title();
+        response.link_line(0);
+        response.gemtext();
+        response.status_summary();
+        response.sniff_gemtext();
+    }
+});
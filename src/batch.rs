@@ -0,0 +1,103 @@
+use log::{debug, error};
+use std::io::{self, BufRead, Write};
+use std::thread;
+use url::Url;
+
+use crate::cache::{Freshness, PageCache};
+use crate::client::parse_user_url;
+use crate::config::Config;
+use crate::models::{Request, Response};
+use crate::parse::StatusCode;
+
+/// Fetch every URL read from `input` and write one tab-separated summary line per URL:
+/// `url status mime bytes links`, or `url ERR error` when the fetch failed.
+/// With `changed_only`, pages whose body is the same as when last recorded there
+/// are left out.
+///
+/// Returns `Ok(false)` if any fetch failed or ended on a status other than a
+/// success or a redirect.
+pub fn run(
+    input: impl BufRead,
+    mut output: impl Write,
+    config: &Config,
+    changed_only: Option<&PageCache>,
+) -> io::Result<bool> {
+    let mut all_succeeded = true;
+    let mut first = true;
+
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if !first {
+            thread::sleep(config.crawl_delay);
+        }
+        first = false;
+
+        let summary = match fetch(line, config) {
+            Ok((url, response)) if is_unchanged(changed_only, &url, &response) => {
+                debug!("{line} is unchanged");
+                continue;
+            }
+            Ok((_, response)) => {
+                if !matches!(
+                    response.status_code,
+                    StatusCode::Success | StatusCode::Redirect
+                ) {
+                    all_succeeded = false;
+                }
+                summarize(line, &response)
+            }
+            Err(e) => {
+                error!("Batch fetch of {line} failed: {e}");
+                all_succeeded = false;
+                format!("{line}\tERR\t{e}")
+            }
+        };
+
+        writeln!(output, "{summary}")?;
+        output.flush()?;
+    }
+
+    Ok(all_succeeded)
+}
+
+/// Record the body of `response` in `cache`, returns whether it is the same as before.
+fn is_unchanged(cache: Option<&PageCache>, url: &Url, response: &Response) -> bool {
+    let Some(cache) = cache else {
+        return false;
+    };
+    matches!(
+        cache.record(url, response.body.as_deref().unwrap_or_default()),
+        Freshness::Unchanged(_)
+    )
+}
+
+fn fetch(input: &str, config: &Config) -> Result<(Url, Response), String> {
+    let url = parse_user_url(input).map_err(|e| e.to_string())?;
+    debug!("Batch fetching {url}");
+
+    let settings = config.connection_settings(url.host_str().unwrap_or_default());
+    match Request::new(url.clone()).with_settings(settings).send() {
+        Ok(Ok(response)) => Ok((url, response)),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn summarize(url: &str, response: &Response) -> String {
+    let mime = response
+        .mime()
+        .map_or_else(|| "-".to_string(), |m| m.essence);
+    let bytes = response.body.as_ref().map_or(0, String::len);
+
+    format!(
+        "{url}\t{}\t{mime}\t{bytes}\t{}",
+        response.status_code_num,
+        response.links.len()
+    )
+}
@@ -8,17 +8,31 @@
 #![warn(clippy::expect_used)]
 
 use clap::Parser;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::sync::LazyLock;
+use std::time::Duration;
 
 use env_logger::Env;
 use log::{error, warn};
 use std::io::{self};
 use url::Url;
 
-use gemini::client::Client;
+use gemini::batch;
+use gemini::bookmarks::{bookmarks_path, Bookmarks};
+use gemini::cache::{cache_path, PageCache};
+use gemini::client::{parse_user_url, start_url, Client};
+use gemini::config::{config_path, Config, ConfigFile};
+use gemini::errors::HeadlessError;
 use gemini::handlers::handle_request;
+use gemini::headless;
 use gemini::models::Pager;
+use gemini::pager;
+use gemini::render::{link_footer, DateDisplay, PreformattedOverflow, RendererKind};
+use gemini::state::{session_path, SessionFlush, SessionMode, SessionState};
+use gemini::terminal::{restore_terminal, TerminalGuard};
+use gemini::tofu::{known_hosts_path, TofuStore};
+use gemini::version::version_info;
 
 fn exit_with_error(msg: &str) -> ! {
     error!("{msg}");
@@ -32,44 +46,422 @@ static LOGGER: LazyLock<()> = LazyLock::new(|| {
         .init();
 });
 
-fn initialize_url(input: String) -> Url {
-    let full_url = if input.starts_with("gemini://") {
-        input
-    } else {
-        format!("gemini://{input}")
-    };
-
-    match Url::try_from(full_url.as_str()) {
+fn initialize_url(input: &str) -> Url {
+    match parse_user_url(input) {
         Ok(u) => u,
         Err(e) => exit_with_error(&format!("Could not parse URL: {e}")),
     }
 }
 
-fn main_loop(client: &mut Client, mut url: Url) -> io::Result<()> {
+fn main_loop(client: &mut Client, mut url: Url, session: Option<&SessionFlush>) -> io::Result<()> {
     loop {
-        match handle_request(client, &url) {
+        let next = handle_request(client, &url);
+        if let Some(session) = session {
+            session.update(client);
+        }
+        match next {
             Some(new_url) => url = new_url,
             None => return Ok(()),
         }
     }
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Parser, Debug)]
-#[command(name = "gemini-client")]
+#[command(name = "gemini-client", disable_version_flag = true)]
 #[command(about = "A simple Gemini protocol client", long_about = None)]
 struct Cli {
-    url: String,
+    /// Print the version and TLS backend
+    #[arg(short = 'V', long)]
+    version: bool,
+
+    /// Page to open [default: --url-file, then the `GEMINI_URL` variable, then the home page]
+    url: Option<String>,
+
+    /// Read the page to open from the first line of this file
+    #[arg(long, value_name = "PATH")]
+    url_file: Option<PathBuf>,
 
     #[arg(long, value_enum, default_value_t = Pager::Less)]
     pager: Pager,
+
+    /// How page bodies are rendered
+    #[arg(long, value_enum, default_value_t = RendererKind::Gemtext)]
+    renderer: RendererKind,
+
+    /// Preformatted lines wider than the terminal are left whole for scrolling, or cut with a →
+    #[arg(long, value_enum, default_value_t = PreformattedOverflow::Scroll)]
+    preformatted_overflow: PreformattedOverflow,
+
+    /// Dates starting link names, as in feeds, shown as written or as how long ago they were
+    #[arg(long, value_enum, default_value_t = DateDisplay::Absolute)]
+    dates: DateDisplay,
+
+    /// Number link lines inside the page body (links are always listed below it)
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    inline_link_numbers: bool,
+
+    /// Number the lines of `text/plain` pages
+    #[arg(long)]
+    line_numbers: bool,
+
+    /// Render pages that look like gemtext as gemtext even when served as another type
+    #[arg(long)]
+    sniff_gemtext: bool,
+
+    /// List links below the page several per line, without their URL
+    #[arg(long)]
+    compact_links: bool,
+
+    /// List at most N links below the page. All links can still be selected by number
+    #[arg(long, value_name = "N")]
+    max_links: Option<usize>,
+
+    /// Preferred languages for pages offering several versions, e.g. `en,fr`
+    #[arg(long, value_delimiter = ',')]
+    lang: Vec<String>,
+
+    /// Go straight to the preferred language version of a page
+    #[arg(long, requires = "lang")]
+    follow_language: bool,
+
+    /// Show the capsule's favicon.txt emoji in the prompt
+    #[arg(long)]
+    favicons: bool,
+
+    /// Ask before quitting with `q`
+    #[arg(long)]
+    confirm_quit: bool,
+
+    /// Do not show a spinner while loading pages
+    #[arg(long)]
+    no_spinner: bool,
+
+    /// Only print pages and errors: no spinner, hints or goodbye
+    #[arg(long, short)]
+    quiet: bool,
+
+    /// Reopen the page (and history) saved when the last session ended
+    #[arg(long)]
+    resume: bool,
+
+    /// What to save of the session on exit
+    #[arg(long, value_enum, default_value_t = SessionMode::Last)]
+    session: SessionMode,
+
+    /// Configuration file [default: ~/.config/gemini/config.toml]
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Print the page body as sent by the server instead of opening the pager
+    #[arg(long)]
+    raw: bool,
+
+    /// Print only the status and meta of the page's response header, without
+    /// downloading the body. The exit code is 0 for a success, the status's first digit otherwise
+    #[arg(long, conflicts_with_all = ["raw", "follow", "batch"])]
+    head: bool,
+
+    /// Follow link N of the page without prompting, then print the resulting page.
+    /// Repeat to follow a path of links
+    #[arg(long, value_name = "N")]
+    follow: Vec<usize>,
+
+    /// Follow link N of the page, then browse from there
+    #[arg(long, value_name = "N", conflicts_with_all = ["raw", "follow", "batch", "head"])]
+    open_link: Option<usize>,
+
+    /// Bookmark the links of a gemtext file, skipping pages already bookmarked
+    #[arg(long, value_name = "PATH")]
+    import_bookmarks: Option<PathBuf>,
+
+    /// Read newline-separated URLs from stdin and print a tab-separated summary of each.
+    /// The exit code is 1 if a fetch failed or a page answered other than with a success or a redirect
+    #[arg(long)]
+    batch: bool,
+
+    /// With --batch, leave out pages whose body didn't change since they were last fetched
+    #[arg(long, requires = "batch")]
+    changed_only: bool,
+
+    /// Forget resolved host addresses after this many seconds without a request
+    #[arg(long, value_name = "SECONDS")]
+    refresh_interval: Option<u64>,
+
+    /// With --raw, --follow and --open-link, follow redirects to other hosts too
+    #[arg(long)]
+    allow_cross_host_redirect: bool,
+
+    /// Times to retry a page that is temporarily unavailable (status 41 or 43)
+    #[arg(long, default_value_t = 1)]
+    retries: usize,
+
+    /// Milliseconds to wait between automated fetches
+    #[arg(long, default_value_t = 1000)]
+    crawl_delay: u64,
+
+    /// Print the bytes of each request and the start of its raw response to stderr.
+    /// Queries typed at a sensitive prompt are hidden
+    #[arg(long)]
+    trace_request: bool,
+
+    /// Show how long each page took to fetch in the status line
+    #[arg(long)]
+    timing: bool,
+
+    /// Accessibility mode: start pages with a line naming their declared language
+    #[arg(long)]
+    a11y: bool,
 }
 
 fn main() -> io::Result<()> {
     LazyLock::force(&LOGGER);
 
     let cli = Cli::parse();
-    let url = initialize_url(cli.url);
-    let mut client = Client::new(&url, cli.pager);
+    if cli.version {
+        println!("{}", version_info());
+        return Ok(());
+    }
+    if let Some(path) = &cli.import_bookmarks {
+        import_bookmarks(path);
+        return Ok(());
+    }
+
+    let config = load_config(&cli);
+
+    let cache = load_page_cache();
 
-    main_loop(&mut client, url)
+    if cli.batch {
+        let changed_only = cache.as_ref().filter(|_| cli.changed_only);
+        let all_succeeded = batch::run(
+            io::stdin().lock(),
+            io::stdout().lock(),
+            &config,
+            changed_only,
+        )?;
+        exit(i32::from(!all_succeeded));
+    }
+
+    let saved = if cli.resume { load_session() } else { None };
+
+    let from_file = cli.url_file.as_deref().map(read_url_file);
+    let from_env = std::env::var("GEMINI_URL").ok();
+    let resumed = saved
+        .as_ref()
+        .and_then(|s| s.last_url.as_ref())
+        .map(Url::to_string);
+
+    let Some(input) = start_url(cli.url, from_file, from_env, resumed, config.home.clone()) else {
+        exit_with_error("No URL given, and no home page configured");
+    };
+    let url = initialize_url(&input);
+    let mut client = Client::new(&url, cli.pager, config);
+    if let Some(cache) = cache {
+        client.cache = cache;
+    }
+    if let Some(path) = known_hosts_path() {
+        match TofuStore::load(&path) {
+            Ok(store) => client.tofu = store,
+            Err(e) => warn!("Could not load known hosts from {}: {e}", path.display()),
+        }
+    }
+    if let Some(saved) = saved {
+        client.history = saved.history;
+        client.titles = saved.titles;
+        // The page we reopen is pushed again by its request
+        if client.history.last() == Some(&url) {
+            client.history.pop();
+        }
+    }
+
+    if cli.head {
+        client.config.spinner = false;
+        match headless::head(&mut client, &url, io::stdout().lock()) {
+            Ok(status) => exit(headless::exit_code(status)),
+            Err(e) => exit_with_error(&e.to_string()),
+        }
+    }
+
+    if cli.raw || !cli.follow.is_empty() {
+        client.config.spinner = false;
+        if let Err(e) = headless::run(&mut client, &url, &cli.follow, cli.raw, io::stdout().lock())
+        {
+            exit_with_error(&e.to_string());
+        }
+        return Ok(());
+    }
+
+    let url = match cli.open_link {
+        Some(index) => open_link(&mut client, &url, index),
+        None => url,
+    };
+
+    let session = session_flush(cli.session);
+
+    {
+        let _terminal = TerminalGuard::new();
+        main_loop(&mut client, url, session.as_ref())?;
+    }
+
+    if let Some(session) = &session {
+        session.update(&client);
+        save_session(session);
+    }
+
+    Ok(())
+}
+
+/// The configuration given by the flags, then the configuration file.
+fn load_config(cli: &Cli) -> Config {
+    let mut config = Config {
+        inline_link_numbers: cli.inline_link_numbers,
+        renderer: cli.renderer,
+        preformatted_overflow: cli.preformatted_overflow,
+        dates: cli.dates,
+        line_numbers: cli.line_numbers,
+        sniff_gemtext: cli.sniff_gemtext,
+        compact_links: cli.compact_links,
+        max_links: cli.max_links,
+        languages: cli.lang.clone(),
+        follow_language: cli.follow_language,
+        favicons: cli.favicons,
+        confirm_quit: cli.confirm_quit,
+        spinner: !cli.no_spinner && !cli.quiet,
+        quiet: cli.quiet,
+        trace_request: cli.trace_request,
+        timing: cli.timing,
+        a11y: cli.a11y,
+        allow_cross_host_redirects: cli.allow_cross_host_redirect,
+        retries: cli.retries,
+        crawl_delay: Duration::from_millis(cli.crawl_delay),
+        refresh_interval: cli.refresh_interval.map(Duration::from_secs),
+        ..Config::default()
+    };
+
+    if let Some(path) = cli.config.clone().or_else(config_path) {
+        match ConfigFile::load_or_default(&path) {
+            Ok(file) => file.apply(&mut config),
+            Err(e) => exit_with_error(&format!("Could not load {}: {e}", path.display())),
+        }
+    }
+
+    config
+}
+
+/// Add the links of the gemtext file at `path` to the bookmarks and tell how many were.
+fn import_bookmarks(path: &Path) {
+    let gemtext = match std::fs::read_to_string(path) {
+        Ok(gemtext) => gemtext,
+        Err(e) => exit_with_error(&format!("Could not read {}: {e}", path.display())),
+    };
+    let Some(bookmarks_path) = bookmarks_path() else {
+        exit_with_error("Could not find a directory for bookmarks");
+    };
+    let mut bookmarks = match Bookmarks::load_or_default(&bookmarks_path) {
+        Ok(bookmarks) => bookmarks,
+        Err(e) => exit_with_error(&format!(
+            "Could not load bookmarks from {}: {e}",
+            bookmarks_path.display()
+        )),
+    };
+
+    let report = bookmarks.import(&gemtext);
+    if let Err(e) = bookmarks.save(&bookmarks_path) {
+        exit_with_error(&format!(
+            "Could not save bookmarks to {}: {e}",
+            bookmarks_path.display()
+        ));
+    }
+    println!(
+        "Imported {} bookmarks, skipped {} duplicates and {} invalid links",
+        report.imported, report.duplicates, report.invalid
+    );
+}
+
+fn read_url_file(path: &Path) -> String {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => contents
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default()
+            .to_string(),
+        Err(e) => exit_with_error(&format!("Could not read {}: {e}", path.display())),
+    }
+}
+
+/// Fetch `url` and resolve its link `index`. When there is no such link, the links
+/// of the page are listed before exiting.
+fn open_link(client: &mut Client, url: &Url, index: usize) -> Url {
+    let (_, response) =
+        headless::fetch(client, url).unwrap_or_else(|e| exit_with_error(&e.to_string()));
+
+    match headless::follow_link(client, &response, index) {
+        Ok(url) => url,
+        Err(e @ HeadlessError::LinkOutOfRange { .. }) => {
+            for line in link_footer(&response.links, None, &[], &[]) {
+                println!("{line}");
+            }
+            exit_with_error(&e.to_string())
+        }
+        Err(e) => exit_with_error(&e.to_string()),
+    }
+}
+
+fn load_page_cache() -> Option<PageCache> {
+    let path = cache_path()?;
+    match PageCache::load(&path) {
+        Ok(cache) => Some(cache),
+        Err(e) => {
+            warn!("Could not load page cache from {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn load_session() -> Option<SessionState> {
+    let path = session_path()?;
+    match SessionState::load(&path) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            warn!("Could not load session from {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Where the session is saved on exit, also on Ctrl-C. `None` when it isn't saved.
+fn session_flush(mode: SessionMode) -> Option<SessionFlush> {
+    if mode == SessionMode::Off {
+        return None;
+    }
+    let Some(path) = session_path() else {
+        warn!("Could not find a directory to save the session in");
+        return None;
+    };
+    let session = SessionFlush::new(path, mode);
+    install_interrupt_handler(session.clone());
+    Some(session)
+}
+
+fn save_session(session: &SessionFlush) {
+    if let Err(e) = session.flush() {
+        warn!("Could not save session: {e}");
+    }
+}
+
+/// Save the session before exiting on Ctrl-C. Ctrl-C typed in an external pager is
+/// left to the pager, and the prompts read it as a key in raw mode.
+fn install_interrupt_handler(session: SessionFlush) {
+    let result = ctrlc::set_handler(move || {
+        if pager::external_pager_running() {
+            return;
+        }
+        restore_terminal();
+        save_session(&session);
+        exit(130);
+    });
+    if let Err(e) = result {
+        warn!("Could not install the Ctrl-C handler: {e}");
+    }
 }
@@ -0,0 +1,171 @@
+use log::warn;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use url::{form_urlencoded, Url};
+
+use crate::models::{is_fence, Link, SpecificCode};
+
+/// URL of the page listing the bookmarks.
+pub const BOOKMARKS_URL: &str = "about:bookmarks";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub url: Url,
+    pub title: Option<String>,
+}
+
+/// Bookmarked pages, in the order they were added. Saved as a gemtext list of links.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Bookmarks {
+    pub entries: Vec<Bookmark>,
+}
+
+/// How many links of an imported list were added or skipped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub imported: usize,
+    /// Links to pages already bookmarked, or listed twice.
+    pub duplicates: usize,
+    /// Links that aren't absolute URLs, there's no page to resolve them against.
+    pub invalid: usize,
+}
+
+impl Bookmarks {
+    pub fn contains(&self, url: &Url) -> bool {
+        self.entries.iter().any(|bookmark| &bookmark.url == url)
+    }
+
+    /// Bookmark `url`, unless it already is. Returns whether it was added.
+    pub fn add(&mut self, url: Url, title: Option<String>) -> bool {
+        if self.contains(&url) {
+            return false;
+        }
+        self.entries.push(Bookmark { url, title });
+        true
+    }
+
+    /// Returns whether `url` was bookmarked.
+    pub fn remove(&mut self, url: &Url) -> bool {
+        let count = self.entries.len();
+        self.entries.retain(|bookmark| &bookmark.url != url);
+        self.entries.len() != count
+    }
+
+    /// Point the bookmark of `from` to `to`, keeping its title and place. Returns
+    /// whether `from` was bookmarked.
+    pub fn replace(&mut self, from: &Url, to: &Url) -> bool {
+        if self.contains(to) {
+            return self.remove(from);
+        }
+        match self
+            .entries
+            .iter_mut()
+            .find(|bookmark| &bookmark.url == from)
+        {
+            Some(bookmark) => {
+                bookmark.url = to.clone();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Bookmark each `=>` link of `gemtext` under its name, leaving out preformatted
+    /// blocks.
+    pub fn import(&mut self, gemtext: &str) -> ImportReport {
+        let mut report = ImportReport::default();
+        let mut preformatted = false;
+
+        for line in gemtext.lines() {
+            if is_fence(line) {
+                preformatted = !preformatted;
+                continue;
+            }
+            let Some(link) = Link::try_from(line).ok().filter(|_| !preformatted) else {
+                continue;
+            };
+            match Url::parse(&link.href) {
+                Ok(url) => {
+                    if self.add(url, link.name) {
+                        report.imported += 1;
+                    } else {
+                        report.duplicates += 1;
+                    }
+                }
+                Err(e) => {
+                    warn!("Skipping invalid bookmark {}: {e}", link.href);
+                    report.invalid += 1;
+                }
+            }
+        }
+
+        report
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut contents = String::new();
+        for bookmark in &self.entries {
+            match &bookmark.title {
+                Some(title) => contents.push_str(&format!("=> {} {title}\n", bookmark.url)),
+                None => contents.push_str(&format!("=> {}\n", bookmark.url)),
+            }
+        }
+
+        fs::write(path, contents)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut bookmarks = Self::default();
+
+        for line in fs::read_to_string(path)?.lines() {
+            let Ok(link) = Link::try_from(line) else {
+                continue;
+            };
+            match Url::parse(&link.href) {
+                Ok(url) => {
+                    bookmarks.add(url, link.name);
+                }
+                Err(e) => warn!("Skipping invalid bookmark {}: {e}", link.href),
+            }
+        }
+
+        Ok(bookmarks)
+    }
+
+    /// The saved bookmarks, none if there is no bookmarks file yet.
+    pub fn load_or_default(path: &Path) -> io::Result<Self> {
+        match Self::load(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            result => result,
+        }
+    }
+}
+
+/// Whether a redirect with `code` away from `url` should move its bookmark: only
+/// permanent redirects of bookmarked pages do.
+pub fn moves_bookmark(bookmarks: &Bookmarks, code: SpecificCode, url: &Url) -> bool {
+    code == SpecificCode::PermanentRedirect && bookmarks.contains(url)
+}
+
+/// Directory for user data, following the XDG base directory spec.
+pub fn data_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map(|dir| dir.join("gemini"))
+}
+
+pub fn bookmarks_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("bookmarks.gmi"))
+}
+
+/// `about:bookmarks?remove=URL`, the link removing `url` from the bookmarks.
+pub fn remove_link(url: &Url) -> String {
+    let encoded: String = form_urlencoded::byte_serialize(url.as_str().as_bytes()).collect();
+    format!("{BOOKMARKS_URL}?remove={encoded}")
+}
@@ -0,0 +1,125 @@
+use log::warn;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+use crate::state::state_dir;
+use crate::tofu::fingerprint;
+
+/// How a page compares with the last time it was fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// Never fetched before.
+    New,
+    /// Same body as last time, fetched at the given Unix time.
+    Unchanged(u64),
+    Changed,
+}
+
+/// Hex SHA-256 of a page body.
+pub fn content_hash(body: &str) -> String {
+    fingerprint(body.as_bytes())
+}
+
+/// Compare the hash of a fresh body with the one stored, if any.
+pub fn freshness(stored: Option<&CacheEntry>, hash: &str) -> Freshness {
+    match stored {
+        None => Freshness::New,
+        Some(entry) if entry.hash == hash => Freshness::Unchanged(entry.fetched),
+        Some(_) => Freshness::Changed,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub hash: String,
+    /// Unix time of the fetch.
+    pub fetched: u64,
+}
+
+/// Content hash of the last fetched body of each page, saved to `path` when it is set.
+#[derive(Debug, Clone, Default)]
+pub struct PageCache {
+    entries: Arc<Mutex<HashMap<String, CacheEntry>>>,
+    path: Option<PathBuf>,
+}
+
+impl PageCache {
+    /// The cache saved at `path`, empty if there is no file yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut entries = HashMap::new();
+
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                for line in contents.lines() {
+                    let mut parts = line.split(' ');
+                    match (parts.next(), parts.next(), parts.next().map(str::parse)) {
+                        (Some(url), Some(hash), Some(Ok(fetched))) => {
+                            let hash = hash.to_string();
+                            entries.insert(url.to_string(), CacheEntry { hash, fetched });
+                        }
+                        _ => warn!("Skipping invalid cache entry: {line}"),
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        Ok(Self {
+            entries: Arc::new(Mutex::new(entries)),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    pub fn get(&self, url: &Url) -> Option<CacheEntry> {
+        self.entries.lock().ok()?.get(url.as_str()).cloned()
+    }
+
+    /// Store the hash of `body` as the latest for `url`, and tell how it compares with
+    /// the previous one.
+    pub fn record(&self, url: &Url, body: &str) -> Freshness {
+        let hash = content_hash(body);
+        let Ok(mut entries) = self.entries.lock() else {
+            return Freshness::New;
+        };
+
+        let freshness = freshness(entries.get(url.as_str()), &hash);
+        let fetched = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        entries.insert(url.to_string(), CacheEntry { hash, fetched });
+
+        if let Some(path) = &self.path {
+            if let Err(e) = save(path, &entries) {
+                warn!("Could not save page cache to {}: {e}", path.display());
+            }
+        }
+
+        freshness
+    }
+}
+
+fn save(path: &Path, entries: &HashMap<String, CacheEntry>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut sorted: Vec<_> = entries.iter().collect();
+    sorted.sort_by_key(|(url, _)| *url);
+
+    let mut contents = String::new();
+    for (url, entry) in sorted {
+        contents.push_str(&format!("{url} {} {}\n", entry.hash, entry.fetched));
+    }
+
+    fs::write(path, contents)
+}
+
+pub fn cache_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("pages"))
+}
@@ -0,0 +1,138 @@
+use log::debug;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use url::Url;
+
+use crate::client::Client;
+use crate::config::Config;
+use crate::models::{Request, Response};
+
+/// How many hosts are checked at the same time.
+pub const CHECK_WORKERS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// The server answered with this status and meta.
+    Status(u8, String),
+    /// No valid answer: connection, TLS or parse error.
+    Unreachable(String),
+    /// Not a Gemini link, so not checked.
+    Skipped,
+}
+
+impl LinkStatus {
+    pub fn is_broken(&self) -> bool {
+        match self {
+            LinkStatus::Status(code, _) => (40..=59).contains(code),
+            LinkStatus::Unreachable(_) => true,
+            LinkStatus::Skipped => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub url: Url,
+    pub name: Option<String>,
+    pub status: LinkStatus,
+}
+
+/// Request every link of `response` and report how each answered.
+///
+/// Hosts are checked in parallel by up to `workers` threads. Links to the same host
+/// are checked one after the other, `config.crawl_delay` apart.
+pub fn check_links(client: &Client, response: &Response, workers: usize) -> Vec<CheckResult> {
+    let mut results: Vec<CheckResult> = Vec::new();
+    let mut by_host: HashMap<String, Vec<usize>> = HashMap::new();
+
+    for link in &response.links {
+        let Ok(url) = client.resolve_link(&link.href) else {
+            continue;
+        };
+        if url.scheme() == "gemini" {
+            let host = url.host_str().unwrap_or_default().to_string();
+            by_host.entry(host).or_default().push(results.len());
+        }
+        results.push(CheckResult {
+            url,
+            name: link.name.clone(),
+            status: LinkStatus::Skipped,
+        });
+    }
+
+    let queue = Arc::new(Mutex::new(by_host.into_values().collect::<Vec<_>>()));
+    let results = Arc::new(Mutex::new(results));
+
+    let handles: Vec<_> = (0..workers.max(1))
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let results = Arc::clone(&results);
+            let config = client.config.clone();
+            thread::spawn(move || loop {
+                let Some(indices) = queue.lock().ok().and_then(|mut queue| queue.pop()) else {
+                    return;
+                };
+                for (n, index) in indices.into_iter().enumerate() {
+                    if n > 0 {
+                        thread::sleep(config.crawl_delay);
+                    }
+                    let Some(url) = results.lock().ok().map(|r| r[index].url.clone()) else {
+                        return;
+                    };
+                    let status = check_url(&url, &config);
+                    if let Ok(mut results) = results.lock() {
+                        results[index].status = status;
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().ok();
+    }
+
+    Arc::try_unwrap(results)
+        .ok()
+        .and_then(|results| results.into_inner().ok())
+        .unwrap_or_default()
+}
+
+fn check_url(url: &Url, config: &Config) -> LinkStatus {
+    debug!("Checking {url}");
+    let settings = config.connection_settings(url.host_str().unwrap_or_default());
+
+    match Request::new(url.clone()).with_settings(settings).send() {
+        Ok(Ok(response)) => LinkStatus::Status(response.status_code_num, response.meta_description),
+        Ok(Err(e)) => LinkStatus::Unreachable(e.to_string()),
+        Err(e) => LinkStatus::Unreachable(e.to_string()),
+    }
+}
+
+/// Gemtext report of `results`, broken links listed first.
+pub fn report_page(results: &[CheckResult]) -> String {
+    let broken = results.iter().filter(|r| r.status.is_broken()).count();
+    let mut page = format!(
+        "# Link check\n\n{} links, {broken} broken\n\n",
+        results.len()
+    );
+
+    let mut sorted: Vec<&CheckResult> = results.iter().collect();
+    sorted.sort_by_key(|result| !result.status.is_broken());
+
+    for result in sorted {
+        let label = match &result.status {
+            LinkStatus::Status(code, meta) if result.status.is_broken() => {
+                format!("[{code} {meta}]")
+            }
+            LinkStatus::Status(code, _) => format!("[{code}]"),
+            LinkStatus::Unreachable(e) => format!("[unreachable: {e}]"),
+            LinkStatus::Skipped => "[not checked]".to_string(),
+        };
+        let name = result.name.as_deref().unwrap_or(result.url.as_str());
+        page.push_str(&format!("=> {} {label} {name}\n", result.url));
+    }
+
+    page
+}
@@ -1,64 +1,340 @@
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::process::Command;
+use std::time::Instant;
 use url::{ParseError, Url};
 
-use crate::errors::{RequestError, ResponseError};
+use crate::cache::{Freshness, PageCache};
+use crate::config::{Config, SelfLink};
+use crate::dns::{DnsCache, IdleTimer};
+use crate::errors::{EditError, RedirectError, RequestError, ResponseError};
+use crate::favicon::Favicons;
 use crate::handlers::get_edit_prompt;
-use crate::models::{Pager, Request, Response};
+use crate::links::{external_link, spoofed_name, ExternalLink};
+use crate::models::{Link, Pager, Passphrase, Request, Response, MAX_META_LENGTH};
+use crate::protocol::{port_of, protocol_for};
+use crate::render::Renderer;
+use crate::tofu::TofuStore;
+use crate::trace::{redacted, Trace};
+
+/// Result of following a link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// Go to this page.
+    Navigate(Url),
+    /// The link was handed to another program, there's nowhere to go.
+    External,
+    /// The link is the current page, and following such links is turned off.
+    Stay,
+}
 
 pub struct Client {
     pub current_url: Url,
     pub redirects: usize,
     pub max_redirects: usize,
     pub history: Vec<Url>,
+    /// Pages left with `go_back`, most recent last.
+    pub forward: Vec<Url>,
     pub last_working_url: Option<Url>,
+    /// URLs carrying status 11 (sensitive) input, never written to disk.
+    pub sensitive_urls: HashSet<Url>,
+    /// Pages loaded successfully this session, without fragment.
+    pub visited: HashSet<Url>,
+    /// Title of each visited page that has a heading.
+    pub titles: HashMap<Url, String>,
+    /// Line of the body of a page where reading stopped to follow one of its links.
+    pub reading_positions: HashMap<Url, usize>,
+    /// The page shown was built by the client, like the history, rather than fetched
+    /// for the current URL.
+    pub synthetic_page: bool,
     pub pager: Pager,
+    pub config: Config,
+    pub renderer: Box<dyn Renderer>,
+    /// Pages are shown without colors or styling, toggled with `T`.
+    pub plain_view: bool,
+    pub favicons: Favicons,
+    pub dns: DnsCache,
+    /// Clears `dns` when no request was made for `config.refresh_interval`.
+    pub idle: IdleTimer,
+    /// Certificates of the hosts seen so far.
+    pub tofu: TofuStore,
+    /// The next request resolves the host again instead of using cached addresses.
+    pub force_reload: bool,
+    /// Hashes of the pages fetched, to tell whether they changed.
+    pub cache: PageCache,
+    /// How the page shown compares with the last visit, `None` for built-in pages.
+    pub freshness: Option<Freshness>,
+    /// Passphrases typed this session, by client certificate.
+    pub passphrases: HashMap<PathBuf, Passphrase>,
+    /// Last input sent to each status 10 prompt, by prompt page without query, until
+    /// the answer succeeds. Status 11 input is never kept.
+    pub last_inputs: HashMap<Url, String>,
+    /// Pages fetched ahead with `O`, without fragment, until they are followed.
+    pub prefetched: HashMap<Url, Response>,
 }
 
 impl Client {
-    pub fn new(url: &Url, pager: Pager) -> Self {
+    pub fn new(url: &Url, pager: Pager, config: Config) -> Self {
         Self {
             current_url: url.clone(),
             redirects: 0,
             max_redirects: 5,
             history: vec![],
+            forward: vec![],
             last_working_url: None,
+            sensitive_urls: HashSet::new(),
+            visited: HashSet::new(),
+            titles: HashMap::new(),
+            reading_positions: HashMap::new(),
+            synthetic_page: false,
             pager,
+            renderer: config.renderer.renderer(
+                config.inline_link_numbers,
+                config.preformatted_overflow,
+                config.dates,
+            ),
+            plain_view: false,
+            idle: IdleTimer::new(config.refresh_interval, Instant::now()),
+            config,
+            favicons: Favicons::default(),
+            dns: DnsCache::default(),
+            tofu: TofuStore::default(),
+            force_reload: false,
+            cache: PageCache::default(),
+            freshness: None,
+            passphrases: HashMap::new(),
+            last_inputs: HashMap::new(),
+            prefetched: HashMap::new(),
         }
     }
 
     pub fn request(&mut self, url: Url) -> Result<Result<Response, ResponseError>, RequestError> {
+        self.prepare_request(url).send()
+    }
+
+    /// Make `url` the current page and build the request for it, to be sent by the caller.
+    pub fn prepare_request(&mut self, url: Url) -> Request {
         self.history.push(url.clone()); // Store URL in history
         self.current_url = url.clone();
-        Request::new(url).send()
+        self.refresh_if_idle(Instant::now());
+        if std::mem::take(&mut self.force_reload) {
+            let host = url.host_str().unwrap_or_default();
+            self.dns.invalidate(host, port_of(&url));
+        }
+        self.build_request(url)
     }
 
-    pub fn click_link(&mut self, link: &str) -> Result<Url, ParseError> {
-        if let Ok(parsed_url) = Url::parse(link) {
-            if parsed_url.scheme() == "gemini" {
-                if parsed_url == self.current_url {
-                    return self
-                        .previous_url()
-                        .cloned()
-                        .ok_or(ParseError::RelativeUrlWithoutBase);
+    /// Forget the resolved addresses of hosts when the client was idle long enough
+    /// for them to have changed. Returns whether it was. Certificates need no
+    /// refresh, they are checked against the known hosts on every request.
+    pub fn refresh_if_idle(&mut self, now: Instant) -> bool {
+        let idle = self.idle.touch(now);
+        if idle {
+            debug!(
+                "Idle for over {:?}, clearing the DNS cache",
+                self.idle.interval
+            );
+            self.dns.clear();
+        }
+        idle
+    }
+
+    /// The request for `url` with the settings of its host, leaving the history as is.
+    pub fn build_request(&self, url: Url) -> Request {
+        let settings = self
+            .config
+            .connection_settings(url.host_str().unwrap_or_default());
+        let trace = Trace {
+            redact_query: self.sensitive_urls.contains(&url),
+        };
+        let passphrase = settings
+            .identity
+            .as_ref()
+            .and_then(|path| self.passphrases.get(path))
+            .cloned();
+        let mut request = Request::new(url)
+            .with_settings(settings)
+            .with_dns_cache(self.dns.clone())
+            .with_tofu(self.tofu.clone(), self.config.tofu);
+        if let Some(passphrase) = passphrase {
+            request = request.with_passphrase(passphrase);
+        }
+        if trace.redact_query {
+            request = request.with_sensitive_query();
+        }
+        if self.config.trace_request {
+            request.with_trace(trace)
+        } else {
+            request
+        }
+    }
+
+    /// Follow `link` from the current page. Links that aren't Gemini, Spartan or `about:`
+    /// pages are opened with the handler configured for their scheme, `xdg-open` by default,
+    /// or shown for `mailto:` links when so configured, and the client stays where it is.
+    pub fn click_link(&mut self, link: &str) -> Result<LinkTarget, ParseError> {
+        let url = self.resolve_link(link)?;
+
+        if protocol_for(url.scheme()).is_none() && url.scheme() != "about" {
+            match external_link(&self.config.handlers, self.config.mailto, &url) {
+                ExternalLink::Launch(command) => {
+                    if let Some((program, args)) = command.split_first() {
+                        if let Err(e) = Command::new(program).args(args).spawn() {
+                            warn!("Could not open {url} with {program}: {e}");
+                        }
+                    }
                 }
-                return Ok(parsed_url);
+                ExternalLink::Show(address) => println!("{address}"),
+            }
+            return Ok(LinkTarget::External);
+        }
+
+        if url == self.current_url {
+            return match self.config.self_link {
+                SelfLink::Reload => Ok(LinkTarget::Navigate(url)),
+                SelfLink::Back => self
+                    .go_back()
+                    .map(LinkTarget::Navigate)
+                    .ok_or(ParseError::RelativeUrlWithoutBase),
+                SelfLink::Ignore => Ok(LinkTarget::Stay),
+            };
+        }
+
+        Ok(LinkTarget::Navigate(url))
+    }
+
+    /// Absolute URL that `link` on the current page points to, without following it.
+    pub fn resolve_link(&self, link: &str) -> Result<Url, ParseError> {
+        if let Ok(parsed_url) = Url::parse(link) {
+            if parsed_url.scheme() == "gemini" && !parsed_url.has_host() {
+                // `gemini:///path` has an empty authority, stay on the current host
+                return Ok(self.same_host_url(&parsed_url));
             }
-            Command::new("xdg-open")
-                .arg(parsed_url.as_str())
-                .spawn()
-                .ok();
-            return Ok(self.current_url.clone());
+            return Ok(parsed_url);
         }
 
-        let url = if link.starts_with("//") {
-            Url::parse(&format!("{}:{}", self.current_url.scheme(), link))?
+        if link.starts_with("//") {
+            Url::parse(&format!("{}:{}", self.current_url.scheme(), link))
         } else if link.starts_with('/') {
-            self.current_url.join(link)?
+            self.current_url.join(link)
+        } else {
+            self.current_url.join(&format!("./{link}"))
+        }
+    }
+
+    /// Absolute URL a redirect from the current page points to. A `<META>` starting
+    /// with a host name, like `example.org/new`, is taken for a Gemini URL missing its
+    /// scheme rather than for a relative path. A redirect must have a target of at
+    /// most `MAX_META_LENGTH` bytes, and can't lead to an `about:` page.
+    pub fn resolve_redirect(&self, meta: &str) -> Result<Url, RedirectError> {
+        let meta = meta.trim();
+        if meta.is_empty() {
+            return Err(RedirectError::Empty);
+        }
+        if meta.len() > MAX_META_LENGTH {
+            return Err(RedirectError::TooLong(meta.len()));
+        }
+
+        // A host name alone is a host when, taken as a path, it would lead back to the
+        // page redirecting
+        let loops = || {
+            is_host_name(meta)
+                && self
+                    .resolve_link(meta)
+                    .is_ok_and(|url| url.path() == self.current_url.path())
+        };
+        let target = if starts_with_host(meta) || loops() {
+            // A host on its own stays with the protocol of the page
+            let scheme = match self.current_url.scheme() {
+                scheme if protocol_for(scheme).is_some() => scheme,
+                _ => "gemini",
+            };
+            Url::parse(&format!("{scheme}://{meta}"))?
         } else {
-            self.current_url.join(&format!("./{link}"))?
+            self.resolve_link(meta)?
         };
+        // Servers don't get to open the pages that change the client's state
+        if target.scheme() == "about" {
+            return Err(RedirectError::ClientPage(target));
+        }
+        Ok(target)
+    }
 
-        Ok(url)
+    /// Whether the current page is the client's own `about:<page>`. Only its links
+    /// change what it lists, links and redirects from elsewhere don't.
+    pub fn on_about_page(&self, page: &str) -> bool {
+        self.current_url.scheme() == "about" && self.current_url.path() == page
+    }
+
+    /// Remember `input` as the last answer sent to the prompt of `url`.
+    pub fn remember_input(&mut self, url: &Url, input: String) {
+        self.last_inputs.insert(prompt_key(url), input);
+    }
+
+    /// The last answer sent to the prompt of `url`, if it didn't succeed.
+    pub fn last_input(&self, url: &Url) -> Option<&str> {
+        self.last_inputs.get(&prompt_key(url)).map(String::as_str)
+    }
+
+    /// Drop the answer kept for the prompt of `url`, it succeeded.
+    pub fn forget_input(&mut self, url: &Url) {
+        self.last_inputs.remove(&prompt_key(url));
+    }
+
+    pub fn mark_visited(&mut self, url: &Url) {
+        let mut url = url.clone();
+        url.set_fragment(None);
+        self.visited.insert(url);
+    }
+
+    /// For each of `links`, whether the page it points to was visited this session.
+    pub fn visited_links(&self, links: &[Link]) -> Vec<bool> {
+        links
+            .iter()
+            .map(|link| {
+                self.resolve_link(&link.href).is_ok_and(|mut url| {
+                    url.set_fragment(None);
+                    self.visited.contains(&url)
+                })
+            })
+            .collect()
+    }
+
+    /// For each of `links`, whether its name shows a URL to somewhere else than the
+    /// link goes, see `spoofed_name`.
+    pub fn spoofed_links(&self, links: &[Link]) -> Vec<bool> {
+        links
+            .iter()
+            .map(|link| {
+                link.name.as_deref().is_some_and(|name| {
+                    self.resolve_link(&link.href)
+                        .is_ok_and(|url| spoofed_name(name, &url))
+                })
+            })
+            .collect()
+    }
+
+    fn same_host_url(&self, url: &Url) -> Url {
+        let mut same_host = self.current_url.clone();
+        same_host.set_path(url.path());
+        same_host.set_query(url.query());
+        same_host.set_fragment(url.fragment());
+        same_host
+    }
+
+    /// Undo `prepare_request` for a request that was cancelled: drop it from the history
+    /// and make `previous` the current page again. Returns the last page that loaded,
+    /// to be requested again, `None` when none did.
+    pub fn cancel_request(&mut self, previous: Url) -> Option<Url> {
+        self.history.pop();
+        self.current_url = previous;
+        let back = self.last_working_url.clone()?;
+        // Pushed again by its request
+        if self.history.last() == Some(&back) {
+            self.history.pop();
+        }
+        Some(back)
     }
 
     pub fn previous_url(&self) -> Option<&Url> {
@@ -73,8 +349,169 @@ impl Client {
         }
     }
 
-    pub fn edit_url(&mut self) -> Option<Url> {
-        get_edit_prompt(self.current_url.as_str())
-            .map(|a| Url::parse(&a).map_err(|_| None::<Url>).unwrap())
+    /// Leave the current page for the one before it, remembering it for `go_forward`.
+    /// `None` on the first page.
+    pub fn go_back(&mut self) -> Option<Url> {
+        if self.history.len() < 2 {
+            return None;
+        }
+        let current = self.history.pop()?;
+        self.forward.push(current);
+        // The previous page is pushed again by its request
+        self.history.pop()
+    }
+
+    /// The page last left with `go_back`, `None` when there is none.
+    pub fn go_forward(&mut self) -> Option<Url> {
+        self.forward.pop()
+    }
+
+    /// `url` as it may be shown, its query hidden if it carries sensitive input.
+    pub fn shown_url(&self, url: &Url) -> Url {
+        if self.sensitive_urls.contains(url) {
+            redacted(url)
+        } else {
+            url.clone()
+        }
+    }
+
+    /// Whether `url` may be listed on a page, not carrying sensitive input.
+    pub fn is_listable(&self, url: &Url) -> bool {
+        !self.sensitive_urls.contains(url)
+    }
+
+    pub fn parent_url(&self) -> Url {
+        parent_url(&self.current_url)
+    }
+
+    pub fn toggle_trailing_slash(&self) -> Url {
+        toggle_trailing_slash(&self.current_url)
     }
+
+    /// Open the URL of the current page in `$EDITOR`, and return the one saved.
+    pub fn edit_url(&mut self) -> Result<Url, EditError> {
+        edited_url(
+            &self.current_url,
+            get_edit_prompt(self.current_url.as_str()),
+        )
+    }
+}
+
+/// `url` without query nor fragment: the prompt page the query answers.
+fn prompt_key(url: &Url) -> Url {
+    let mut key = url.clone();
+    key.set_query(None);
+    key.set_fragment(None);
+    key
+}
+
+/// The URL saved in the editor when editing `current`, `edited` being `None` when the
+/// editor was aborted.
+pub fn edited_url(current: &Url, edited: Option<String>) -> Result<Url, EditError> {
+    let edited = edited.ok_or(EditError::Aborted)?;
+    let edited = edited.trim();
+    if edited.is_empty() {
+        return Err(EditError::Empty);
+    }
+
+    let url = Url::parse(edited)?;
+    if &url == current {
+        Err(EditError::Unchanged)
+    } else {
+        Ok(url)
+    }
+}
+
+/// Parent directory of `url`: `/a/b/c.gmi` goes to `/a/b/` and `/a/b/` goes to `/a/`.
+/// The root is its own parent.
+pub fn parent_url(url: &Url) -> Url {
+    let path = url.path();
+    let trimmed = path.strip_suffix('/').unwrap_or(path);
+    let parent = match trimmed.rfind('/') {
+        Some(index) => &trimmed[..=index],
+        None => "/",
+    };
+
+    let mut parent_url = url.clone();
+    parent_url.set_path(parent);
+    parent_url.set_query(None);
+    parent_url.set_fragment(None);
+    parent_url
+}
+
+/// `/a/b` becomes `/a/b/` and `/a/b/` becomes `/a/b`. The root always keeps its slash.
+pub fn toggle_trailing_slash(url: &Url) -> Url {
+    let path = url.path();
+    let toggled = match path.strip_suffix('/') {
+        Some(stripped) if !stripped.is_empty() => stripped.to_string(),
+        Some(_) => "/".to_string(),
+        None => format!("{path}/"),
+    };
+
+    let mut toggled_url = url.clone();
+    toggled_url.set_path(&toggled);
+    toggled_url
+}
+
+/// Whether `reference` starts with a host name followed by a path, like
+/// `example.org/new`. `page.gmi` or `doc.pdf` could be host names too, without a
+/// path they are taken for files.
+pub fn starts_with_host(reference: &str) -> bool {
+    reference
+        .split_once('/')
+        .is_some_and(|(host, _)| is_host_name(host))
+}
+
+/// Whether `host` is labels separated by dots, ending with an alphabetic one, with an
+/// optional port.
+fn is_host_name(host: &str) -> bool {
+    let host = host.rsplit_once(':').map_or(host, |(host, port)| {
+        if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) {
+            host
+        } else {
+            ""
+        }
+    });
+
+    let Some((labels, tld)) = host.rsplit_once('.') else {
+        return false;
+    };
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    };
+
+    labels.split('.').all(valid_label)
+        && tld.len() >= 2
+        && tld.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+/// Parse a URL typed by the user, assuming `gemini://` when no scheme is given.
+pub fn parse_user_url(input: &str) -> Result<Url, ParseError> {
+    if ["gemini://", "spartan://", "about:"]
+        .iter()
+        .any(|prefix| input.starts_with(prefix))
+    {
+        Url::parse(input)
+    } else {
+        Url::parse(&format!("gemini://{input}"))
+    }
+}
+
+/// The first URL that is set among, in order of precedence: the command line
+/// argument, the `--url-file`, `$GEMINI_URL`, the resumed session and the home page.
+pub fn start_url(
+    argument: Option<String>,
+    file: Option<String>,
+    env: Option<String>,
+    resumed: Option<String>,
+    home: Option<String>,
+) -> Option<String> {
+    [argument, file, env, resumed, home]
+        .into_iter()
+        .flatten()
+        .map(|url| url.trim().to_string())
+        .find(|url| !url.is_empty())
 }
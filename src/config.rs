@@ -0,0 +1,387 @@
+use native_tls::Protocol;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::errors::ConfigError;
+use crate::input::{InputSettings, QueryEncoding};
+use crate::prompt::DEFAULT_PROMPT;
+use crate::render::{DateDisplay, PreformattedOverflow, RendererKind};
+use crate::tofu::TofuPolicy;
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Page opened when no URL is given.
+    pub home: Option<String>,
+    /// Prefix `=>` lines in the rendered body with their link index.
+    pub inline_link_numbers: bool,
+    pub renderer: RendererKind,
+    /// What happens to preformatted lines wider than the terminal.
+    pub preformatted_overflow: PreformattedOverflow,
+    /// How dates starting link names are shown.
+    pub dates: DateDisplay,
+    /// Prefix each line of `text/plain` bodies with its line number.
+    pub line_numbers: bool,
+    /// Render bodies that look like gemtext as gemtext whatever their declared type.
+    pub sniff_gemtext: bool,
+    /// List links below the page several per line, by name only.
+    pub compact_links: bool,
+    /// Most links listed below the page, all of them when unset.
+    pub max_links: Option<usize>,
+    /// Language codes in order of preference, used to pick between language versions.
+    pub languages: Vec<String>,
+    /// Navigate straight to the preferred language version of a page.
+    pub follow_language: bool,
+    /// Fetch each capsule's `favicon.txt` and show it in the prompt.
+    pub favicons: bool,
+    /// Ask for confirmation before quitting with `q`.
+    pub confirm_quit: bool,
+    /// Show a spinner on stderr while a request is in flight.
+    pub spinner: bool,
+    /// Leave out messages about the session, only pages and errors are printed.
+    pub quiet: bool,
+    /// Print the bytes of each request and the start of its response to stderr.
+    pub trace_request: bool,
+    /// Show how long the page took to fetch in the status line.
+    pub timing: bool,
+    /// Start pages with lines meant for screen readers, e.g. their language.
+    pub a11y: bool,
+    /// Offer to update bookmarks of pages that moved permanently (status 31).
+    pub update_bookmarks: bool,
+    /// What to do with the certificate of a host seen for the first time.
+    pub tofu: TofuPolicy,
+    /// Clean up of text typed for status 10 prompts.
+    pub input: InputSettings,
+    /// What following a link to the current page does.
+    pub self_link: SelfLink,
+    /// What following a `mailto:` link does.
+    pub mailto: Mailto,
+    /// Follow redirects to another host in the non-interactive modes.
+    pub allow_cross_host_redirects: bool,
+    /// How many times a request failing with 41 or 43 is retried.
+    pub retries: usize,
+    /// Pause between consecutive automated fetches.
+    pub crawl_delay: Duration,
+    /// Idle time after which resolved host addresses are forgotten, never when unset.
+    pub refresh_interval: Option<Duration>,
+    /// Template of the prompt below pages, see `expand_prompt`.
+    pub prompt: String,
+    /// How many links of the page `O` prefetches, at most `MAX_PREFETCH`.
+    pub prefetch_links: usize,
+    /// Try `index_files` in a directory that was not found (status 51).
+    pub try_index_files: bool,
+    /// File names tried in order by `try_index_files`.
+    pub index_files: Vec<String>,
+    /// Connection settings used for hosts without their own entry in `hosts`.
+    pub connection: ConnectionSettings,
+    /// Per-host overrides of `connection`.
+    pub hosts: HashMap<String, ConnectionSettings>,
+    /// Commands opening links by scheme, e.g. `gopher` to `lynx %u`.
+    pub handlers: HashMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            home: None,
+            inline_link_numbers: true,
+            renderer: RendererKind::Gemtext,
+            preformatted_overflow: PreformattedOverflow::default(),
+            dates: DateDisplay::default(),
+            line_numbers: false,
+            sniff_gemtext: false,
+            compact_links: false,
+            max_links: None,
+            languages: vec![],
+            follow_language: false,
+            favicons: false,
+            confirm_quit: false,
+            spinner: true,
+            quiet: false,
+            trace_request: false,
+            timing: false,
+            a11y: false,
+            update_bookmarks: false,
+            tofu: TofuPolicy::default(),
+            input: InputSettings::default(),
+            self_link: SelfLink::default(),
+            mailto: Mailto::default(),
+            allow_cross_host_redirects: false,
+            retries: 1,
+            crawl_delay: Duration::from_millis(1000),
+            refresh_interval: None,
+            prompt: DEFAULT_PROMPT.to_string(),
+            prefetch_links: 3,
+            try_index_files: false,
+            index_files: vec!["index.gmi".to_string()],
+            connection: ConnectionSettings::default(),
+            hosts: HashMap::new(),
+            handlers: HashMap::new(),
+        }
+    }
+}
+
+/// What following a link to the current page does.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SelfLink {
+    /// Load the page again.
+    #[default]
+    Reload,
+    /// Go back to the previous page.
+    Back,
+    /// Stay on the page without loading it.
+    Ignore,
+}
+
+/// What following a `mailto:` link does.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mailto {
+    /// Open it with the `mailto` handler, the mail client by default.
+    #[default]
+    Open,
+    /// Print the address, to be copied.
+    Show,
+}
+
+impl Config {
+    /// Settings for connecting to `host`: its own entry merged onto the global ones.
+    pub fn connection_settings(&self, host: &str) -> ConnectionSettings {
+        match self.hosts.get(host) {
+            Some(overrides) => self.connection.merged_with(overrides),
+            None => self.connection.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+pub enum TlsVersion {
+    #[serde(rename = "1.0")]
+    Tls10,
+    #[serde(rename = "1.1")]
+    Tls11,
+    #[serde(rename = "1.2")]
+    Tls12,
+    /// Not available on macOS, where connections fail when it is the minimum.
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+impl From<TlsVersion> for Protocol {
+    fn from(version: TlsVersion) -> Self {
+        match version {
+            TlsVersion::Tls10 => Protocol::Tlsv10,
+            TlsVersion::Tls11 => Protocol::Tlsv11,
+            TlsVersion::Tls12 => Protocol::Tlsv12,
+            TlsVersion::Tls13 => Protocol::Tlsv13,
+        }
+    }
+}
+
+/// How to connect to a host. Unset fields fall back to the global settings, then to
+/// the `native_tls` and OS defaults.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConnectionSettings {
+    /// Connect, read and write timeout in seconds.
+    pub timeout: Option<u64>,
+    /// Times resolving the host is tried again when it fails.
+    pub dns_retries: Option<u32>,
+    /// Oldest TLS version to accept.
+    pub min_tls_version: Option<TlsVersion>,
+    /// PKCS#12 client certificate to present.
+    pub identity: Option<PathBuf>,
+    /// Rewrite of the path sent to the server.
+    pub rewrite: Option<PathRewrite>,
+    /// How input typed at a prompt is written in the query.
+    pub query_encoding: Option<QueryEncoding>,
+}
+
+impl ConnectionSettings {
+    /// `self` with every field set in `overrides` replaced.
+    pub fn merged_with(&self, overrides: &ConnectionSettings) -> ConnectionSettings {
+        ConnectionSettings {
+            timeout: overrides.timeout.or(self.timeout),
+            dns_retries: overrides.dns_retries.or(self.dns_retries),
+            min_tls_version: overrides.min_tls_version.or(self.min_tls_version),
+            identity: overrides.identity.clone().or_else(|| self.identity.clone()),
+            rewrite: overrides.rewrite.clone().or_else(|| self.rewrite.clone()),
+            query_encoding: overrides.query_encoding.or(self.query_encoding),
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout.map(Duration::from_secs)
+    }
+}
+
+/// Changes the path of the request sent to the server, leaving the URL shown and
+/// stored untouched. Either `prefix` is prepended, or `pattern` is replaced by
+/// `replacement` (which can use `$1` for capture groups). The pattern is compiled
+/// when the configuration is loaded.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RewriteRule")]
+pub enum PathRewrite {
+    Prefix(String),
+    Replace { pattern: Regex, replacement: String },
+}
+
+/// A `rewrite` table as written in the configuration file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RewriteRule {
+    prefix: Option<String>,
+    pattern: Option<String>,
+    replacement: String,
+}
+
+impl TryFrom<RewriteRule> for PathRewrite {
+    type Error = ConfigError;
+
+    fn try_from(rule: RewriteRule) -> Result<Self, ConfigError> {
+        match (rule.prefix, rule.pattern) {
+            (Some(prefix), None) => Ok(PathRewrite::Prefix(prefix)),
+            (None, Some(pattern)) => Ok(PathRewrite::Replace {
+                pattern: Regex::new(&pattern)?,
+                replacement: rule.replacement,
+            }),
+            (Some(_), Some(_)) => Err(ConfigError::AmbiguousRewrite),
+            (None, None) => Err(ConfigError::EmptyRewrite),
+        }
+    }
+}
+
+impl PartialEq for PathRewrite {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (PathRewrite::Prefix(a), PathRewrite::Prefix(b)) => a == b,
+            (
+                PathRewrite::Replace {
+                    pattern: a,
+                    replacement: a_replacement,
+                },
+                PathRewrite::Replace {
+                    pattern: b,
+                    replacement: b_replacement,
+                },
+            ) => a.as_str() == b.as_str() && a_replacement == b_replacement,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for PathRewrite {}
+
+impl PathRewrite {
+    pub fn apply(&self, path: &str) -> String {
+        match self {
+            PathRewrite::Prefix(prefix) => format!("{}{path}", prefix.trim_end_matches('/')),
+            PathRewrite::Replace {
+                pattern,
+                replacement,
+            } => pattern.replace(path, replacement.as_str()).into_owned(),
+        }
+    }
+}
+
+/// Contents of the TOML configuration file.
+///
+/// ```toml
+/// home = "gemini://geminiprotocol.net/"
+/// update_bookmarks = true
+/// tofu = "prompt"
+/// self_link = "ignore"
+/// mailto = "show"
+/// prefetch_links = 5
+/// prompt = "[{n} links] {url}> "
+/// try_index_files = true
+/// index_files = ["index.gmi", "index.gemini"]
+///
+/// [input]
+/// collapse_whitespace = true
+/// prefill = true
+///
+/// [connection]
+/// timeout = 10
+/// dns_retries = 3
+///
+/// [hosts."slow.example"]
+/// timeout = 60
+/// min_tls_version = "1.2"
+/// identity = "/home/me/.config/gemini/slow.p12"
+///
+/// [hosts."forms.example"]
+/// query_encoding = "form"
+///
+/// [hosts."dev.example".rewrite]
+/// prefix = "/mirror"
+///
+/// [handlers]
+/// gopher = "lynx %u"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub home: Option<String>,
+    pub update_bookmarks: bool,
+    pub tofu: TofuPolicy,
+    pub input: InputSettings,
+    pub self_link: SelfLink,
+    pub mailto: Mailto,
+    pub prompt: Option<String>,
+    pub prefetch_links: Option<usize>,
+    pub try_index_files: bool,
+    pub index_files: Option<Vec<String>>,
+    pub connection: ConnectionSettings,
+    pub hosts: HashMap<String, ConnectionSettings>,
+    pub handlers: HashMap<String, String>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Load the file at `path`, or an empty configuration if it doesn't exist.
+    pub fn load_or_default(path: &Path) -> Result<Self, ConfigError> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn apply(self, config: &mut Config) {
+        config.home = self.home;
+        config.update_bookmarks = self.update_bookmarks;
+        config.tofu = self.tofu;
+        config.input = self.input;
+        config.self_link = self.self_link;
+        config.mailto = self.mailto;
+        if let Some(prompt) = self.prompt {
+            config.prompt = prompt;
+        }
+        if let Some(count) = self.prefetch_links {
+            config.prefetch_links = count;
+        }
+        config.try_index_files = self.try_index_files;
+        if let Some(names) = self.index_files {
+            config.index_files = names;
+        }
+        config.connection = self.connection;
+        config.hosts = self.hosts;
+        config.handlers = self.handlers;
+    }
+}
+
+/// `config.toml` in the XDG config directory.
+pub fn config_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("gemini").join("config.toml"))
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a resolved address is reused before resolving the host again.
+pub const DNS_TTL: Duration = Duration::from_secs(60);
+
+/// Times a failed resolution is tried again when nothing else is configured.
+pub const DNS_RETRIES: u32 = 2;
+
+/// Pause before resolving a host again after a failure.
+pub const DNS_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Whether `error` is the name of the host failing to resolve, often transient on
+/// mobile networks, rather than the host refusing or dropping the connection.
+pub fn is_dns_failure(error: &io::Error) -> bool {
+    // `getaddrinfo` errors have no kind of their own, only their message tells them apart
+    error.kind() == io::ErrorKind::NotFound
+        || error
+            .to_string()
+            .starts_with("failed to lookup address information")
+}
+
+/// Resolved addresses of a host and port, with when they were resolved.
+type Entries = HashMap<(String, u16), (Vec<SocketAddr>, Instant)>;
+
+/// Addresses of recently contacted hosts, shared by the requests of a client so that
+/// following links on the same capsule doesn't resolve its name every time.
+#[derive(Debug, Clone)]
+pub struct DnsCache {
+    entries: Arc<Mutex<Entries>>,
+    ttl: Duration,
+}
+
+impl Default for DnsCache {
+    fn default() -> Self {
+        Self::with_ttl(DNS_TTL)
+    }
+}
+
+impl DnsCache {
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            entries: Arc::default(),
+            ttl,
+        }
+    }
+
+    /// The addresses `host` resolved to, unless they are older than the TTL.
+    pub fn lookup(&self, host: &str, port: u16) -> Option<Vec<SocketAddr>> {
+        let mut entries = self.entries.lock().ok()?;
+        let key = (host.to_string(), port);
+
+        match entries.get(&key) {
+            Some((addrs, resolved)) if resolved.elapsed() < self.ttl => Some(addrs.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, host: &str, port: u16, addrs: Vec<SocketAddr>) {
+        if addrs.is_empty() {
+            return;
+        }
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert((host.to_string(), port), (addrs, Instant::now()));
+        }
+    }
+
+    /// Forget every host.
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries
+            .lock()
+            .map_or(true, |entries| entries.is_empty())
+    }
+
+    /// Forget `host`, e.g. because its cached addresses stopped answering.
+    pub fn invalidate(&self, host: &str, port: u16) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.remove(&(host.to_string(), port));
+        }
+    }
+}
+
+/// Tells when the client has been idle for longer than `interval` since its last
+/// request. Times are given by the caller.
+#[derive(Debug, Clone, Copy)]
+pub struct IdleTimer {
+    /// `None` never counts as idle.
+    pub interval: Option<Duration>,
+    last_activity: Instant,
+}
+
+impl IdleTimer {
+    pub fn new(interval: Option<Duration>, now: Instant) -> Self {
+        Self {
+            interval,
+            last_activity: now,
+        }
+    }
+
+    /// Record activity at `now`, and tell whether the time since the last one was
+    /// longer than the interval.
+    pub fn touch(&mut self, now: Instant) -> bool {
+        let idle = self
+            .interval
+            .is_some_and(|interval| now.saturating_duration_since(self.last_activity) > interval);
+        self.last_activity = now;
+        idle
+    }
+}
@@ -1,6 +1,11 @@
 use native_tls::Error as TlsError;
+use std::io::ErrorKind;
 use std::net::TcpStream;
+use std::path::PathBuf;
 use thiserror::Error;
+use url::Url;
+
+use crate::models::SpecificCode;
 
 #[derive(Debug, Error)]
 pub enum RequestError {
@@ -16,6 +21,30 @@ pub enum RequestError {
     #[error("TLS error: {0}")]
     TlsError(#[from] TlsError),
 
+    #[error("Could not resolve {0}")]
+    UnresolvedHost(String),
+
+    #[error("First connection to {host}, its certificate {fingerprint} needs to be trusted")]
+    UntrustedCertificate { host: String, fingerprint: String },
+
+    #[error("Refusing the certificate of {0}, a host that isn't known yet")]
+    UnknownCertificate(String),
+
+    #[error(
+        "The certificate of {host} changed from {known} to {fingerprint}, it needs to be trusted"
+    )]
+    ChangedCertificate {
+        host: String,
+        known: String,
+        fingerprint: String,
+    },
+
+    #[error("Refusing the certificate of {0}, it isn't the one it was trusted with")]
+    RefusedCertificate(String),
+
+    #[error("Could not unlock the client certificate {}, its passphrase is needed or wrong", .0.display())]
+    LockedIdentity(PathBuf),
+
     #[error("Handshake error: {0}")]
     HandshakeError(#[from] native_tls::HandshakeError<TcpStream>),
 
@@ -23,6 +52,40 @@ pub enum RequestError {
     ResponseParseError(String),
 }
 
+impl RequestError {
+    /// What went wrong in words meant for the user rather than for a developer.
+    pub fn user_message(&self) -> String {
+        match self {
+            RequestError::IoError(e) => {
+                io_error_message(e.kind()).map_or_else(|| self.to_string(), str::to_string)
+            }
+            RequestError::UnresolvedHost(host) => {
+                format!("Could not find {host} — check the address.")
+            }
+            _ => self.to_string(),
+        }
+    }
+}
+
+/// Explanation of the common ways connecting to a capsule fails.
+pub fn io_error_message(kind: ErrorKind) -> Option<&'static str> {
+    match kind {
+        ErrorKind::ConnectionRefused => Some("Connection refused — the capsule may be down."),
+        // Read timeouts are `WouldBlock` on Unix
+        ErrorKind::TimedOut | ErrorKind::WouldBlock => {
+            Some("Connection timed out — the capsule is slow or unreachable.")
+        }
+        ErrorKind::HostUnreachable | ErrorKind::NetworkUnreachable => {
+            Some("Host unreachable — check your network connection.")
+        }
+        ErrorKind::NotFound => Some("Host not found — check the address."),
+        ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted => {
+            Some("Connection closed by the capsule.")
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ResponseError {
     #[error("Response is empty")]
@@ -37,6 +100,12 @@ pub enum ResponseError {
     #[error("Missing meta description in response")]
     MissingMetaDescription,
 
+    #[error("Meta description is {0} bytes, more than the 1024 allowed")]
+    MetaTooLong(usize),
+
+    #[error("Meta description contains a control character")]
+    ControlInMeta,
+
     #[error("Error parsing the body: {0}")]
     BodyParseError(#[from] std::io::Error),
 
@@ -46,3 +115,89 @@ pub enum ResponseError {
     #[error(transparent)]
     Infallible(#[from] std::convert::Infallible),
 }
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Invalid configuration: {0}")]
+    ParseError(#[from] toml::de::Error),
+
+    #[error("Invalid rewrite pattern: {0}")]
+    RewriteError(#[from] regex::Error),
+
+    #[error("A rewrite sets either `prefix` or `pattern`, not both")]
+    AmbiguousRewrite,
+
+    #[error("A rewrite needs a `prefix` or a `pattern`")]
+    EmptyRewrite,
+}
+
+/// Why the target of a redirect (status 3x) can't be followed.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RedirectError {
+    #[error("the server gave no target")]
+    Empty,
+
+    #[error("its target is {0} bytes, more than the 1024 allowed")]
+    TooLong(usize),
+
+    #[error("{0}")]
+    InvalidTarget(#[from] url::ParseError),
+
+    #[error("it leads to {0}, a page of the client")]
+    ClientPage(Url),
+}
+
+/// Why editing the URL of the current page leads nowhere.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EditError {
+    #[error("Editing cancelled, the editor exited with an error")]
+    Aborted,
+
+    #[error("No URL given, staying on this page")]
+    Empty,
+
+    #[error("URL unchanged")]
+    Unchanged,
+
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+}
+
+#[derive(Debug, Error)]
+pub enum HeadlessError {
+    #[error("{}", .0.user_message())]
+    Request(#[from] RequestError),
+
+    #[error(transparent)]
+    Response(#[from] ResponseError),
+
+    #[error("Server answered {0} {label}: {1}", label = SpecificCode::from(*.0).label())]
+    Status(u8, String),
+
+    #[error("More than {0} redirects")]
+    TooManyRedirects(usize),
+
+    #[error("Redirected back to {0}")]
+    RedirectLoop(Url),
+
+    #[error("Refusing the redirect to another host: {0}")]
+    CrossHostRedirect(Url),
+
+    #[error("Invalid redirect: {0}")]
+    InvalidRedirect(#[from] RedirectError),
+
+    #[error("No link {index}, the page has {count} links")]
+    LinkOutOfRange { index: usize, count: usize },
+
+    #[error("Link {index} is not a Gemini or Spartan link: {url}")]
+    UnfetchableLink { index: usize, url: Url },
+
+    #[error("Invalid link: {0}")]
+    InvalidLink(#[from] url::ParseError),
+
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+}
@@ -0,0 +1,71 @@
+use log::debug;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use unicode_segmentation::UnicodeSegmentation;
+use url::Url;
+
+use crate::models::{Request, StatusCode};
+
+/// Per-host `favicon.txt` emojis, fetched in the background the first time a host is visited.
+#[derive(Default, Clone)]
+pub struct Favicons {
+    icons: Arc<Mutex<HashMap<String, Option<String>>>>,
+}
+
+impl Favicons {
+    /// The favicon of `host`, if it has been fetched and is valid.
+    pub fn get(&self, host: &str) -> Option<String> {
+        self.icons.lock().ok()?.get(host).cloned().flatten()
+    }
+
+    /// Fetch the favicon of the host of `url` unless it was already requested,
+    /// with the request `build_request` makes for the favicon's URL, see
+    /// `Client::build_request`. Failures are silent, the host just has no favicon.
+    pub fn fetch(&self, url: &Url, build_request: impl FnOnce(Url) -> Request) {
+        let Some(host) = url.host_str().map(str::to_string) else {
+            return;
+        };
+
+        let mut favicon_url = url.clone();
+        favicon_url.set_path("/favicon.txt");
+        favicon_url.set_query(None);
+        favicon_url.set_fragment(None);
+
+        {
+            let Ok(mut icons) = self.icons.lock() else {
+                return;
+            };
+            if icons.contains_key(&host) {
+                return;
+            }
+            icons.insert(host.clone(), None);
+        }
+
+        let request = build_request(favicon_url);
+        let icons = Arc::clone(&self.icons);
+        thread::spawn(move || {
+            let icon = match request.send() {
+                Ok(Ok(response)) if matches!(response.status_code, StatusCode::Success) => {
+                    response.body.as_deref().and_then(parse_favicon)
+                }
+                _ => None,
+            };
+            debug!("Favicon for {host}: {icon:?}");
+            if let Ok(mut icons) = icons.lock() {
+                icons.insert(host, icon);
+            }
+        });
+    }
+}
+
+/// A `favicon.txt` body is valid when it holds a single grapheme, such as one emoji.
+pub fn parse_favicon(body: &str) -> Option<String> {
+    let icon = body.trim();
+
+    if icon.graphemes(true).count() == 1 {
+        Some(icon.to_string())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,110 @@
+use chrono::{DateTime, NaiveDate};
+use roxmltree::{Document, Node};
+
+/// MIME types of the Atom and RSS feeds shown as lists of links.
+pub const FEED_MIME_TYPES: [&str; 2] = ["application/atom+xml", "application/rss+xml"];
+
+/// An Atom or RSS feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed {
+    pub title: Option<String>,
+    pub entries: Vec<FeedEntry>,
+}
+
+/// An Atom `<entry>` or RSS `<item>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedEntry {
+    pub title: String,
+    pub link: String,
+    pub updated: Option<NaiveDate>,
+}
+
+/// Parse an Atom or RSS document. Entries without a link are skipped.
+pub fn parse_feed(xml: &str) -> Option<Feed> {
+    let document = Document::parse(xml).ok()?;
+    let root = document.root_element();
+
+    match root.tag_name().name() {
+        "feed" => Some(Feed {
+            title: child_text(root, "title"),
+            entries: children(root, "entry").filter_map(atom_entry).collect(),
+        }),
+        "rss" => {
+            let channel = children(root, "channel").next()?;
+            Some(Feed {
+                title: child_text(channel, "title"),
+                entries: children(channel, "item").filter_map(rss_item).collect(),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn atom_entry(entry: Node) -> Option<FeedEntry> {
+    // The `alternate` link is the entry itself, it is the default relation
+    let link = children(entry, "link")
+        .find(|link| link.attribute("rel").unwrap_or("alternate") == "alternate")
+        .and_then(|link| link.attribute("href"))?;
+    let updated = child_text(entry, "updated")
+        .or_else(|| child_text(entry, "published"))
+        .and_then(|date| NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok());
+
+    Some(FeedEntry {
+        title: child_text(entry, "title").unwrap_or_else(|| link.to_string()),
+        link: link.to_string(),
+        updated,
+    })
+}
+
+fn rss_item(item: Node) -> Option<FeedEntry> {
+    let link = child_text(item, "link")?;
+    let updated = child_text(item, "pubDate")
+        .and_then(|date| DateTime::parse_from_rfc2822(&date).ok())
+        .map(|date| date.date_naive());
+
+    Some(FeedEntry {
+        title: child_text(item, "title").unwrap_or_else(|| link.clone()),
+        link,
+        updated,
+    })
+}
+
+/// Child elements of `node` named `name`, whatever their namespace.
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// Trimmed text of the first child element of `node` named `name`, `None` if empty.
+fn child_text(node: Node, name: &'static str) -> Option<String> {
+    let text: String = children(node, name)
+        .next()?
+        .descendants()
+        .filter(Node::is_text)
+        .filter_map(|text| text.text())
+        .collect();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// `feed` as a gemtext feed: a link per entry, named by its date and title.
+pub fn feed_gemtext(feed: &Feed) -> String {
+    let mut page = format!("# {}\n\n", feed.title.as_deref().unwrap_or("Feed"));
+
+    for entry in &feed.entries {
+        match entry.updated {
+            Some(date) => page.push_str(&format!(
+                "=> {} {} {}\n",
+                entry.link,
+                date.format("%Y-%m-%d"),
+                entry.title
+            )),
+            None => page.push_str(&format!("=> {} {}\n", entry.link, entry.title)),
+        }
+    }
+
+    page
+}
@@ -1,34 +1,102 @@
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
 use colored::Colorize;
 use crossterm::{
-    cursor, execute,
-    terminal::{Clear, ClearType},
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{self, Clear, ClearType},
 };
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use rpassword::read_password;
 use rustyline::{self, history::MemHistory, Config};
 use std::fs;
-use std::io::{self, stdout, Write};
+use std::io::{self, stdout, IsTerminal, Write};
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
 use tempfile::NamedTempFile;
 use url::Url;
 
-use crate::client::Client;
-use crate::models::{Pager, Response, StatusCode};
+use crate::bookmarks::{bookmarks_path, moves_bookmark, Bookmarks, BOOKMARKS_URL};
+use crate::cache::Freshness;
+use crate::checker::{check_links, report_page, CHECK_WORKERS};
+use crate::client::{parse_user_url, Client, LinkTarget};
+use crate::errors::{EditError, RequestError, ResponseError};
+use crate::index::index_candidates;
+use crate::input::{encode_query, prefill_value, sanitize_input, InputSettings};
+use crate::lang::{language_preamble, preferred_language_link};
+use crate::links::{best_link_match, link_summary};
+use crate::models::{
+    CancelHandle, Link, Pager, Passphrase, Request, Response, SpecificCode, StatusCode,
+};
+use crate::output::notice;
+use crate::pager;
+use crate::pages::{bookmarks_page, certs_page, history_page, link_list, site_map_page};
+use crate::prefetch::{prefetch, prefetch_targets, take_prefetched, CONFIRM_PREFETCH};
+use crate::prompt::{expand_prompt, PromptState};
+use crate::protocol::protocol_for;
+use crate::render::{
+    compact_link_footer, link_footer, number_lines, strip_ansi, truncate_to_width, PlainRenderer,
+    Renderer,
+};
+use crate::spinner::Spinner;
+use crate::stats::{format_reading_stats, reading_stats};
+use crate::trace::reproduce_command;
 
 pub fn handle_request(client: &mut Client, url: &Url) -> Option<Url> {
-    match client.request(url.clone()) {
+    if url.scheme() == "about" {
+        return handle_about(client, url);
+    }
+
+    let previous = client.current_url.clone();
+    let mut result = match take_prefetched(client, url) {
+        Some(response) if !client.force_reload => {
+            debug!("Showing prefetched {url}");
+            client.history.push(url.clone());
+            client.current_url = url.clone();
+            Ok(Ok(response))
+        }
+        _ => match send_request(client, url) {
+            Some(result) => result,
+            None => {
+                info!("Request cancelled");
+                return client
+                    .cancel_request(previous)
+                    .or_else(|| prompt_for_url(client));
+            }
+        },
+    };
+
+    if let Ok(Ok(response)) = &mut result {
+        if client.config.sniff_gemtext {
+            response.sniff_gemtext();
+        }
+    }
+
+    match result {
         Ok(Ok(response)) => match response.status_code {
             StatusCode::Input => handle_input(client, &response, url),
             StatusCode::Success => handle_success(client, &response, url),
             StatusCode::Redirect => handle_redirect(client, &response, url),
+            StatusCode::PermanentFailure
+                if client.config.try_index_files
+                    && response.specific_code() == SpecificCode::NotFound =>
+            {
+                find_index_file(client, url).or_else(|| {
+                    error!("{}", response.status_summary());
+                    None
+                })
+            }
             StatusCode::TemporaryFailure
             | StatusCode::PermanentFailure
             | StatusCode::ClientCertificateRequired => {
-                error!("{:?}: {}", response.status_code, response.meta_description);
+                error!("{}", response.status_summary());
                 None
             }
-            StatusCode::Unknown(code) => {
-                error!("INVALID STATUS CODE {code}");
+            StatusCode::Unknown(_) => {
+                error!("Invalid status: {}", response.status_summary());
                 None
             }
         },
@@ -36,54 +104,600 @@ pub fn handle_request(client: &mut Client, url: &Url) -> Option<Url> {
             error!("Response Error: {e:?}");
             None
         }
+        Err(RequestError::UntrustedCertificate { host, fingerprint }) => {
+            println!("First connection to {host}, its certificate fingerprint is\n{fingerprint}");
+            trust_certificate(client, url, &host, &fingerprint)
+        }
+        Err(RequestError::ChangedCertificate {
+            host,
+            known,
+            fingerprint,
+        }) => {
+            println!(
+                "The certificate of {host} changed, its fingerprint was\n{known}\nand is now\n{fingerprint}"
+            );
+            trust_certificate(client, url, &host, &fingerprint)
+        }
+        Err(RequestError::LockedIdentity(path)) => unlock_identity(client, url, path),
         Err(e) => {
-            error!("Request Error: {e:?}");
+            debug!("Request Error: {e:?}");
+            error!("{}", e.user_message());
             None
         }
     }
 }
 
+/// Look for an index file in the directory `url`, which was not found, and open the
+/// first of `config.index_files` that is there.
+fn find_index_file(client: &mut Client, url: &Url) -> Option<Url> {
+    for candidate in index_candidates(url, &client.config.index_files) {
+        debug!("Trying {candidate}");
+        match client.build_request(candidate.clone()).send() {
+            Ok(Ok(response)) if matches!(response.status_code, StatusCode::Success) => {
+                notice(
+                    client.config.quiet,
+                    format!("{url} was not found, opening {candidate}"),
+                );
+                // `url` isn't a page, the candidate is shown in its place
+                client.history.pop();
+                client.prefetched.insert(candidate.clone(), response);
+                return Some(candidate);
+            }
+            Ok(Ok(response)) => debug!("{candidate}: {}", response.status_summary()),
+            Ok(Err(e)) => debug!("{candidate}: {e}"),
+            Err(e) => debug!("{candidate}: {e}"),
+        }
+    }
+    None
+}
+
+/// Ask whether to trust the certificate of `host`, new or changed, and load `url` again if so.
+fn trust_certificate(client: &mut Client, url: &Url, host: &str, fingerprint: &str) -> Option<Url> {
+    if !confirm("Trust this certificate? [y/N] ") {
+        return client.last_working_url.clone();
+    }
+
+    client.tofu.trust(host, fingerprint);
+    // The request is made again, and pushes the page again
+    client.history.pop();
+    Some(url.clone())
+}
+
+/// Ask for the passphrase of the client certificate at `path`, and load `url` again
+/// with it. Typing nothing gives up.
+fn unlock_identity(client: &mut Client, url: &Url, path: PathBuf) -> Option<Url> {
+    let prompt = format!("Passphrase for {}: ", path.display());
+    let Some(passphrase) = get_secure_user_input(&prompt).filter(|p| !p.is_empty()) else {
+        error!("Could not unlock the client certificate {}", path.display());
+        return client.last_working_url.clone();
+    };
+
+    client.passphrases.insert(path, Passphrase(passphrase));
+    // The request is made again, and pushes the page again
+    client.history.pop();
+    Some(url.clone())
+}
+
+/// Pages built by the client itself rather than fetched.
+fn handle_about(client: &mut Client, url: &Url) -> Option<Url> {
+    match url.path() {
+        "bookmarks" => show_bookmarks(client, url),
+        "certs" => show_certs(client, url),
+        _ => {
+            error!("No such page: {url}");
+            client.last_working_url.clone()
+        }
+    }
+}
+
+/// `about:bookmarks`, removing the bookmark given as `?remove=URL` first when coming
+/// from `about:bookmarks` itself.
+fn show_bookmarks(client: &mut Client, url: &Url) -> Option<Url> {
+    let Some(path) = bookmarks_path() else {
+        error!("Could not find a directory for bookmarks");
+        return client.last_working_url.clone();
+    };
+    let mut bookmarks = match Bookmarks::load_or_default(&path) {
+        Ok(bookmarks) => bookmarks,
+        Err(e) => {
+            error!("Could not load bookmarks from {}: {e}", path.display());
+            return client.last_working_url.clone();
+        }
+    };
+
+    let removing = client.on_about_page("bookmarks");
+    let removed = url
+        .query_pairs()
+        .filter(|(key, _)| key == "remove")
+        .filter_map(|(_, value)| Url::parse(&value).ok())
+        .filter(|removed| {
+            if !removing {
+                warn!(
+                    "Not removing the bookmark of {removed}, the link isn't from about:bookmarks"
+                );
+            }
+            removing && bookmarks.remove(removed)
+        })
+        .count();
+    if removed > 0 {
+        if let Err(e) = bookmarks.save(&path) {
+            error!("Could not save bookmarks to {}: {e}", path.display());
+        }
+    }
+
+    let mut page_url = url.clone();
+    page_url.set_query(None);
+    client.history.push(page_url.clone());
+    client.current_url = page_url.clone();
+    client.last_working_url = Some(page_url.clone());
+
+    show_synthetic_page(client, &bookmarks_page(&bookmarks.entries), &page_url)
+}
+
+/// `about:certs`, forgetting the certificates of the hosts given as `?forget=HOST`
+/// first when coming from `about:certs` itself.
+fn show_certs(client: &mut Client, url: &Url) -> Option<Url> {
+    let forgetting = client.on_about_page("certs");
+    for (_, key) in url.query_pairs().filter(|(key, _)| key == "forget") {
+        if forgetting {
+            client.tofu.forget(&key);
+        } else {
+            warn!("Not forgetting the certificate of {key}, the link isn't from about:certs");
+        }
+    }
+
+    let mut page_url = url.clone();
+    page_url.set_query(None);
+    client.history.push(page_url.clone());
+    client.current_url = page_url.clone();
+    client.last_working_url = Some(page_url.clone());
+
+    show_synthetic_page(client, &certs_page(&client.tofu.entries()), &page_url)
+}
+
+/// Ask for a file and write the links of `response` to it as gemtext.
+fn export_links(client: &Client, response: &Response) {
+    let Some(path) = get_user_input("Save links to: ").filter(|path| !path.trim().is_empty())
+    else {
+        return;
+    };
+    let path = path.trim();
+
+    let list = link_list(&response.links, |href| client.resolve_link(href));
+    match fs::write(path, &list) {
+        Ok(()) => notice(
+            client.config.quiet,
+            format!("Saved {} links to {path}", list.lines().count()),
+        ),
+        Err(e) => error!("Could not save links to {path}: {e}"),
+    }
+}
+
+/// Fetch the first `config.prefetch_links` links of `response` ahead, asking first
+/// when they are many.
+fn prefetch_links(client: &mut Client, response: &Response) {
+    let targets = prefetch_targets(&response.links, client.config.prefetch_links, |href| {
+        client.resolve_link(href)
+    });
+    if targets.is_empty() {
+        notice(client.config.quiet, "No links to prefetch");
+        return;
+    }
+    if targets.len() > CONFIRM_PREFETCH
+        && !confirm(&format!("Prefetch {} links? [y/N] ", targets.len()))
+    {
+        return;
+    }
+
+    let fetched = prefetch(client, &targets);
+    notice(
+        client.config.quiet,
+        format!("Prefetched {fetched} of {} links", targets.len()),
+    );
+}
+
+/// Print a command sending the request for `url` without the client, to report a
+/// problem with the capsule. Sensitive input is left out.
+fn print_reproduce_command(client: &Client, url: &Url) {
+    let Some(protocol) = protocol_for(url.scheme()) else {
+        notice(client.config.quiet, "This page is built by the client");
+        return;
+    };
+    let shown = client.shown_url(url);
+
+    let settings = client
+        .config
+        .connection_settings(url.host_str().unwrap_or_default());
+    println!("{}", reproduce_command(protocol, &shown, &settings));
+}
+
+/// Bookmark the current page, under its title if it has one.
+fn add_bookmark(client: &Client, url: &Url) {
+    let Some(path) = bookmarks_path() else {
+        error!("Could not find a directory for bookmarks");
+        return;
+    };
+
+    let result = Bookmarks::load_or_default(&path).and_then(|mut bookmarks| {
+        let added = bookmarks.add(url.clone(), client.titles.get(url).cloned());
+        bookmarks.save(&path).map(|()| added)
+    });
+
+    match result {
+        Ok(true) => notice(client.config.quiet, format!("Bookmarked {url}")),
+        Ok(false) => notice(client.config.quiet, format!("{url} is already bookmarked")),
+        Err(e) => error!("Could not save bookmarks to {}: {e}", path.display()),
+    }
+}
+
+type RequestResult = Result<Result<Response, ResponseError>, RequestError>;
+
+/// Delay before retrying a request that failed with a transient status.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Request `url` with a spinner, retrying transient failures up to `config.retries`
+/// times. Returns `None` when the user cancelled the request.
+fn send_request(client: &mut Client, url: &Url) -> Option<RequestResult> {
+    let mut spinner = if client.config.spinner && io::stderr().is_terminal() {
+        Spinner::start(&format!("Loading {}", client.shown_url(url)))
+    } else {
+        Spinner::disabled()
+    };
+    let request = client.prepare_request(url.clone());
+
+    let mut attempts = 0;
+    loop {
+        let result = if io::stdin().is_terminal() {
+            send_cancellable(request.clone())?
+        } else {
+            request.clone().send()
+        };
+
+        match &result {
+            Ok(Ok(response))
+                if response.specific_code().is_retryable() && attempts < client.config.retries =>
+            {
+                attempts += 1;
+                warn!(
+                    "{:?}: {}, retrying in {}s ({attempts}/{})",
+                    response.specific_code(),
+                    response.meta_description,
+                    RETRY_DELAY.as_secs(),
+                    client.config.retries
+                );
+                thread::sleep(RETRY_DELAY);
+            }
+            _ => {
+                spinner.stop();
+                return Some(result);
+            }
+        }
+    }
+}
+
+/// Ask for a page to open when there is no page to go back to.
+fn prompt_for_url(client: &Client) -> Option<Url> {
+    loop {
+        match get_user_input("Type a URL to open ([q]uit): ")?.trim() {
+            "" => {}
+            "q" => {
+                notice(client.config.quiet, "Goodbye!");
+                return None;
+            }
+            input => match parse_user_url(input) {
+                Ok(url) => return Some(url),
+                Err(e) => error!("Could not parse URL: {e}"),
+            },
+        }
+    }
+}
+
+/// Send `request` on another thread, aborting it if `q` or Ctrl-C is pressed.
+/// Returns `None` when the request was cancelled.
+fn send_cancellable(request: Request) -> Option<RequestResult> {
+    let cancel = CancelHandle::default();
+    let worker = {
+        let request = request.with_cancel_handle(cancel.clone());
+        thread::spawn(move || request.send())
+    };
+
+    let raw_mode = terminal::enable_raw_mode().is_ok();
+    while !worker.is_finished() {
+        if cancel_key_pressed() {
+            cancel.cancel();
+            break;
+        }
+    }
+    if raw_mode {
+        terminal::disable_raw_mode().ok();
+    }
+
+    if cancel.is_cancelled() {
+        // The worker ends on its own now that its connection is shut down
+        None
+    } else {
+        worker.join().ok()
+    }
+}
+
+fn cancel_key_pressed() -> bool {
+    if !event::poll(Duration::from_millis(50)).unwrap_or(false) {
+        return false;
+    }
+
+    matches!(
+        event::read(),
+        Ok(Event::Key(KeyEvent {
+            code: KeyCode::Char('q'),
+            kind: KeyEventKind::Press,
+            ..
+        })) | Ok(Event::Key(KeyEvent {
+            code: KeyCode::Char('c'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: KeyEventKind::Press,
+            ..
+        }))
+    )
+}
+
 pub fn handle_success(client: &mut Client, response: &Response, url: &Url) -> Option<Url> {
     debug!("Success!");
     client.last_working_url = Some(url.clone());
     client.redirects = 0;
+    client.mark_visited(url);
+    client.forget_input(url);
 
-    let mut pager = spawn_pager(client.pager).expect("Failed to spawn pager");
+    let preferred_link = preferred_language_link(&response.links, &client.config.languages);
 
-    if let Some(stdin) = pager.stdin.as_mut() {
-        stdin
-            .write_all(response.body.as_deref().unwrap_or("No content").as_bytes())
-            .expect("Failed to write to pager stdin");
-        writeln!(stdin, "\n").expect("Failed to write to pager"); // 2 new lines
-        for (i, link) in response.links.iter().enumerate() {
-            writeln!(
-                stdin,
-                "{}: {} ({})",
-                i.to_string().blue(),
-                link.name.as_deref().unwrap_or("").bright_white(),
-                link.href.blue()
-            )
-            .expect("Failed to write links to pager stdin");
+    if client.config.follow_language {
+        if let Some(target) = preferred_link
+            .and_then(|index| client.resolve_link(&response.links[index].href).ok())
+            .filter(|target| target.scheme() == "gemini")
+            .filter(|target| !client.history.contains(target))
+        {
+            info!("Following preferred language version {target}");
+            return Some(target);
         }
     }
 
-    pager.wait().expect("Error waiting for pager");
+    if client.config.favicons {
+        client
+            .favicons
+            .fetch(url, |favicon_url| client.build_request(favicon_url));
+    }
+
+    if let Some(title) = response.title() {
+        client.titles.insert(url.clone(), title);
+    }
+
+    client.freshness = response
+        .body
+        .as_deref()
+        .map(|body| client.cache.record(url, body));
+
+    show_page(client, response, url)
+}
+
+/// Display `response` in the pager and prompt for where to go next. A page left by
+/// following one of its links opens again where that link was.
+pub fn show_page(client: &mut Client, response: &Response, url: &Url) -> Option<Url> {
+    let start_line = reading_position(client, response, url);
+    show_page_at(client, response, url, start_line)
+}
+
+/// Remember that reading `url` stopped at link `index` of `response`. Lines of a page
+/// built by the client aren't lines of the page at `url`, and aren't remembered.
+pub fn save_reading_position(client: &mut Client, response: &Response, url: &Url, index: usize) {
+    if client.synthetic_page {
+        return;
+    }
+    if let Some(line) = response.link_line(index) {
+        client.reading_positions.insert(url.clone(), line);
+    }
+}
+
+/// The line of the rendered page where reading `url` stopped, forgotten once
+/// returned to. Pages built by the client start at the top and leave it as is.
+pub fn reading_position(client: &mut Client, response: &Response, url: &Url) -> Option<usize> {
+    if client.synthetic_page {
+        return None;
+    }
+    let line = client.reading_positions.remove(url)?;
+    Some(shown_line(client, response, line))
+}
+
+/// Line of the page as shown showing `line` of the body, both 1-based. The plain view
+/// shows the body as is, below the accessibility preamble if any.
+pub fn shown_line(client: &Client, response: &Response, line: usize) -> usize {
+    let line = if client.plain_view || !response.is_markup() {
+        line
+    } else {
+        client.renderer.rendered_line(response, line)
+    };
+    // The preamble is followed by a blank line
+    a11y_preamble(client, response).map_or(line, |preamble| line + preamble.lines().count() + 1)
+}
+
+/// The lines starting the page in accessibility mode.
+fn a11y_preamble(client: &Client, response: &Response) -> Option<String> {
+    language_preamble(response).filter(|_| client.config.a11y)
+}
+
+/// The lines listing the links of `response` below the page, at most
+/// `config.max_links` of them.
+pub fn footer_lines(
+    client: &Client,
+    response: &Response,
+    preferred_link: Option<usize>,
+) -> Vec<String> {
+    let shown = client
+        .config
+        .max_links
+        .map_or(response.links.len(), |max| max.min(response.links.len()));
+    // Links past `shown` are left out of the list but can still be selected
+    let links = &response.links[..shown];
+    let visited = client.visited_links(links);
+    let spoofed = client.spoofed_links(links);
+    let mut lines = if client.config.compact_links {
+        let width = terminal::size().map_or(80, |(columns, _)| usize::from(columns));
+        compact_link_footer(links, preferred_link, &visited, &spoofed, width)
+    } else {
+        link_footer(links, preferred_link, &visited, &spoofed)
+    };
+    if shown < response.links.len() {
+        let more = format!("…and {} more", response.links.len() - shown);
+        lines.push(more.dimmed().to_string());
+    }
+    lines
+}
+
+/// `show_page`, with the pager opened at `start_line` of the rendered page when it
+/// supports it.
+fn show_page_at(
+    client: &mut Client,
+    response: &Response,
+    url: &Url,
+    start_line: Option<usize>,
+) -> Option<Url> {
+    let preferred_link = preferred_language_link(&response.links, &client.config.languages);
+
+    let plain_text = response
+        .mime()
+        .is_some_and(|mime| mime.essence == "text/plain");
+    let body = match response.body.as_deref() {
+        Some(body) if client.config.line_numbers && plain_text => number_lines(body),
+        Some(body) if !response.is_markup() => body.to_string(),
+        _ if client.plain_view => PlainRenderer {
+            inline_link_numbers: true,
+        }
+        .render(response, url),
+        _ => client.renderer.render(response, url),
+    };
+
+    let mut page = match a11y_preamble(client, response) {
+        Some(preamble) => format!("{preamble}\n\n{body}"),
+        None => body,
+    };
+    if response.truncated {
+        page.push_str(&format!("\n\n{}", "⚠ response truncated".yellow().bold()));
+    }
+    page.push_str("\n\n");
+    for line in footer_lines(client, response, preferred_link) {
+        page.push_str(&line);
+        page.push('\n');
+    }
+    if !client.pager.renders_ansi() || client.plain_view {
+        page = strip_ansi(&page);
+    }
+
+    display_page(client.pager, &page, start_line);
 
     let mut stdout = stdout();
 
-    execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 1)).unwrap();
+    // The terminal may have been resized while paging
+    let width = terminal::size().map_or(80, |(columns, _)| usize::from(columns));
+    execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0)).unwrap();
+    notice(
+        client.config.quiet,
+        status_line(client, response, url, width).dimmed(),
+    );
 
     get_client_prompt(client, response, url)
 }
 
-fn spawn_pager(pager: Pager) -> std::io::Result<std::process::Child> {
-    let mut cmd = match pager {
+/// One line summary of the page shown above the prompt, cut to `width` columns so
+/// that it doesn't wrap.
+pub fn status_line(client: &Client, response: &Response, url: &Url, width: usize) -> String {
+    let mut status = client.shown_url(url).to_string();
+
+    let is_text = response
+        .mime()
+        .is_some_and(|mime| mime.essence.starts_with("text/"));
+    if let Some(body) = response.body.as_deref().filter(|_| is_text) {
+        let (words, time) = reading_stats(body);
+        status.push_str(&format!(" — {}", format_reading_stats(words, time)));
+    }
+    if let Some(summary) = link_summary(&response.links, |href| client.resolve_link(href)) {
+        status.push_str(&format!(" — {summary}"));
+    }
+    if response.truncated {
+        status.push_str(" — truncated");
+    }
+    match client.freshness {
+        Some(Freshness::Unchanged(fetched)) => {
+            let date = i64::try_from(fetched)
+                .ok()
+                .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0));
+            match date {
+                Some(date) => status.push_str(&format!(
+                    " — unchanged since last visit on {}",
+                    date.format("%Y-%m-%d")
+                )),
+                None => status.push_str(" — unchanged since last visit"),
+            }
+        }
+        Some(Freshness::Changed) => status.push_str(" — changed since last visit"),
+        Some(Freshness::New) | None => {}
+    }
+    if let Some(connection) = response.connection.filter(|_| client.config.timing) {
+        status.push_str(&format!(" — {connection}"));
+    }
+
+    truncate_to_width(&status, width)
+}
+
+/// Write `page` to the stdin of a pager. A pager quit before reading all of it closes
+/// its stdin, which ends the page as well.
+pub fn write_page(mut stdin: impl Write, page: &str) -> io::Result<()> {
+    match stdin.write_all(page.as_bytes()) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+/// Show `page` in `pager`, or in the internal pager when `pager` can't be started.
+fn display_page(pager: Pager, page: &str, start_line: Option<usize>) {
+    if let Some(mut command) = pager_command(pager, start_line) {
+        match command.stdin(Stdio::piped()).spawn() {
+            Ok(mut child) => {
+                pager::set_external_pager_running(true);
+                if let Some(stdin) = child.stdin.as_mut() {
+                    if let Err(e) = write_page(stdin, page) {
+                        warn!("Could not write the page to {pager:?}: {e}");
+                    }
+                }
+                child.wait().expect("Error waiting for pager");
+                pager::set_external_pager_running(false);
+                return;
+            }
+            Err(e) => warn!("Could not start {pager:?}, using the internal pager: {e}"),
+        }
+    }
+
+    if let Err(e) = pager::run(page, start_line) {
+        error!("Internal pager failed: {e}");
+    }
+}
+
+/// The command running `pager`, `None` for the internal one.
+fn pager_command(pager: Pager, start_line: Option<usize>) -> Option<Command> {
+    let cmd = match pager {
         Pager::Less => {
             let mut c = Command::new("less");
             c.arg("-R");
+            if let Some(line) = start_line {
+                c.arg(format!("+{line}g"));
+            }
             c
         }
-        Pager::More => Command::new("more"),
+        Pager::More => {
+            let mut c = Command::new("more");
+            if let Some(line) = start_line {
+                c.arg(format!("+{line}"));
+            }
+            c
+        }
+        // bat hands the page to its own pager, which can't be told where to start
         Pager::Bat => {
             let mut c = Command::new("bat");
             c.arg("--paging=always");
@@ -93,15 +707,24 @@ fn spawn_pager(pager: Pager) -> std::io::Result<std::process::Child> {
         Pager::Neovim => {
             let mut c = Command::new("nvim");
             c.arg("+Man!");
+            if let Some(line) = start_line {
+                c.arg(format!("+{line}"));
+            }
             c
         }
+        Pager::Internal => return None,
     };
 
-    cmd.stdin(Stdio::piped()).spawn()
+    Some(cmd)
 }
 
-pub fn handle_redirect(client: &mut Client, response: &Response, _url: &Url) -> Option<Url> {
-    info!("Redirecting to {}", response.meta_description);
+pub fn handle_redirect(client: &mut Client, response: &Response, url: &Url) -> Option<Url> {
+    match response.specific_code() {
+        SpecificCode::PermanentRedirect => {
+            info!("Permanently redirecting to {}", response.meta_description);
+        }
+        _ => info!("Redirecting to {}", response.meta_description),
+    }
     client.redirects += 1;
 
     if client.redirects >= client.max_redirects {
@@ -109,79 +732,448 @@ pub fn handle_redirect(client: &mut Client, response: &Response, _url: &Url) ->
         return client.last_working_url.clone();
     }
 
-    match client.click_link(&response.meta_description) {
-        Ok(new_url) => Some(new_url),
+    let target = client
+        .resolve_redirect(&response.meta_description)
+        .and_then(|target| Ok(client.click_link(target.as_str())?));
+
+    match target {
+        Ok(LinkTarget::Navigate(new_url)) => {
+            if client.config.update_bookmarks {
+                offer_bookmark_update(response.specific_code(), url, &new_url);
+            }
+            Some(new_url)
+        }
+        Ok(LinkTarget::External | LinkTarget::Stay) => client.last_working_url.clone(),
         Err(e) => {
-            error!("Error parsing redirect: {e}");
+            error!("Invalid redirect: {e}");
             None
         }
     }
 }
 
-pub fn handle_input(client: &mut Client, response: &Response, _url: &Url) -> Option<Url> {
+/// Ask whether to move the bookmark of `url` to `new_url` when `url` moved permanently.
+fn offer_bookmark_update(code: SpecificCode, url: &Url, new_url: &Url) {
+    let Some(path) = bookmarks_path() else {
+        return;
+    };
+    let mut bookmarks = match Bookmarks::load_or_default(&path) {
+        Ok(bookmarks) => bookmarks,
+        Err(e) => {
+            error!("Could not load bookmarks from {}: {e}", path.display());
+            return;
+        }
+    };
+
+    if !moves_bookmark(&bookmarks, code, url)
+        || !confirm(&format!(
+            "{url} moved to {new_url}, update the bookmark? [y/N] "
+        ))
+    {
+        return;
+    }
+
+    bookmarks.replace(url, new_url);
+    if let Err(e) = bookmarks.save(&path) {
+        error!("Could not save bookmarks to {}: {e}", path.display());
+    }
+}
+
+pub fn handle_input(client: &mut Client, response: &Response, url: &Url) -> Option<Url> {
     info!("Page asks for user input");
 
-    let input = match response.status_code_num {
-        10 => get_user_input(&response.meta_description),
-        _ => get_secure_user_input(&response.meta_description),
+    let sensitive = response.specific_code() == SpecificCode::SensitiveInput;
+    // An answer that failed comes back first, so that it can be fixed
+    let prefill = match client.last_input(url) {
+        Some(last) => last.to_string(),
+        None => prefill_value(url)
+            .filter(|_| client.config.input.prefill)
+            .unwrap_or_default(),
+    };
+
+    let input = loop {
+        let input = if sensitive {
+            get_secure_user_input(&response.meta_description)
+        } else {
+            get_prefilled_user_input(&response.meta_description, &prefill)
+        };
+
+        // Sensitive input, like a password, is sent as typed
+        let settings = if sensitive {
+            InputSettings {
+                trim: false,
+                collapse_whitespace: false,
+                prefill: false,
+            }
+        } else {
+            client.config.input
+        };
+
+        match input
+            .as_deref()
+            .map(|input| sanitize_input(input, settings))
+        {
+            Some(None) => notice(
+                client.config.quiet,
+                "Nothing to send, please type something",
+            ),
+            Some(sanitized) => break sanitized,
+            None => break None,
+        }
     };
 
     let mut new_url = client.previous_url().unwrap().clone();
 
-    new_url.set_query(input.as_deref());
+    let encoding = client
+        .config
+        .connection_settings(new_url.host_str().unwrap_or_default())
+        .query_encoding
+        .unwrap_or_default();
+    let query = input.as_ref().map(|input| encode_query(input, encoding));
+    new_url.set_query(query.as_deref());
+
+    if sensitive {
+        client.sensitive_urls.insert(new_url.clone());
+    } else if let Some(input) = input {
+        client.remember_input(&new_url, input);
+    }
 
     Some(new_url)
 }
 
+/// What to do after the user answered the prompt.
+enum PromptAction {
+    Navigate(Url),
+    /// Prompt again, without reloading the page.
+    Stay,
+    Quit,
+}
+
+impl From<Option<Url>> for PromptAction {
+    fn from(url: Option<Url>) -> Self {
+        url.map_or(PromptAction::Quit, PromptAction::Navigate)
+    }
+}
+
+/// The prompt shown below `response`, `config.prompt` with its placeholders filled in.
+pub fn prompt_text(client: &Client, response: &Response, url: &Url) -> String {
+    let shown = client.shown_url(url);
+    expand_prompt(
+        &client.config.prompt,
+        &PromptState {
+            url: shown.as_str(),
+            host: url.host_str().unwrap_or_default(),
+            title: client.titles.get(url).map_or("", String::as_str),
+            links: response.links.len(),
+        },
+    )
+}
+
 fn get_client_prompt(client: &mut Client, response: &Response, url: &Url) -> Option<Url> {
-    let prompt = "Select a link by number or type a new URL ([q]uit [b]ack [r]eload [e]dit): ";
+    loop {
+        let prompt = prompt_text(client, response, url);
+        let favicon = url.host_str().and_then(|host| client.favicons.get(host));
+        let input = match favicon {
+            Some(icon) => get_user_input(&format!("{icon} {prompt}")),
+            None => get_user_input(&prompt),
+        };
 
-    let input = get_user_input(prompt);
+        match prompt_action(client, response, url, &input?) {
+            PromptAction::Navigate(new_url) => return Some(new_url),
+            PromptAction::Stay => {}
+            PromptAction::Quit => return None,
+        }
+    }
+}
 
-    match input {
-        Some(_) => {}
-        None => {
-            std::process::exit(1);
+fn prompt_action(client: &mut Client, response: &Response, url: &Url, input: &str) -> PromptAction {
+    let action = match input {
+        "b" => client.go_back().map_or_else(
+            || {
+                notice(client.config.quiet, "Already at the beginning of history");
+                PromptAction::Stay
+            },
+            PromptAction::Navigate,
+        ),
+        "f" => client.go_forward().map_or_else(
+            || {
+                notice(client.config.quiet, "Already at the end of history");
+                PromptAction::Stay
+            },
+            PromptAction::Navigate,
+        ),
+        "r" => client
+            .previous_url()
+            .cloned()
+            .map_or(PromptAction::Stay, PromptAction::Navigate),
+        // The certificate is checked on every request, a hard reload also skips caches
+        "R" => {
+            client.force_reload = true;
+            client
+                .previous_url()
+                .cloned()
+                .map_or(PromptAction::Stay, PromptAction::Navigate)
         }
+        _ => command_action(client, response, url, input),
     };
 
-    let input = input.unwrap();
+    // Going somewhere new drops the pages that were ahead
+    if matches!(&action, PromptAction::Navigate(new_url) if new_url != url)
+        && !matches!(input, "b" | "f")
+    {
+        client.forward.clear();
+    }
+
+    action
+}
+
+fn command_action(
+    client: &mut Client,
+    response: &Response,
+    url: &Url,
+    input: &str,
+) -> PromptAction {
+    if is_first_link_alias(input) && response.links.is_empty() {
+        notice(client.config.quiet, "This page has no links");
+        return PromptAction::Stay;
+    }
+
+    if let Some(message) = out_of_range_link(input, response.links.len()) {
+        notice(client.config.quiet, message);
+        return PromptAction::Stay;
+    }
+
+    let index = link_index(input);
+    let link = index.and_then(|index| response.links.get(index));
+
+    if let Some(action) = link.and_then(|link| follow_link(client, link)) {
+        if let (PromptAction::Navigate(_), Some(index)) = (&action, index) {
+            save_reading_position(client, response, url, index);
+        }
+        return action;
+    }
 
-    match input.as_str() {
+    match input {
+        "q" if client.config.confirm_quit && !confirm("Really quit? [y/N] ") => PromptAction::Stay,
         "q" => {
-            println!("Goodbye!");
+            notice(client.config.quiet, "Goodbye!");
+            PromptAction::Quit
+        }
+        "e" => match client.edit_url() {
+            Ok(new_url) => PromptAction::Navigate(new_url),
+            Err(e @ EditError::InvalidUrl(_)) => {
+                error!("{e}");
+                PromptAction::Stay
+            }
+            Err(e) => {
+                notice(client.config.quiet, e);
+                PromptAction::Stay
+            }
+        },
+        "^" | "up" => PromptAction::Navigate(client.parent_url()),
+        "/" => PromptAction::Navigate(client.toggle_trailing_slash()),
+        "check" => {
+            let results = check_links(client, response, CHECK_WORKERS);
+            show_synthetic_page(client, &report_page(&results), url).into()
+        }
+        "g" => match choose_heading(response) {
+            Some(line) => {
+                let start_line = shown_line(client, response, line);
+                show_page_at(client, response, url, Some(start_line)).into()
+            }
+            None => PromptAction::Stay,
+        },
+        "a" => {
+            add_bookmark(client, url);
+            PromptAction::Stay
+        }
+        "P" => {
+            client.pager = client.pager.next();
+            if let Some(name) = client.pager.to_possible_value() {
+                notice(client.config.quiet, format!("Pager: {}", name.get_name()));
+            }
+            show_page(client, response, url).into()
+        }
+        "bm" => match Url::parse(BOOKMARKS_URL) {
+            Ok(bookmarks_url) => PromptAction::Navigate(bookmarks_url),
+            Err(_) => PromptAction::Stay,
+        },
+        "h" => {
+            let history: Vec<Url> = client
+                .history
+                .iter()
+                .filter(|visited| client.is_listable(visited))
+                .cloned()
+                .collect();
+            let page = history_page(&history, &client.titles);
+            show_synthetic_page(client, &page, url).into()
+        }
+        "i" => {
+            println!("{}\n{}", client.shown_url(url), response.status_summary());
+            println!("{} links", response.links.len());
+            if let Some(lang) = response.mime().as_ref().and_then(|mime| mime.param("lang")) {
+                println!("Language: {lang}");
+            }
+            if let Some(connection) = response.connection {
+                println!("{connection}");
+            }
+            PromptAction::Stay
+        }
+        "L" => {
+            export_links(client, response);
+            PromptAction::Stay
+        }
+        "O" => {
+            prefetch_links(client, response);
+            PromptAction::Stay
+        }
+        "repro" => {
+            print_reproduce_command(client, url);
+            PromptAction::Stay
+        }
+        "T" => {
+            client.plain_view = !client.plain_view;
+            show_page(client, response, url).into()
+        }
+        "map" => {
+            let host = url.host_str().unwrap_or_default();
+            let urls: Vec<Url> = client
+                .visited
+                .iter()
+                .filter(|visited| visited.host_str() == Some(host) && client.is_listable(visited))
+                .cloned()
+                .collect();
+            let page = site_map_page(host, &urls);
+            show_synthetic_page(client, &page, url).into()
+        }
+        _ => match Url::parse(input) {
+            Ok(new_url) => PromptAction::Navigate(new_url),
+            Err(_) => best_link_match(&response.links, input)
+                .and_then(|index| follow_link(client, &response.links[index]))
+                .unwrap_or_else(|| {
+                    notice(client.config.quiet, "Invalid input. Please try again.");
+                    PromptAction::Stay
+                }),
+        },
+    }
+}
+
+/// List the headings of the page and ask for one. Returns its line in the body.
+fn choose_heading(response: &Response) -> Option<usize> {
+    let headings = response.headings();
+    if headings.is_empty() {
+        println!("This page has no headings");
+        return None;
+    }
+
+    let index_width = headings.len().saturating_sub(1).to_string().len();
+    for (i, heading) in headings.iter().enumerate() {
+        let indent = "  ".repeat(heading.level - 1);
+        println!(
+            "{}: {indent}{}",
+            format!("{i:>index_width$}").blue(),
+            heading.text
+        );
+    }
+
+    let choice = get_user_input("Go to heading: ")?;
+    match choice
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|i| headings.get(i))
+    {
+        Some(heading) => Some(heading.line),
+        None => {
+            println!("No such heading");
             None
         }
-        "b" => client.actual_previous_url().cloned(),
-        "r" => client.previous_url().cloned(),
-        "e" => client.edit_url(),
-        _ if input
-            .parse::<usize>()
-            .ok()
-            .and_then(|index| response.links.get(index))
-            .and_then(|link| client.click_link(&link.href).ok())
-            .is_some() =>
-        {
-            client
-                .click_link(&response.links[input.parse::<usize>().unwrap()].href)
-                .ok()
+    }
+}
+
+/// `.` or a space, which follow the first link of the page.
+fn is_first_link_alias(input: &str) -> bool {
+    input == "." || (!input.is_empty() && input.trim().is_empty())
+}
+
+/// The link index `input` selects, if it selects one.
+pub fn link_index(input: &str) -> Option<usize> {
+    if is_first_link_alias(input) {
+        Some(0)
+    } else {
+        input.parse().ok()
+    }
+}
+
+/// The message for `input` when it is the number of a link the page doesn't have,
+/// `None` for any other input.
+pub fn out_of_range_link(input: &str, link_count: usize) -> Option<String> {
+    let index: usize = input.parse().ok()?;
+    if index < link_count {
+        return None;
+    }
+
+    Some(match link_count {
+        0 => format!("No link {index} — this page has no links"),
+        1 => format!("No link {index} — this page has 1 link (0)"),
+        _ => format!(
+            "No link {index} — this page has {link_count} links (0–{})",
+            link_count - 1
+        ),
+    })
+}
+
+/// Click `link`, `None` if it can't be followed.
+fn follow_link(client: &mut Client, link: &Link) -> Option<PromptAction> {
+    match client.click_link(&link.href) {
+        Ok(LinkTarget::Navigate(new_url)) => Some(PromptAction::Navigate(new_url)),
+        Ok(LinkTarget::External | LinkTarget::Stay) => Some(PromptAction::Stay),
+        Err(e) => {
+            debug!("Could not follow {}: {e}", link.href);
+            None
         }
-        _ if Url::parse(&input).is_ok() => Some(Url::parse(&input).unwrap()),
-        _ => {
-            println!("Invalid input. Please try again.");
+    }
+}
+
+fn show_synthetic_page(client: &mut Client, gemtext: &str, url: &Url) -> Option<Url> {
+    client.freshness = None;
+    match Response::try_from(format!("20 text/gemini\r\n{gemtext}").as_str()) {
+        Ok(response) => {
+            let shown = std::mem::replace(&mut client.synthetic_page, true);
+            let next = show_page(client, &response, url);
+            client.synthetic_page = shown;
+            next
+        }
+        Err(e) => {
+            error!("Could not build page: {e}");
             Some(url.clone())
         }
     }
 }
 
+/// Ask a yes/no question, defaulting to no.
+fn confirm(question: &str) -> bool {
+    get_user_input(question).is_some_and(|answer| is_yes(&answer))
+}
+
+/// Whether `answer` to a yes/no question is yes: `y` or `yes`, in any case.
+pub fn is_yes(answer: &str) -> bool {
+    let answer = answer.trim();
+    answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes")
+}
+
 fn get_user_input(prompt: &str) -> Option<String> {
+    get_prefilled_user_input(prompt, "")
+}
+
+/// `get_user_input`, with `initial` already typed.
+fn get_prefilled_user_input(prompt: &str, initial: &str) -> Option<String> {
     let mut rl =
         rustyline::Editor::<(), MemHistory>::with_history(Config::default(), MemHistory::default())
             .expect("Failed creating editor");
 
     let prompt = prompt.yellow().to_string();
 
-    let input_result = rl.readline(&prompt);
+    let input_result = rl.readline_with_initial(&prompt, (initial, ""));
 
     input_result.map(Some).unwrap_or_else(|e| {
         error!("Error on getting input: {e}");
@@ -0,0 +1,122 @@
+use log::info;
+use std::io::Write;
+use url::Url;
+
+use crate::client::Client;
+use crate::errors::HeadlessError;
+use crate::models::{Response, StatusCode};
+use crate::protocol::protocol_for;
+
+/// Fetch `url` without any interaction, following up to `client.max_redirects`
+/// redirects. Redirects back to a page of the chain are refused, and so are those to
+/// another host unless `config.allow_cross_host_redirects`. Anything but a success
+/// in the end is an error. Returns the page the redirects led to, with its response.
+pub fn fetch(client: &mut Client, url: &Url) -> Result<(Url, Response), HeadlessError> {
+    let mut url = url.clone();
+    let mut chain = vec![url.clone()];
+
+    loop {
+        let response = client.request(url.clone())??;
+
+        match response.status_code {
+            StatusCode::Success => {
+                client.last_working_url = Some(url.clone());
+                return Ok((url, response));
+            }
+            StatusCode::Redirect => {
+                if chain.len() > client.max_redirects {
+                    return Err(HeadlessError::TooManyRedirects(client.max_redirects));
+                }
+                let target = client.resolve_redirect(&response.meta_description)?;
+                if chain.contains(&target) {
+                    return Err(HeadlessError::RedirectLoop(target));
+                }
+                if target.host_str() != url.host_str() && !client.config.allow_cross_host_redirects
+                {
+                    return Err(HeadlessError::CrossHostRedirect(target));
+                }
+
+                info!("Redirecting to {target}");
+                chain.push(target.clone());
+                url = target;
+            }
+            _ => {
+                return Err(HeadlessError::Status(
+                    response.status_code_num,
+                    response.meta_description,
+                ))
+            }
+        }
+    }
+}
+
+/// URL of link `index` of `response`, resolved against the current page. Links to
+/// pages the client can't fetch, like `https:` or `mailto:` ones, are errors.
+pub fn follow_link(
+    client: &Client,
+    response: &Response,
+    index: usize,
+) -> Result<Url, HeadlessError> {
+    let link = response
+        .links
+        .get(index)
+        .ok_or(HeadlessError::LinkOutOfRange {
+            index,
+            count: response.links.len(),
+        })?;
+
+    let url = client.resolve_link(&link.href)?;
+    if protocol_for(url.scheme()).is_none() {
+        return Err(HeadlessError::UnfetchableLink { index, url });
+    }
+    Ok(url)
+}
+
+/// Fetch `url`, then follow each link index of `follow` in turn, and write the last
+/// page to `output`: its body as sent when `raw`, rendered otherwise.
+pub fn run(
+    client: &mut Client,
+    url: &Url,
+    follow: &[usize],
+    raw: bool,
+    mut output: impl Write,
+) -> Result<(), HeadlessError> {
+    let (mut url, mut response) = fetch(client, url)?;
+
+    for &index in follow {
+        let link = follow_link(client, &response, index)?;
+        info!("Following link {index} to {link}");
+        (url, response) = fetch(client, &link)?;
+    }
+
+    let page = if raw {
+        response.body.clone().unwrap_or_default()
+    } else {
+        client.renderer.render(&response, &url)
+    };
+
+    writeln!(output, "{page}")?;
+    Ok(())
+}
+
+/// Fetch only the header of `url` and write it to `output` as `STATUS META`.
+/// Returns the status code.
+pub fn head(client: &mut Client, url: &Url, mut output: impl Write) -> Result<u8, HeadlessError> {
+    let response = client.prepare_request(url.clone()).send_head()??;
+
+    writeln!(
+        output,
+        "{} {}",
+        response.status_code_num, response.meta_description
+    )?;
+    Ok(response.status_code_num)
+}
+
+/// Process exit code for a `head` status: 0 for a success, the status category
+/// (1, 3, 4, 5 or 6) otherwise.
+pub fn exit_code(status: u8) -> i32 {
+    match status / 10 {
+        2 => 0,
+        category => i32::from(category),
+    }
+}
@@ -0,0 +1,31 @@
+use url::Url;
+
+/// Whether `url` looks like it names a directory: its path ends with `/`, or its last
+/// segment has no extension, and it has no query.
+pub fn looks_like_directory(url: &Url) -> bool {
+    if url.query().is_some() {
+        return false;
+    }
+    let last = url.path().rsplit('/').next().unwrap_or_default();
+    last.is_empty() || !last.contains('.')
+}
+
+/// The URLs to try when the directory `url` was not found: each of `names` in it, in
+/// order. None when `url` doesn't look like a directory.
+pub fn index_candidates(url: &Url, names: &[String]) -> Vec<Url> {
+    if !looks_like_directory(url) {
+        return vec![];
+    }
+
+    let mut directory = url.clone();
+    directory.set_fragment(None);
+    if !directory.path().ends_with('/') {
+        directory.set_path(&format!("{}/", directory.path()));
+    }
+
+    names
+        .iter()
+        .filter(|name| !name.is_empty() && !name.contains('/'))
+        .filter_map(|name| directory.join(name).ok())
+        .collect()
+}
@@ -0,0 +1,104 @@
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::Deserialize;
+use url::{form_urlencoded, Url};
+
+/// How text typed for a status 10 prompt is cleaned up before being sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InputSettings {
+    /// Remove leading and trailing whitespace.
+    pub trim: bool,
+    /// Replace runs of whitespace inside the input by a single space.
+    pub collapse_whitespace: bool,
+    /// Start from the query the URL already has, to edit it rather than type it again.
+    pub prefill: bool,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self {
+            trim: true,
+            collapse_whitespace: false,
+            prefill: false,
+        }
+    }
+}
+
+/// How typed input is written in the query of the request.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryEncoding {
+    /// The whole input percent-encoded, as the spec says.
+    #[default]
+    Raw,
+    /// `key=value&...` pairs encoded like a web form, spaces as `+`.
+    Form,
+}
+
+/// Characters left as is in raw queries, the unreserved ones of RFC 3986.
+const RAW_QUERY: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// `input` as the query of a request.
+pub fn encode_query(input: &str, encoding: QueryEncoding) -> String {
+    match encoding {
+        QueryEncoding::Raw => utf8_percent_encode(input, RAW_QUERY).to_string(),
+        QueryEncoding::Form => input
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => format!("{}={}", form_encode(key), form_encode(value)),
+                None => form_encode(pair),
+            })
+            .collect::<Vec<_>>()
+            .join("&"),
+    }
+}
+
+fn form_encode(text: &str) -> String {
+    form_urlencoded::byte_serialize(text.as_bytes()).collect()
+}
+
+pub fn trim(input: &str) -> String {
+    input.trim().to_string()
+}
+
+pub fn collapse_whitespace(input: &str) -> String {
+    let mut collapsed = String::with_capacity(input.len());
+    let mut in_whitespace = false;
+
+    for c in input.chars() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            in_whitespace = false;
+        }
+    }
+
+    collapsed
+}
+
+/// `input` after the steps enabled in `settings`, `None` if nothing is left to send.
+pub fn sanitize_input(input: &str, settings: InputSettings) -> Option<String> {
+    let mut input = input.to_string();
+    if settings.collapse_whitespace {
+        input = collapse_whitespace(&input);
+    }
+    if settings.trim {
+        input = trim(&input);
+    }
+
+    (!input.is_empty()).then_some(input)
+}
+
+/// The query of `url`, decoded, to start the input of a status 10 prompt from.
+pub fn prefill_value(url: &Url) -> Option<String> {
+    let query = percent_decode_str(url.query()?).decode_utf8_lossy();
+    (!query.is_empty()).then(|| query.into_owned())
+}
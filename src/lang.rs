@@ -0,0 +1,89 @@
+use crate::models::{Link, Response};
+
+/// Language codes with the names a link to that language version is likely to use.
+const LANGUAGES: &[(&str, &[&str])] = &[
+    ("en", &["english"]),
+    ("fr", &["français", "francais", "french"]),
+    ("de", &["deutsch", "german"]),
+    ("es", &["español", "espanol", "castellano", "spanish"]),
+    ("eu", &["euskara", "basque"]),
+    ("ca", &["català", "catala", "catalan"]),
+    ("it", &["italiano", "italian"]),
+    ("pt", &["português", "portugues", "portuguese"]),
+    ("nl", &["nederlands", "dutch"]),
+    ("sv", &["svenska", "swedish"]),
+    ("fi", &["suomi", "finnish"]),
+    ("pl", &["polski", "polish"]),
+    ("ru", &["русский", "russian"]),
+    ("uk", &["українська", "ukrainian"]),
+    ("eo", &["esperanto"]),
+    ("ja", &["日本語", "japanese"]),
+    ("zh", &["中文", "chinese"]),
+];
+
+/// Language code a link name refers to, if a word of it is a known language name.
+pub fn link_language(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    let words: Vec<&str> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    LANGUAGES
+        .iter()
+        .find(|(_, names)| names.iter().any(|n| words.contains(n)))
+        .map(|(code, _)| *code)
+}
+
+/// English name of the language `code` stands for, e.g. `French` for `fr` or `fr-CA`.
+pub fn language_name(code: &str) -> Option<String> {
+    let primary = code.split(['-', '_']).next().unwrap_or_default();
+    let (_, names) = LANGUAGES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(primary))?;
+    // The English name comes last
+    let name = names.last()?;
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    Some(first.to_uppercase().chain(chars).collect())
+}
+
+/// The line starting a page in accessibility mode, naming the languages declared with
+/// `lang` in its MIME type, e.g. `Page language: French`. Unknown codes are given as is.
+pub fn language_preamble(response: &Response) -> Option<String> {
+    let mime = response.mime()?;
+    let codes = mime
+        .param("lang")
+        .filter(|codes| !codes.trim().is_empty())?;
+    let names: Vec<String> = codes
+        .split(',')
+        .map(str::trim)
+        .filter(|code| !code.is_empty())
+        .map(|code| language_name(code).unwrap_or_else(|| code.to_string()))
+        .collect();
+    Some(format!("Page language: {}", names.join(", ")))
+}
+
+/// Index of the link to the most preferred language version of the page.
+///
+/// A page only counts as offering language versions when its links name at
+/// least two different languages, so a lone "English grammar" link is ignored.
+pub fn preferred_language_link(links: &[Link], preferred: &[String]) -> Option<usize> {
+    let languages: Vec<Option<&str>> = links
+        .iter()
+        .map(|link| link.name.as_deref().and_then(link_language))
+        .collect();
+
+    let mut offered: Vec<&str> = languages.iter().flatten().copied().collect();
+    offered.sort_unstable();
+    offered.dedup();
+    if offered.len() < 2 {
+        return None;
+    }
+
+    preferred.iter().find_map(|code| {
+        languages
+            .iter()
+            .position(|language| language.is_some_and(|l| l.eq_ignore_ascii_case(code)))
+    })
+}
@@ -1,4 +1,34 @@
+pub mod batch;
+pub mod bookmarks;
+pub mod cache;
+pub mod checker;
 pub mod client;
+pub mod config;
+pub mod dns;
 pub mod errors;
+pub mod favicon;
+pub mod feed;
 pub mod handlers;
+pub mod headless;
+pub mod index;
+pub mod input;
+pub mod lang;
+pub mod links;
+pub mod markdown;
 pub mod models;
+pub mod output;
+pub mod pager;
+pub mod pages;
+pub mod parse;
+pub mod prefetch;
+pub mod prompt;
+pub mod protocol;
+pub mod render;
+pub mod sniff;
+pub mod spinner;
+pub mod state;
+pub mod stats;
+pub mod terminal;
+pub mod tofu;
+pub mod trace;
+pub mod version;
@@ -0,0 +1,239 @@
+use colored::Color;
+use percent_encoding::percent_decode_str;
+use std::collections::HashMap;
+use url::{ParseError, Url};
+
+use crate::config::Mailto;
+use crate::models::Link;
+use crate::protocol::port_of;
+
+/// What a link points to, judged from its href.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkKind {
+    Gemini,
+    Web,
+    Mail,
+    Gopher,
+    Finger,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkClass {
+    pub kind: LinkKind,
+    /// The href is relative to the page, which makes it a Gemini link.
+    pub relative: bool,
+}
+
+impl LinkClass {
+    pub fn color(self) -> Color {
+        match self.kind {
+            LinkKind::Gemini if self.relative => Color::Cyan,
+            LinkKind::Gemini => Color::Blue,
+            LinkKind::Web => Color::Yellow,
+            LinkKind::Mail => Color::Magenta,
+            LinkKind::Gopher | LinkKind::Finger => Color::Green,
+            LinkKind::Other => Color::Red,
+        }
+    }
+
+    /// Shown before the href in the link list.
+    pub fn prefix(self) -> &'static str {
+        match self.kind {
+            LinkKind::Mail => "✉ ",
+            _ => "",
+        }
+    }
+}
+
+pub fn classify_link(href: &str) -> LinkClass {
+    let Ok(url) = Url::parse(href) else {
+        return LinkClass {
+            kind: LinkKind::Gemini,
+            relative: true,
+        };
+    };
+
+    let kind = match url.scheme() {
+        "gemini" => LinkKind::Gemini,
+        "http" | "https" => LinkKind::Web,
+        "mailto" => LinkKind::Mail,
+        "gopher" => LinkKind::Gopher,
+        "finger" => LinkKind::Finger,
+        _ => LinkKind::Other,
+    };
+
+    LinkClass {
+        kind,
+        relative: false,
+    }
+}
+
+/// How a link the client doesn't browse itself is followed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalLink {
+    /// Run this program with these arguments.
+    Launch(Vec<String>),
+    /// Print this, a mail address, for the user to copy.
+    Show(String),
+}
+
+/// How to follow `url`, a link handed out of the client: `mailto:` links are shown
+/// when `mailto` says so, the rest is opened with `handler_command`.
+pub fn external_link(
+    handlers: &HashMap<String, String>,
+    mailto: Mailto,
+    url: &Url,
+) -> ExternalLink {
+    if url.scheme() == "mailto" && mailto == Mailto::Show {
+        let address = percent_decode_str(url.path()).decode_utf8_lossy();
+        return ExternalLink::Show(address.into_owned());
+    }
+    ExternalLink::Launch(handler_command(handlers, url))
+}
+
+/// Program and arguments opening `url` outside the client: the handler configured
+/// for its scheme, with `%u` replaced by the URL, or `xdg-open`.
+pub fn handler_command(handlers: &HashMap<String, String>, url: &Url) -> Vec<String> {
+    let Some(template) = handlers.get(url.scheme()) else {
+        return vec!["xdg-open".to_string(), url.to_string()];
+    };
+
+    let mut command: Vec<String> = template
+        .split_whitespace()
+        .map(|part| part.replace("%u", url.as_str()))
+        .collect();
+    if !template.contains("%u") {
+        command.push(url.to_string());
+    }
+    command
+}
+
+/// Lowest score of a fuzzy match that is good enough to follow.
+pub const FUZZY_THRESHOLD: f64 = 0.4;
+
+/// Fewest characters a query needs to be matched against links, shorter ones such
+/// as a mistyped command letter would match almost any link.
+pub const MIN_FUZZY_QUERY: usize = 3;
+
+/// How well `query` matches `text`, between 0 and 1, ignoring case: 1 for a prefix,
+/// 0.9 anywhere else, and lower the more spread out its characters are in `text`.
+/// `None` when `text` doesn't contain all characters of `query` in order.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<f64> {
+    let query = query.to_lowercase();
+    let text = text.to_lowercase();
+
+    if query.is_empty() {
+        return None;
+    }
+    if text.starts_with(&query) {
+        return Some(1.0);
+    }
+    if text.contains(&query) {
+        return Some(0.9);
+    }
+
+    let mut wanted = query.chars().peekable();
+    let mut first = None;
+    let mut last = 0;
+    for (i, c) in text.chars().enumerate() {
+        if wanted.peek() == Some(&c) {
+            wanted.next();
+            first.get_or_insert(i);
+            last = i;
+        }
+    }
+    if wanted.peek().is_some() {
+        return None;
+    }
+
+    let span = last - first.unwrap_or(0) + 1;
+    #[allow(clippy::cast_precision_loss)]
+    Some(0.8 * query.chars().count() as f64 / span as f64)
+}
+
+/// Index of the link whose name or href best matches `query`, if any matches with
+/// at least `FUZZY_THRESHOLD`. The first link wins a tie. Queries shorter than
+/// `MIN_FUZZY_QUERY` characters match nothing.
+pub fn best_link_match(links: &[Link], query: &str) -> Option<usize> {
+    if query.trim().chars().count() < MIN_FUZZY_QUERY {
+        return None;
+    }
+    let mut best: Option<(usize, f64)> = None;
+
+    for (i, link) in links.iter().enumerate() {
+        let score = [link.name.as_deref(), Some(link.href.as_str())]
+            .into_iter()
+            .flatten()
+            .filter_map(|text| fuzzy_score(query, text))
+            .fold(0.0, f64::max);
+
+        if score >= FUZZY_THRESHOLD && best.is_none_or(|(_, best)| score > best) {
+            best = Some((i, score));
+        }
+    }
+
+    best.map(|(i, _)| i)
+}
+
+/// `12 links: 8 gemini, 3 http, 1 mailto`: how many `links` there are of each kind
+/// once resolved with `resolve`, most common first, `None` without links. Links that
+/// don't resolve count as `invalid`.
+pub fn link_summary(
+    links: &[Link],
+    resolve: impl Fn(&str) -> Result<Url, ParseError>,
+) -> Option<String> {
+    if links.is_empty() {
+        return None;
+    }
+
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for link in links {
+        let kind = match resolve(&link.href) {
+            Ok(url) if classify_link(url.as_str()).kind == LinkKind::Web => "http".to_string(),
+            Ok(url) => url.scheme().to_string(),
+            Err(_) => "invalid".to_string(),
+        };
+        match counts.iter_mut().find(|(known, _)| *known == kind) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((kind, 1)),
+        }
+    }
+    // Stable, kinds as common keep the order they first appear in
+    counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    let kinds: Vec<String> = counts
+        .iter()
+        .map(|(kind, count)| format!("{count} {kind}"))
+        .collect();
+    let noun = if links.len() == 1 { "link" } else { "links" };
+    Some(format!("{} {noun}: {}", links.len(), kinds.join(", ")))
+}
+
+/// Whether `name`, shown for a link to `href`, spells out a URL elsewhere: any
+/// `scheme://…` word of the name pointing to another scheme, host or port than
+/// `href`. A name showing the capsule a link goes to, or no URL at all, is fine.
+pub fn spoofed_name(name: &str, href: &Url) -> bool {
+    name_urls(name).any(|shown| {
+        shown.scheme() != href.scheme()
+            || shown.host_str() != href.host_str()
+            || port_of(&shown) != port_of(href)
+    })
+}
+
+/// The URLs written out in `text`, without the punctuation around them.
+fn name_urls(text: &str) -> impl Iterator<Item = Url> + '_ {
+    text.split_whitespace().filter_map(|word| {
+        let separator = word.find("://")?;
+        let start = word[..separator]
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))
+            .map_or(0, |i| i + 1);
+        let candidate = word[start..].trim_end_matches(|c: char| {
+            matches!(
+                c,
+                '.' | ',' | ';' | ':' | '!' | '?' | ')' | ']' | '>' | '"' | '\''
+            )
+        });
+        Url::parse(candidate).ok().filter(Url::has_host)
+    })
+}
@@ -0,0 +1,208 @@
+use colored::Colorize;
+
+use crate::models::{is_fence, Link};
+
+/// Byte ranges of a `[text](url)` link found in a line.
+struct InlineLink {
+    start: usize,
+    end: usize,
+    text: (usize, usize),
+    url: (usize, usize),
+}
+
+impl InlineLink {
+    /// The text and URL of the link in `line`.
+    fn slices<'a>(&self, line: &'a str) -> (&'a str, &'a str) {
+        (
+            &line[self.text.0..self.text.1],
+            &line[self.url.0..self.url.1],
+        )
+    }
+}
+
+/// Find the first `[text](url)` link in `line` at or after `from`.
+fn find_link(line: &str, from: usize) -> Option<InlineLink> {
+    let mut search = from;
+
+    while let Some(offset) = line[search..].find('[') {
+        let start = search + offset;
+        let text_len = line[start + 1..].find(']')?;
+        let text_end = start + 1 + text_len;
+
+        if line[text_end + 1..].starts_with('(') {
+            if let Some(url_len) = line[text_end + 2..].find(')') {
+                let url_end = text_end + 2 + url_len;
+                let target = &line[text_end + 2..url_end];
+                let url_start = text_end + 2 + (target.len() - target.trim_start().len());
+                // Drop an optional title: [text](url "title")
+                let url_len = line[url_start..url_end]
+                    .find(char::is_whitespace)
+                    .unwrap_or(url_end - url_start);
+
+                if url_len > 0 {
+                    // Images, `![alt](src)`, are links too
+                    let is_image = start > from && line[..start].ends_with('!');
+                    return Some(InlineLink {
+                        start: if is_image { start - 1 } else { start },
+                        end: url_end + 1,
+                        text: (start + 1, text_end),
+                        url: (url_start, url_start + url_len),
+                    });
+                }
+            }
+        }
+
+        search = start + 1;
+    }
+
+    None
+}
+
+/// Lines of `body` outside fenced code blocks.
+fn prose_lines(body: &str) -> impl Iterator<Item = &str> {
+    let mut fenced = false;
+
+    body.lines().filter(move |line| {
+        if is_fence(line) {
+            fenced = !fenced;
+            return false;
+        }
+        !fenced
+    })
+}
+
+/// `line` with the content of code spans replaced by spaces, so that links inside
+/// them aren't found. Byte offsets are the same as in `line`.
+fn blank_code_spans(line: &str) -> String {
+    let mut in_code = false;
+    let mut blanked = String::with_capacity(line.len());
+
+    for c in line.chars() {
+        if c == '`' {
+            in_code = !in_code;
+            blanked.push(c);
+        } else if in_code {
+            blanked.push_str(&" ".repeat(c.len_utf8()));
+        } else {
+            blanked.push(c);
+        }
+    }
+
+    blanked
+}
+
+/// All `[text](url)` links of a Markdown body, in order. Links in code are skipped.
+pub fn extract_links(body: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+
+    for line in prose_lines(body) {
+        let masked = blank_code_spans(line);
+        let mut from = 0;
+        while let Some(link) = find_link(&masked, from) {
+            let (text, href) = link.slices(line);
+            let text = text.trim();
+            links.push(Link::new(
+                href.to_string(),
+                (!text.is_empty()).then(|| text.to_string()),
+            ));
+            from = link.end;
+        }
+    }
+
+    links
+}
+
+/// Style a Markdown body for the terminal: headings, emphasis, code and links.
+/// Links are numbered like their index in `Response::links` when `inline_link_numbers` is set.
+pub fn render(body: &str, inline_link_numbers: bool) -> String {
+    let mut fenced = false;
+    let mut count: usize = 0;
+    let mut lines = Vec::new();
+
+    for line in body.lines() {
+        if is_fence(line) {
+            fenced = !fenced;
+            continue;
+        }
+
+        if fenced {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let hashes = line.chars().take_while(|&c| c == '#').count();
+        let rendered = match hashes {
+            1..=6 if line[hashes..].starts_with(' ') => {
+                // Links in headings are links of the page too
+                let heading = render_inline(line[hashes..].trim(), inline_link_numbers, &mut count);
+                match hashes {
+                    1 => heading.bright_green().bold().underline().to_string(),
+                    2 => heading.green().bold().to_string(),
+                    _ => heading.green().to_string(),
+                }
+            }
+            _ => render_inline(line, inline_link_numbers, &mut count),
+        };
+
+        lines.push(rendered);
+    }
+
+    lines.join("\n")
+}
+
+fn render_inline(line: &str, inline_link_numbers: bool, count: &mut usize) -> String {
+    let masked = blank_code_spans(line);
+    let mut rendered = String::new();
+    let mut from = 0;
+
+    while let Some(link) = find_link(&masked, from) {
+        rendered.push_str(&render_emphasis(&line[from..link.start]));
+
+        let (text, url) = link.slices(line);
+        let text = text.trim();
+        let text = if text.is_empty() { url } else { text };
+        rendered.push_str(&text.underline().to_string());
+        if inline_link_numbers {
+            rendered.push_str(&format!(" [{count}]").blue().to_string());
+        }
+        *count += 1;
+
+        from = link.end;
+    }
+
+    rendered.push_str(&render_emphasis(&line[from..]));
+    rendered
+}
+
+/// Style `` `code` ``, `**strong**` and `*emphasis*` spans.
+fn render_emphasis(text: &str) -> String {
+    let mut rendered = String::new();
+    let mut rest = text;
+
+    loop {
+        let next = ["`", "**", "*"]
+            .iter()
+            .filter_map(|marker| rest.find(marker).map(|index| (index, *marker)))
+            .min_by_key(|(index, marker)| (*index, usize::MAX - marker.len()));
+
+        let Some((start, marker)) = next else {
+            rendered.push_str(rest);
+            return rendered;
+        };
+
+        let inner_start = start + marker.len();
+        let Some(inner_len) = rest[inner_start..].find(marker) else {
+            rendered.push_str(rest);
+            return rendered;
+        };
+
+        let inner = &rest[inner_start..inner_start + inner_len];
+        rendered.push_str(&rest[..start]);
+        rendered.push_str(&match marker {
+            "`" => inner.yellow().to_string(),
+            "**" => inner.bold().to_string(),
+            _ => inner.italic().to_string(),
+        });
+        rest = &rest[inner_start + inner_len + marker.len()..];
+    }
+}
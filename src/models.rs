@@ -1,208 +1,527 @@
 use clap::ValueEnum;
-use log::{debug, info, trace};
-use native_tls::TlsConnector;
-use std::io::{BufReader, Read, Write};
-use std::net::TcpStream;
+use log::{debug, info, trace, warn};
+use native_tls::{HandshakeError, Identity, TlsConnector, TlsStream};
+use std::borrow::Cow;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 use url::Url;
 
+use crate::config::ConnectionSettings;
+use crate::dns::{is_dns_failure, DnsCache, DNS_RETRIES, DNS_RETRY_DELAY};
 use crate::errors::{RequestError, ResponseError};
-
-#[derive(ValueEnum, Debug, Clone, Copy)]
+use crate::parse::header_len;
+use crate::protocol::{port_of, protocol_for, Gemini, Protocol};
+use crate::tofu::{check, fingerprint, host_key, TofuDecision, TofuPolicy, TofuStore};
+use crate::trace::{hex_dump, redacted, trace_request, Trace};
+
+// The response parser lives in `parse`, away from the networking code
+pub use crate::parse::{
+    decode_response, is_fence, leading_date, ConnectionInfo, Heading, Link, LinkParseError, Mime,
+    Response, SpecificCode, StatusCode, MAX_META_LENGTH,
+};
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
 #[clap(rename_all = "lower")]
 pub enum Pager {
     Less,
     More,
     Bat,
     Neovim,
+    // Built in, for when none of the others is installed
+    Internal,
+}
+
+impl Pager {
+    /// Whether the pager shows ANSI colors instead of the raw escape sequences.
+    pub fn renders_ansi(self) -> bool {
+        !matches!(self, Pager::More)
+    }
+
+    /// The pager after this one, in declaration order, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            Pager::Less => Pager::More,
+            Pager::More => Pager::Bat,
+            Pager::Bat => Pager::Neovim,
+            Pager::Neovim => Pager::Internal,
+            Pager::Internal => Pager::Less,
+        }
+    }
 }
 
-#[derive(Debug)]
-pub enum StatusCode {
-    Input,
-    Success,
-    Redirect,
-    TemporaryFailure,
-    PermanentFailure,
-    ClientCertificateRequired,
-    Unknown(u8),
+/// Aborts a request from another thread by shutting its connection down.
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle {
+    cancelled: Arc<AtomicBool>,
+    stream: Arc<Mutex<Option<TcpStream>>>,
 }
 
-impl From<u8> for StatusCode {
-    fn from(code: u8) -> Self {
-        match code {
-            10..=19 => StatusCode::Input,
-            20..=29 => StatusCode::Success,
-            30..=39 => StatusCode::Redirect,
-            40..=49 => StatusCode::TemporaryFailure,
-            50..=59 => StatusCode::PermanentFailure,
-            60..=69 => StatusCode::ClientCertificateRequired,
-            _ => StatusCode::Unknown(code),
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        if let Ok(stream) = self.stream.lock() {
+            if let Some(stream) = stream.as_ref() {
+                stream.shutdown(Shutdown::Both).ok();
+            }
         }
     }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Keep a handle on the socket of `stream`, shutting it down right away if
+    /// the request was cancelled while connecting.
+    fn register(&self, stream: &TcpStream) -> std::io::Result<()> {
+        if let Ok(mut registered) = self.stream.lock() {
+            *registered = Some(stream.try_clone()?);
+        }
+        if self.is_cancelled() {
+            stream.shutdown(Shutdown::Both)?;
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Request {
     url: Url,
+    settings: ConnectionSettings,
+    cancel: Option<CancelHandle>,
+    dns: Option<DnsCache>,
+    tofu: Option<(TofuStore, TofuPolicy)>,
+    protocol: &'static dyn Protocol,
+    trace: Option<Trace>,
+    passphrase: Option<Passphrase>,
+    /// The query is status 11 input.
+    sensitive: bool,
+}
+
+/// Environment variable holding the passphrase of the client certificate, for
+/// sessions where it can't be typed.
+pub const PASSPHRASE_VAR: &str = "GEMINI_IDENTITY_PASSPHRASE";
+
+/// The passphrase of a client certificate, hidden from `Debug` output.
+#[derive(Clone)]
+pub struct Passphrase(pub String);
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Passphrase(..)")
+    }
+}
+
+/// The PKCS#12 client certificate at `path`, unlocked with `passphrase`.
+pub fn load_identity(path: &Path, passphrase: &str) -> Result<Identity, RequestError> {
+    let der = fs::read(path)?;
+    Identity::from_pkcs12(&der, passphrase).map_err(|e| {
+        debug!("Could not load {}: {e}", path.display());
+        RequestError::LockedIdentity(path.to_path_buf())
+    })
 }
 
 impl Request {
     pub fn new(url: Url) -> Self {
-        info!("Creating new request for URL: {url}");
-        Self { url }
+        Self {
+            protocol: protocol_for(url.scheme()).unwrap_or(&Gemini),
+            url,
+            settings: ConnectionSettings::default(),
+            cancel: None,
+            dns: None,
+            tofu: None,
+            trace: None,
+            passphrase: None,
+            sensitive: false,
+        }
     }
 
-    pub fn send(&self) -> Result<Result<Response, ResponseError>, RequestError> {
-        info!("Sending request to: {}", self.url);
+    /// Keep the query out of the logs, it was typed at a sensitive input prompt.
+    pub fn with_sensitive_query(mut self) -> Self {
+        self.sensitive = true;
+        self
+    }
 
-        let connector = TlsConnector::builder()
-            .danger_accept_invalid_certs(true)
-            .build()?;
+    /// The URL as it can be logged.
+    fn logged_url(&self) -> Cow<'_, Url> {
+        if self.sensitive {
+            Cow::Owned(redacted(&self.url))
+        } else {
+            Cow::Borrowed(&self.url)
+        }
+    }
 
-        let host = self
-            .url
-            .host_str()
-            .ok_or(RequestError::MissingHost)?
-            .to_string();
+    /// Unlock the client certificate with `passphrase` rather than the one in
+    /// `PASSPHRASE_VAR`, or none.
+    pub fn with_passphrase(mut self, passphrase: Passphrase) -> Self {
+        self.passphrase = Some(passphrase);
+        self
+    }
 
-        let port = self.url.port().unwrap_or(1965);
+    /// Print the bytes sent and the start of the raw response to stderr.
+    pub fn with_trace(mut self, trace: Trace) -> Self {
+        self.trace = Some(trace);
+        self
+    }
 
-        debug!("Connecting to {host} on port {port}");
-        let stream = TcpStream::connect(format!("{host}:{port}"))?;
-        let mut stream = connector.connect(&host, stream)?;
+    /// Check the server certificate against the hosts known in `store`, applying
+    /// `policy` to hosts seen for the first time.
+    pub fn with_tofu(mut self, store: TofuStore, policy: TofuPolicy) -> Self {
+        self.tofu = Some((store, policy));
+        self
+    }
 
-        let request = format!("gemini://{host}{}\r\n", self.url.path());
-        debug!("Sending request: {request:?}");
+    /// Resolve the host through `dns`, and remember its address there.
+    pub fn with_dns_cache(mut self, dns: DnsCache) -> Self {
+        self.dns = Some(dns);
+        self
+    }
 
-        stream.write_all(request.as_bytes())?;
-        stream.flush()?;
-        info!("Request sent successfully");
+    pub fn with_cancel_handle(mut self, cancel: CancelHandle) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
 
-        let mut reader = BufReader::new(stream);
-        let mut string_response = String::new();
+    pub fn with_settings(mut self, settings: ConnectionSettings) -> Self {
+        self.settings = settings;
+        self
+    }
 
-        reader.read_to_string(&mut string_response)?;
+    fn connect(&self, host: &str, port: u16) -> Result<TcpStream, RequestError> {
+        if let Some(addrs) = self.dns.as_ref().and_then(|dns| dns.lookup(host, port)) {
+            match self.connect_to(host, &addrs) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => debug!("Cached address of {host} failed, resolving again: {e}"),
+            }
+        }
 
-        trace!("Raw response received: {string_response:?}");
+        let addrs = self.resolve(host, port)?;
+        let stream = self.connect_to(host, &addrs);
+
+        if let Some(dns) = &self.dns {
+            match stream {
+                Ok(_) => dns.insert(host, port, addrs),
+                Err(_) => dns.invalidate(host, port),
+            }
+        }
 
-        Ok(Response::try_from(string_response.as_str()))
+        stream
     }
-}
 
-impl TryFrom<&str> for Request {
-    type Error = RequestError;
+    /// Addresses of `host`, resolving it again a few times when that fails.
+    fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, RequestError> {
+        let retries = self.settings.dns_retries.unwrap_or(DNS_RETRIES);
+        let mut attempt = 0;
+
+        loop {
+            match (host, port).to_socket_addrs() {
+                Ok(addrs) => return Ok(addrs.collect()),
+                Err(e) if attempt < retries && is_dns_failure(&e) => {
+                    attempt += 1;
+                    debug!("Could not resolve {host}, trying again ({attempt}/{retries}): {e}");
+                    thread::sleep(DNS_RETRY_DELAY);
+                }
+                Err(e) => {
+                    debug!("Could not resolve {host}: {e}");
+                    return Err(RequestError::UnresolvedHost(host.to_string()));
+                }
+            }
+        }
+    }
 
-    fn try_from(url: &str) -> Result<Self, RequestError> {
-        info!("Parsing URL: {url}");
-        let url = Url::parse(url)?;
-        Ok(Self::new(url))
+    fn connect_to(&self, host: &str, addrs: &[SocketAddr]) -> Result<TcpStream, RequestError> {
+        let Some(timeout) = self.settings.timeout() else {
+            return Ok(TcpStream::connect(addrs)?);
+        };
+
+        let mut last_error = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(addr, timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(timeout))?;
+                    stream.set_write_timeout(Some(timeout))?;
+                    return Ok(stream);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        Err(last_error.map_or_else(
+            || RequestError::UnresolvedHost(host.to_string()),
+            Into::into,
+        ))
     }
-}
 
-#[derive(Debug)]
-pub struct Link {
-    pub href: String,
-    pub name: Option<String>,
-}
+    pub fn send(&self) -> Result<Result<Response, ResponseError>, RequestError> {
+        let started = Instant::now();
+        let mut reader = self.open()?;
+        let mut bytes = Vec::new();
+
+        // The end of a response is the server closing the TLS session. A connection
+        // reset or aborted on the way leaves us with part of the body only.
+        let truncated = match reader.read_to_end(&mut bytes) {
+            Ok(_) => false,
+            Err(e) if is_truncation(&e) && !bytes.is_empty() => {
+                warn!("Connection closed before the end of the response: {e}");
+                true
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let connection = ConnectionInfo {
+            elapsed: started.elapsed(),
+        };
+        if self.trace.is_some() {
+            eprint!("{}", hex_dump(&bytes));
+        }
+        log_header(&bytes);
 
-pub enum LinkParseError {
-    InvalidFormat,
-}
+        // The last character may have been cut
+        let string_response = decode_response(&bytes, truncated)?;
 
-impl TryFrom<&str> for Link {
-    type Error = LinkParseError;
+        trace!("Raw response received: {string_response:?}");
+        let string_response = self.with_gemini_header(&string_response);
+
+        Ok(
+            Response::try_from(string_response.as_str()).map(|mut response| {
+                response.truncated = truncated;
+                response.connection = Some(connection);
+                response
+            }),
+        )
+    }
 
-    fn try_from(line: &str) -> Result<Self, Self::Error> {
-        // Strip leading whitespace and check if the line starts with "=>"
-        let trimmed = line.trim_start();
-        if !trimmed.starts_with("=>") {
-            return Err(LinkParseError::InvalidFormat);
+    /// Send the request but only read the response header, the connection is closed
+    /// before the body. The response has no body and no links.
+    pub fn send_head(&self) -> Result<Result<Response, ResponseError>, RequestError> {
+        let started = Instant::now();
+        let reader = self.open()?;
+        let mut bytes = Vec::new();
+
+        // Status, space, meta and CRLF
+        let limit = (MAX_META_LENGTH + 5) as u64;
+        reader.take(limit).read_until(b'\n', &mut bytes)?;
+        let connection = ConnectionInfo {
+            elapsed: started.elapsed(),
+        };
+        if self.trace.is_some() {
+            eprint!("{}", hex_dump(&bytes));
         }
+        log_header(&bytes);
 
-        let trimmed = trimmed.trim_start_matches("=>").trim();
+        let header = String::from_utf8_lossy(&bytes);
 
-        let parts: Vec<&str> = trimmed.splitn(2, ' ').collect();
+        trace!("Raw header received: {header:?}");
+        let header = self.with_gemini_header(&header);
 
-        if let Some(url) = parts.first() {
-            let name = parts.get(1).map(|s| (*s).to_string());
-            Ok(Link {
-                href: (*url).to_string(),
-                name,
-            })
-        } else {
-            Err(LinkParseError::InvalidFormat)
-        }
+        Ok(Response::try_from(header.as_str()).map(|mut response| {
+            response.body = None;
+            response.connection = Some(connection);
+            response
+        }))
     }
-}
 
-pub struct Response {
-    pub status_code: StatusCode,
-    pub status_code_num: u8,
-    pub meta_description: String,
-    pub body: Option<String>,
-    pub links: Vec<Link>,
-}
+    /// `response` with its header line in the Gemini form `Response` parses.
+    fn with_gemini_header(&self, response: &str) -> String {
+        let end = response.find('\n').unwrap_or(response.len());
+        let header = self.protocol.gemini_header(&response[..end]);
+        format!("{header}{}", &response[end..])
+    }
 
-impl TryFrom<&str> for Response {
-    type Error = ResponseError;
+    /// Connect and send the request, returning the connection to read the response from.
+    fn open(&self) -> Result<BufReader<Box<dyn Read + Send>>, RequestError> {
+        info!("Sending request to: {}", self.logged_url());
 
-    fn try_from(response_str: &str) -> Result<Self, ResponseError> {
-        debug!("Parsing response string");
+        let host = self
+            .url
+            .host_str()
+            .ok_or(RequestError::MissingHost)?
+            .to_string();
 
-        let mut count: u32 = 0;
+        let port = port_of(&self.url);
 
-        let mut lines = response_str.lines();
-        let first_line = lines.next().ok_or(ResponseError::EmptyResponse)?;
+        debug!("Connecting to {host} on port {port}");
+        let stream = self.connect(&host, port)?;
+        if let Some(cancel) = &self.cancel {
+            cancel.register(&stream)?;
+        }
 
-        let mut first_line_parts = first_line.splitn(2, ' ');
+        let mut request_url = self.url.clone();
+        if let Some(rewrite) = &self.settings.rewrite {
+            let path = rewrite.apply(request_url.path());
+            request_url.set_path(&path);
+        }
+        let request = self.protocol.build_request_line(&request_url);
+        if self.sensitive {
+            let logged = self.protocol.build_request_line(&redacted(&request_url));
+            debug!("Sending request: {logged:?}");
+        } else {
+            debug!("Sending request: {request:?}");
+        }
+        if let Some(trace) = self.trace {
+            eprintln!("{}", trace_request(self.protocol, &request_url, trace));
+        }
 
-        let status_code_num = first_line_parts
-            .next()
-            .ok_or(ResponseError::MissingStatusCode)?
-            .parse::<u8>()
-            .map_err(|_| ResponseError::InvalidStatusCode)?;
+        let stream: Box<dyn Read + Send> = if self.protocol.uses_tls() {
+            let mut stream = self.tls_connect(&host, port, stream)?;
+            send_request(&mut stream, &request)?;
+            Box::new(stream)
+        } else {
+            let mut stream = stream;
+            send_request(&mut stream, &request)?;
+            Box::new(stream)
+        };
 
-        let status_code = StatusCode::from(status_code_num);
+        Ok(BufReader::new(stream))
+    }
 
-        let meta_description = first_line_parts
-            .next()
-            .ok_or(ResponseError::MissingMetaDescription)?
-            .to_string();
+    /// Wrap `stream` in TLS and check the server's certificate.
+    fn tls_connect(
+        &self,
+        host: &str,
+        port: u16,
+        stream: TcpStream,
+    ) -> Result<TlsStream<TcpStream>, RequestError> {
+        let mut builder = TlsConnector::builder();
+        builder
+            .danger_accept_invalid_certs(true)
+            .min_protocol_version(self.settings.min_tls_version.map(Into::into));
+
+        if let Some(path) = &self.settings.identity {
+            debug!("Using client certificate {}", path.display());
+            let passphrase = match &self.passphrase {
+                Some(passphrase) => passphrase.0.clone(),
+                None => env::var(PASSPHRASE_VAR).unwrap_or_default(),
+            };
+            builder.identity(load_identity(path, &passphrase)?);
+        }
 
-        let body = lines
-            .clone()
-            .map(|line| {
-                if line.trim_start().starts_with("=>") {
-                    let result = format!("({}) {}", count, line.trim_start());
-                    count += 1;
-                    result
-                } else {
-                    line.to_string()
-                }
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        let links: Vec<Link> = lines
-            .filter_map(|line| {
-                if line.starts_with("=>") {
-                    Link::try_from(line).ok()
-                } else {
-                    None
+        let connector = builder.build()?;
+        let deadline = self
+            .settings
+            .timeout()
+            .map(|timeout| Instant::now() + timeout);
+        let stream = tls_handshake(&connector, host, stream, deadline)?;
+
+        if let Some((store, policy)) = &self.tofu {
+            let der = match stream.peer_certificate()? {
+                Some(certificate) => certificate.to_der()?,
+                None => vec![],
+            };
+            verify_certificate(store, *policy, &host_key(host, port), &fingerprint(&der))?;
+        }
+
+        Ok(stream)
+    }
+}
+
+/// Pause before resuming a handshake that would have blocked.
+const HANDSHAKE_POLL: Duration = Duration::from_millis(10);
+
+/// Run the TLS handshake with `host` over `stream`, resuming it for as long as it
+/// would block on a socket that isn't ready, until `deadline` if there is one.
+pub fn tls_handshake(
+    connector: &TlsConnector,
+    host: &str,
+    stream: TcpStream,
+    deadline: Option<Instant>,
+) -> Result<TlsStream<TcpStream>, RequestError> {
+    let mut result = connector.connect(host, stream);
+    loop {
+        match result {
+            Ok(stream) => return Ok(stream),
+            Err(HandshakeError::WouldBlock(mid)) => {
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("TLS handshake with {host} timed out"),
+                    )
+                    .into());
                 }
-            })
-            .collect();
+                trace!("Handshake with {host} would block, resuming");
+                thread::sleep(HANDSHAKE_POLL);
+                result = mid.handshake();
+            }
+            Err(e @ HandshakeError::Failure(_)) => return Err(e.into()),
+        }
+    }
+}
 
-        trace!(
-            "Response parsed: status_code={status_code:?}, meta={meta_description}, body={body:?}, links={links:?}",
-        );
+fn send_request(stream: &mut impl Write, request: &str) -> Result<(), RequestError> {
+    stream.write_all(request.as_bytes())?;
+    stream.flush()?;
+    info!("Request sent successfully");
+    Ok(())
+}
 
-        Ok(Self {
-            status_code,
-            status_code_num,
-            meta_description,
-            body: if body.is_empty() { None } else { Some(body) },
-            links,
-        })
+/// Trust on first use: apply `policy` to the certificate presented by the host `key`.
+fn verify_certificate(
+    store: &TofuStore,
+    policy: TofuPolicy,
+    key: &str,
+    fingerprint: &str,
+) -> Result<(), RequestError> {
+    match check(policy, store.get(key).as_deref(), fingerprint) {
+        TofuDecision::Trusted => Ok(()),
+        TofuDecision::Changed(known) => {
+            warn!("The certificate of {key} changed: was {known}, is now {fingerprint}");
+            Ok(())
+        }
+        TofuDecision::Record => {
+            debug!("Trusting the certificate of {key} on first use: {fingerprint}");
+            store.trust(key, fingerprint);
+            Ok(())
+        }
+        TofuDecision::Ask => Err(RequestError::UntrustedCertificate {
+            host: key.to_string(),
+            fingerprint: fingerprint.to_string(),
+        }),
+        TofuDecision::Refuse => Err(RequestError::UnknownCertificate(key.to_string())),
+        TofuDecision::AskChanged(known) => Err(RequestError::ChangedCertificate {
+            host: key.to_string(),
+            known,
+            fingerprint: fingerprint.to_string(),
+        }),
+        TofuDecision::RefuseChanged(_) => Err(RequestError::RefusedCertificate(key.to_string())),
     }
 }
+
+/// Whether `error` is the connection breaking rather than the request failing.
+fn is_truncation(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::WouldBlock
+    )
+}
+
+impl TryFrom<&str> for Request {
+    type Error = RequestError;
+
+    fn try_from(url: &str) -> Result<Self, RequestError> {
+        info!("Parsing URL: {url}");
+        let url = Url::parse(url)?;
+        Ok(Self::new(url))
+    }
+}
+
+/// Log the header line of a raw response and the size of its body, the whole
+/// response is only logged at trace level.
+fn log_header(bytes: &[u8]) {
+    let header_end = header_len(bytes);
+    let header = String::from_utf8_lossy(&bytes[..header_end]);
+    debug!(
+        "Response header: {:?}, {} body bytes",
+        header.trim_end(),
+        bytes.len() - header_end
+    );
+}
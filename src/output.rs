@@ -0,0 +1,17 @@
+use std::fmt::Display;
+use std::io::{self, Write};
+
+/// Print `message` to `out` on its own line, unless `quiet`.
+///
+/// For messages about the session, like hints and confirmations, rather than page
+/// content. Errors aren't notices, they go to stderr through the log.
+pub fn notice_to(out: &mut impl Write, quiet: bool, message: impl Display) {
+    if !quiet {
+        let _ = writeln!(out, "{message}");
+    }
+}
+
+/// `notice_to` stdout.
+pub fn notice(quiet: bool, message: impl Display) {
+    notice_to(&mut io::stdout(), quiet, message);
+}
@@ -0,0 +1,161 @@
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{
+    self, Clear, ClearType, DisableLineWrap, EnableLineWrap, EnterAlternateScreen,
+    LeaveAlternateScreen,
+};
+use crossterm::{cursor, queue};
+use std::io::{self, stdout, Write};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set while an external pager is in the foreground. It gets the Ctrl-C typed
+/// there too, and handles it itself.
+static EXTERNAL_PAGER: AtomicBool = AtomicBool::new(false);
+
+pub fn set_external_pager_running(running: bool) {
+    EXTERNAL_PAGER.store(running, Ordering::SeqCst);
+}
+
+pub fn external_pager_running() -> bool {
+    EXTERNAL_PAGER.load(Ordering::SeqCst)
+}
+
+/// A move through the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scroll {
+    LineUp,
+    LineDown,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+}
+
+impl Scroll {
+    fn from_key(key: KeyCode) -> Option<Self> {
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => Some(Scroll::LineUp),
+            KeyCode::Down | KeyCode::Enter | KeyCode::Char('j') => Some(Scroll::LineDown),
+            KeyCode::PageUp | KeyCode::Char('b') => Some(Scroll::PageUp),
+            KeyCode::PageDown | KeyCode::Char(' ') => Some(Scroll::PageDown),
+            KeyCode::Home | KeyCode::Char('g') => Some(Scroll::Top),
+            KeyCode::End | KeyCode::Char('G') => Some(Scroll::Bottom),
+            _ => None,
+        }
+    }
+}
+
+/// First line shown once the page can't scroll any further: the last screenful.
+pub fn max_top(content_height: usize, view_height: usize) -> usize {
+    content_height.saturating_sub(view_height)
+}
+
+/// First line shown after `scroll` from `top`, for `content_height` lines seen
+/// `view_height` at a time.
+pub fn scroll(top: usize, scroll: Scroll, content_height: usize, view_height: usize) -> usize {
+    let page = view_height.max(1);
+    let top = match scroll {
+        Scroll::LineUp => top.saturating_sub(1),
+        Scroll::LineDown => top + 1,
+        Scroll::PageUp => top.saturating_sub(page),
+        Scroll::PageDown => top + page,
+        Scroll::Top => 0,
+        Scroll::Bottom => usize::MAX,
+    };
+    top.min(max_top(content_height, view_height))
+}
+
+/// Lines shown from `top`.
+pub fn visible_lines(top: usize, content_height: usize, view_height: usize) -> Range<usize> {
+    let start = top.min(content_height);
+    start..(start + view_height).min(content_height)
+}
+
+/// Show `page`, possibly ANSI styled, in the terminal until `q` is pressed, starting at
+/// its 1-based `start_line`. Used when no external pager can be started.
+pub fn run(page: &str, start_line: Option<usize>) -> io::Result<()> {
+    let lines: Vec<String> = page
+        .lines()
+        .map(|line| line.replace('\t', "    "))
+        .collect();
+    let mut out = stdout();
+
+    terminal::enable_raw_mode()?;
+    queue!(out, EnterAlternateScreen, DisableLineWrap, cursor::Hide)?;
+
+    let result = browse(&mut out, &lines, start_line.unwrap_or(1).saturating_sub(1));
+
+    queue!(out, cursor::Show, EnableLineWrap, LeaveAlternateScreen)?;
+    out.flush()?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn browse(out: &mut impl Write, lines: &[String], start: usize) -> io::Result<()> {
+    let (_, rows) = terminal::size()?;
+    // The last row is the status line
+    let mut view_height = usize::from(rows).saturating_sub(1);
+    let mut top = start.min(max_top(lines.len(), view_height));
+
+    loop {
+        draw(out, lines, top, view_height)?;
+
+        match event::read()? {
+            Event::Key(KeyEvent {
+                code,
+                kind: KeyEventKind::Press,
+                ..
+            }) => {
+                if matches!(code, KeyCode::Char('q') | KeyCode::Esc) {
+                    return Ok(());
+                }
+                if let Some(action) = Scroll::from_key(code) {
+                    top = scroll(top, action, lines.len(), view_height);
+                }
+            }
+            Event::Resize(_, rows) => {
+                view_height = usize::from(rows).saturating_sub(1);
+                top = top.min(max_top(lines.len(), view_height));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn draw(out: &mut impl Write, lines: &[String], top: usize, view_height: usize) -> io::Result<()> {
+    let visible = visible_lines(top, lines.len(), view_height);
+
+    for row in 0..view_height {
+        let line = lines
+            .get(visible.start + row)
+            .filter(|_| row < visible.len());
+        queue!(
+            out,
+            cursor::MoveTo(0, u16::try_from(row).unwrap_or(u16::MAX)),
+            Print(line.map_or("", String::as_str)),
+            SetAttribute(Attribute::Reset),
+            Clear(ClearType::UntilNewLine)
+        )?;
+    }
+
+    let status = if lines.is_empty() {
+        "(empty) — q to quit".to_string()
+    } else {
+        format!(
+            "lines {}–{} of {} — q to quit",
+            visible.start + 1,
+            visible.end,
+            lines.len()
+        )
+    };
+    queue!(
+        out,
+        cursor::MoveTo(0, u16::try_from(view_height).unwrap_or(u16::MAX)),
+        SetAttribute(Attribute::Reverse),
+        Print(status),
+        SetAttribute(Attribute::Reset),
+        Clear(ClearType::UntilNewLine)
+    )?;
+    out.flush()
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use url::{ParseError, Url};
+
+use crate::bookmarks::{remove_link, Bookmark};
+use crate::models::Link;
+use crate::tofu::{forget_link, KnownHost};
+
+/// Gemtext listing `history`, most recent first, named by page title where known.
+pub fn history_page(history: &[Url], titles: &HashMap<Url, String>) -> String {
+    let mut page = String::from("# History\n\n");
+
+    for url in history.iter().rev() {
+        match titles.get(url) {
+            Some(title) => page.push_str(&format!("=> {url} {title}\n")),
+            None => page.push_str(&format!("=> {url}\n")),
+        }
+    }
+
+    page
+}
+
+/// Gemtext listing `bookmarks`, each followed by a link removing it.
+pub fn bookmarks_page(bookmarks: &[Bookmark]) -> String {
+    let mut page = String::from("# Bookmarks\n\n");
+
+    if bookmarks.is_empty() {
+        page.push_str("No bookmarks yet, add the current page with `a` at the prompt.\n");
+    }
+
+    for bookmark in bookmarks {
+        match &bookmark.title {
+            Some(title) => page.push_str(&format!("=> {} {title}\n", bookmark.url)),
+            None => page.push_str(&format!("=> {}\n", bookmark.url)),
+        }
+        page.push_str(&format!("=> {} Remove\n", remove_link(&bookmark.url)));
+    }
+
+    page
+}
+
+/// Gemtext listing the certificates trusted for `hosts`, each followed by a link
+/// forgetting it.
+pub fn certs_page(hosts: &[(String, KnownHost)]) -> String {
+    let mut page = String::from("# Certificates\n\n");
+
+    if hosts.is_empty() {
+        page.push_str("No certificates trusted yet.\n");
+    }
+
+    for (key, known) in hosts {
+        let first_seen = known
+            .first_seen
+            .and_then(|secs| i64::try_from(secs).ok())
+            .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0));
+
+        page.push_str(&format!("\n## {key}\n\n"));
+        page.push_str(&format!("{}\n", known.fingerprint));
+        if let Some(date) = first_seen {
+            page.push_str(&format!("First seen on {}\n", date.format("%Y-%m-%d")));
+        }
+        page.push_str(&format!("=> {} Forget\n", forget_link(key)));
+    }
+
+    page
+}
+
+/// A path segment of the site map, with the pages below it.
+#[derive(Default)]
+struct SiteNode {
+    children: BTreeMap<String, SiteNode>,
+}
+
+/// Gemtext site map of `urls`, pages of a single host: a link per path segment,
+/// indented below its parent. Directories that weren't visited are listed too, so
+/// that every level can be opened.
+pub fn site_map_page(host: &str, urls: &[Url]) -> String {
+    let mut root = SiteNode::default();
+
+    for url in urls {
+        let mut node = &mut root;
+        for segment in path_segments(url.path()) {
+            node = node.children.entry(segment.to_string()).or_default();
+        }
+    }
+
+    let mut page = format!("# Site map of {host}\n\n=> / {host}\n");
+    site_map_lines(&root, "/", 1, &mut page);
+    page
+}
+
+/// `/docs/a.gmi` as `docs/` and `a.gmi`: directories keep their trailing slash.
+fn path_segments(path: &str) -> impl Iterator<Item = &str> {
+    path.trim_start_matches('/')
+        .split_inclusive('/')
+        .filter(|segment| !segment.is_empty())
+}
+
+fn site_map_lines(node: &SiteNode, path: &str, depth: usize, page: &mut String) {
+    for (segment, child) in &node.children {
+        let child_path = format!("{path}{segment}");
+        page.push_str(&format!(
+            "=> {child_path} {}{segment}\n",
+            "  ".repeat(depth)
+        ));
+        site_map_lines(child, &child_path, depth + 1, page);
+    }
+}
+
+/// Gemtext list of `links`, with their href made absolute by `resolve`. Links that
+/// don't resolve are left out.
+pub fn link_list(links: &[Link], resolve: impl Fn(&str) -> Result<Url, ParseError>) -> String {
+    let mut list = String::new();
+
+    for link in links {
+        let Ok(url) = resolve(&link.href) else {
+            continue;
+        };
+        match &link.name {
+            Some(name) => list.push_str(&format!("=> {url} {name}\n")),
+            None => list.push_str(&format!("=> {url}\n")),
+        }
+    }
+
+    list
+}
@@ -0,0 +1,564 @@
+use chrono::NaiveDate;
+use log::{debug, trace};
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+use crate::errors::ResponseError;
+use crate::feed::{feed_gemtext, parse_feed, FEED_MIME_TYPES};
+use crate::markdown;
+use crate::sniff::looks_like_gemtext;
+
+/// Maximum length in bytes of the `<META>` field of a response header.
+pub const MAX_META_LENGTH: usize = 1024;
+
+#[derive(Debug)]
+pub enum StatusCode {
+    Input,
+    Success,
+    Redirect,
+    TemporaryFailure,
+    PermanentFailure,
+    ClientCertificateRequired,
+    Unknown(u8),
+}
+
+impl From<u8> for StatusCode {
+    fn from(code: u8) -> Self {
+        match code {
+            10..=19 => StatusCode::Input,
+            20..=29 => StatusCode::Success,
+            30..=39 => StatusCode::Redirect,
+            40..=49 => StatusCode::TemporaryFailure,
+            50..=59 => StatusCode::PermanentFailure,
+            60..=69 => StatusCode::ClientCertificateRequired,
+            _ => StatusCode::Unknown(code),
+        }
+    }
+}
+
+/// The exact meaning of a status code. Codes the spec does not define fall back
+/// to the generic code of their category, e.g. 12 is treated as 10.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecificCode {
+    Input,
+    SensitiveInput,
+    Success,
+    TemporaryRedirect,
+    PermanentRedirect,
+    TemporaryFailure,
+    ServerUnavailable,
+    CgiError,
+    ProxyError,
+    SlowDown,
+    PermanentFailure,
+    NotFound,
+    Gone,
+    ProxyRequestRefused,
+    BadRequest,
+    ClientCertificateRequired,
+    CertificateNotAuthorized,
+    CertificateNotValid,
+    Unknown(u8),
+}
+
+impl SpecificCode {
+    /// Name of the status in the spec, e.g. "Not Found" for 51.
+    pub fn label(self) -> &'static str {
+        match self {
+            SpecificCode::Input => "Input",
+            SpecificCode::SensitiveInput => "Sensitive Input",
+            SpecificCode::Success => "Success",
+            SpecificCode::TemporaryRedirect => "Temporary Redirect",
+            SpecificCode::PermanentRedirect => "Permanent Redirect",
+            SpecificCode::TemporaryFailure => "Temporary Failure",
+            SpecificCode::ServerUnavailable => "Server Unavailable",
+            SpecificCode::CgiError => "CGI Error",
+            SpecificCode::ProxyError => "Proxy Error",
+            SpecificCode::SlowDown => "Slow Down",
+            SpecificCode::PermanentFailure => "Permanent Failure",
+            SpecificCode::NotFound => "Not Found",
+            SpecificCode::Gone => "Gone",
+            SpecificCode::ProxyRequestRefused => "Proxy Request Refused",
+            SpecificCode::BadRequest => "Bad Request",
+            SpecificCode::ClientCertificateRequired => "Client Certificate Required",
+            SpecificCode::CertificateNotAuthorized => "Certificate Not Authorized",
+            SpecificCode::CertificateNotValid => "Certificate Not Valid",
+            SpecificCode::Unknown(_) => "Unknown Status",
+        }
+    }
+
+    /// Transient failures worth retrying as is. A CGI error (42) is left out,
+    /// it's likely to happen again.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            SpecificCode::ServerUnavailable | SpecificCode::ProxyError
+        )
+    }
+}
+
+impl From<u8> for SpecificCode {
+    fn from(code: u8) -> Self {
+        match code {
+            11 => SpecificCode::SensitiveInput,
+            10..=19 => SpecificCode::Input,
+            20..=29 => SpecificCode::Success,
+            31 => SpecificCode::PermanentRedirect,
+            30..=39 => SpecificCode::TemporaryRedirect,
+            41 => SpecificCode::ServerUnavailable,
+            42 => SpecificCode::CgiError,
+            43 => SpecificCode::ProxyError,
+            44 => SpecificCode::SlowDown,
+            40..=49 => SpecificCode::TemporaryFailure,
+            51 => SpecificCode::NotFound,
+            52 => SpecificCode::Gone,
+            53 => SpecificCode::ProxyRequestRefused,
+            59 => SpecificCode::BadRequest,
+            50..=59 => SpecificCode::PermanentFailure,
+            61 => SpecificCode::CertificateNotAuthorized,
+            62 => SpecificCode::CertificateNotValid,
+            60..=69 => SpecificCode::ClientCertificateRequired,
+            _ => SpecificCode::Unknown(code),
+        }
+    }
+}
+
+/// A parsed `<META>` MIME type, e.g. `text/gemini; charset=utf-8; lang=en`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mime {
+    pub essence: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl Mime {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl From<&str> for Mime {
+    fn from(meta: &str) -> Self {
+        let mut parts = meta.split(';').map(str::trim);
+
+        // An empty MIME type means text/gemini (spec 3.3)
+        let essence = match parts.next() {
+            Some(essence) if !essence.is_empty() => essence.to_ascii_lowercase(),
+            _ => "text/gemini".to_string(),
+        };
+
+        let params = parts
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| {
+                (
+                    key.trim().to_ascii_lowercase(),
+                    value.trim().trim_matches('"').to_string(),
+                )
+            })
+            .collect();
+
+        Self { essence, params }
+    }
+}
+
+#[derive(Debug)]
+pub struct Link {
+    pub href: String,
+    pub name: Option<String>,
+    /// Date the name starts with, as in feed entries: `=> /post 2023-05-01 Title`.
+    pub date: Option<NaiveDate>,
+}
+
+impl Link {
+    pub fn new(href: String, name: Option<String>) -> Self {
+        let date = name.as_deref().and_then(leading_date);
+        Self { href, name, date }
+    }
+}
+
+/// A `YYYY-MM-DD` date at the very start of `text`, followed by the end of the text
+/// or anything but a digit.
+pub fn leading_date(text: &str) -> Option<NaiveDate> {
+    let candidate = text.get(..10)?;
+    let bytes = candidate.as_bytes();
+
+    let well_formed = bytes.iter().enumerate().all(|(i, b)| match i {
+        4 | 7 => *b == b'-',
+        _ => b.is_ascii_digit(),
+    });
+    let followed_by_digit = text[10..].starts_with(|c: char| c.is_ascii_digit());
+
+    if !well_formed || followed_by_digit {
+        return None;
+    }
+
+    NaiveDate::parse_from_str(candidate, "%Y-%m-%d").ok()
+}
+
+pub enum LinkParseError {
+    InvalidFormat,
+}
+
+impl TryFrom<&str> for Link {
+    type Error = LinkParseError;
+
+    fn try_from(line: &str) -> Result<Self, Self::Error> {
+        // Strip leading whitespace and check if the line starts with "=>"
+        let trimmed = line.trim_start();
+        if !trimmed.starts_with("=>") {
+            return Err(LinkParseError::InvalidFormat);
+        }
+
+        let trimmed = trimmed.trim_start_matches("=>").trim();
+
+        let parts: Vec<&str> = trimmed.splitn(2, ' ').collect();
+
+        // `=>` alone, or followed by spaces only, has no target
+        match parts.first() {
+            Some(url) if !url.is_empty() => {
+                let name = parts.get(1).map(|s| (*s).to_string());
+                Ok(Link::new((*url).to_string(), name))
+            }
+            _ => Err(LinkParseError::InvalidFormat),
+        }
+    }
+}
+
+/// Links of a gemtext body, leaving out preformatted blocks.
+fn gemtext_links(body: &str) -> Vec<Link> {
+    let mut preformatted = false;
+
+    body.lines()
+        .filter_map(|line| gemtext_link(line, &mut preformatted))
+        .collect()
+}
+
+/// Whether `line` opens or closes a preformatted block. Only lines starting with the
+/// three backticks are fences, an indented one is shown as text.
+pub fn is_fence(line: &str) -> bool {
+    line.starts_with("```")
+}
+
+/// The link `line` of a gemtext body is, if any, `preformatted` telling whether the
+/// line is in a preformatted block and updated by fence lines.
+fn gemtext_link(line: &str, preformatted: &mut bool) -> Option<Link> {
+    if is_fence(line) {
+        *preformatted = !*preformatted;
+        return None;
+    }
+    if *preformatted {
+        return None;
+    }
+    Link::try_from(line).ok()
+}
+
+/// `raw` with its lines joined by `\n`, a last line without line ending included, and
+/// its links when it is `gemtext`, both read in a single pass.
+fn split_body(raw: &str, gemtext: bool) -> (String, Vec<Link>) {
+    let mut body = String::with_capacity(raw.len());
+    let mut links = Vec::new();
+    let mut preformatted = false;
+
+    for (i, line) in raw.lines().enumerate() {
+        if i > 0 {
+            body.push('\n');
+        }
+        body.push_str(line);
+        if let Some(link) = gemtext_link(line, &mut preformatted).filter(|_| gemtext) {
+            links.push(link);
+        }
+    }
+
+    (body, links)
+}
+
+/// Whether `code` is a status code as the spec writes them, two ASCII digits. `u8`
+/// parsing alone would take `+2` or `7`.
+fn is_status_code(code: &str) -> bool {
+    code.len() == 2 && code.bytes().all(|b| b.is_ascii_digit())
+}
+
+pub struct Response {
+    pub status_code: StatusCode,
+    pub status_code_num: u8,
+    pub meta_description: String,
+    pub body: Option<String>,
+    pub links: Vec<Link>,
+    /// The connection broke before the end of the body, which is incomplete.
+    pub truncated: bool,
+    /// How the response was fetched, `None` for pages built by the client.
+    pub connection: Option<ConnectionInfo>,
+    /// The body is taken for gemtext despite its declared type.
+    pub sniffed: bool,
+}
+
+/// Measures of the connection a response was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// From connecting to the host to the end of the body.
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for ConnectionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fetched in {} ms", self.elapsed.as_millis())
+    }
+}
+
+/// A `#`, `##` or `###` line of a body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// 1 to 3, the number of `#`.
+    pub level: usize,
+    pub text: String,
+    /// 1-based line number in the body.
+    pub line: usize,
+}
+
+impl Response {
+    /// Headings of the body outside preformatted blocks, in order.
+    pub fn headings(&self) -> Vec<Heading> {
+        let mut preformatted = false;
+        let Some(body) = self.body.as_deref() else {
+            return vec![];
+        };
+
+        body.lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                if is_fence(line) {
+                    preformatted = !preformatted;
+                    return None;
+                }
+                let level = line.chars().take_while(|&c| c == '#').count();
+                let text = line[level..].trim();
+                (!preformatted && (1..=3).contains(&level) && !text.is_empty()).then(|| Heading {
+                    level,
+                    text: text.to_string(),
+                    line: i + 1,
+                })
+            })
+            .collect()
+    }
+
+    /// 1-based line of the body holding link `index`, for gemtext bodies.
+    pub fn link_line(&self, index: usize) -> Option<usize> {
+        if self.is_markdown() || self.links.is_empty() {
+            return None;
+        }
+        let mut preformatted = false;
+
+        // The same lines as `links` were read from
+        self.body
+            .as_deref()?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| gemtext_link(line, &mut preformatted).is_some())
+            .nth(index)
+            .map(|(i, _)| i + 1)
+    }
+
+    /// Text of the first heading of the body.
+    pub fn title(&self) -> Option<String> {
+        let mut preformatted = false;
+
+        self.body.as_deref()?.lines().find_map(|line| {
+            if is_fence(line) {
+                preformatted = !preformatted;
+            }
+            let heading = line.strip_prefix('#').filter(|_| !preformatted)?;
+            let title = heading.trim_start_matches('#').trim();
+            (!title.is_empty()).then(|| title.to_string())
+        })
+    }
+
+    /// Whether the body is rendered: gemtext, Markdown, a feed, or sniffed as gemtext.
+    /// Other bodies are shown as sent.
+    pub fn is_markup(&self) -> bool {
+        self.sniffed
+            || self.mime().is_none_or(|mime| {
+                mime.essence == "text/gemini" || self.is_markdown() || self.is_feed()
+            })
+    }
+
+    /// Take the body for gemtext and list its links if it looks like gemtext while
+    /// declared as something else.
+    pub fn sniff_gemtext(&mut self) {
+        if self.is_markup() {
+            return;
+        }
+        if let Some(body) = self.body.as_deref().filter(|body| looks_like_gemtext(body)) {
+            debug!("Treating a {} body as gemtext", self.meta_description);
+            self.links = gemtext_links(body);
+            self.sniffed = true;
+        }
+    }
+
+    pub fn is_markdown(&self) -> bool {
+        self.mime()
+            .is_some_and(|mime| mime.essence == "text/markdown")
+    }
+
+    /// Whether the body is an Atom or RSS feed.
+    pub fn is_feed(&self) -> bool {
+        self.mime()
+            .is_some_and(|mime| FEED_MIME_TYPES.contains(&mime.essence.as_str()))
+    }
+
+    /// The body as gemtext: feeds are turned into a list of their entries, other
+    /// bodies are returned as is.
+    pub fn gemtext(&self) -> Option<Cow<'_, str>> {
+        let body = self.body.as_deref()?;
+        match parse_feed(body).filter(|_| self.is_feed()) {
+            Some(feed) => Some(Cow::Owned(feed_gemtext(&feed))),
+            None => Some(Cow::Borrowed(body)),
+        }
+    }
+
+    pub fn specific_code(&self) -> SpecificCode {
+        SpecificCode::from(self.status_code_num)
+    }
+
+    /// The status with its number and name, then the meta: `51 Not Found: no such page`.
+    pub fn status_summary(&self) -> String {
+        format!(
+            "{} {}: {}",
+            self.status_code_num,
+            self.specific_code().label(),
+            self.meta_description
+        )
+    }
+
+    /// MIME type of a success response. Other statuses have no MIME type.
+    pub fn mime(&self) -> Option<Mime> {
+        match self.status_code {
+            StatusCode::Success => Some(Mime::from(self.meta_description.as_str())),
+            _ => None,
+        }
+    }
+}
+
+/// Length of the header of a raw response, line ending included.
+pub(crate) fn header_len(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map_or(bytes.len(), |end| end + 1)
+}
+
+/// Decode a raw response. The header line is decoded lossily, a server may send
+/// a meta in another charset even on error pages; the body must be UTF-8 unless
+/// `lossy_body`.
+pub fn decode_response(bytes: &[u8], lossy_body: bool) -> io::Result<String> {
+    let header_end = header_len(bytes);
+    let (header, body) = bytes.split_at(header_end);
+
+    let mut response = String::from_utf8_lossy(header).into_owned();
+    if lossy_body {
+        response.push_str(&String::from_utf8_lossy(body));
+    } else {
+        response.push_str(
+            std::str::from_utf8(body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        );
+    }
+
+    Ok(response)
+}
+
+impl TryFrom<&[u8]> for Response {
+    type Error = ResponseError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, ResponseError> {
+        let response = decode_response(bytes, false).map_err(ResponseError::BodyParseError)?;
+        Response::try_from(response.as_str())
+    }
+}
+
+impl TryFrom<&str> for Response {
+    type Error = ResponseError;
+
+    fn try_from(response_str: &str) -> Result<Self, ResponseError> {
+        debug!("Parsing response string");
+
+        if response_str.is_empty() {
+            return Err(ResponseError::EmptyResponse);
+        }
+
+        // A header without a line ending right before EOF still ends the header,
+        // the body is then empty
+        let (first_line, raw_body) = match response_str.split_once('\n') {
+            Some((header, body)) => (header.strip_suffix('\r').unwrap_or(header), body),
+            None => (response_str.strip_suffix('\r').unwrap_or(response_str), ""),
+        };
+
+        let mut first_line_parts = first_line.splitn(2, ' ');
+
+        let status_code_num = first_line_parts
+            .next()
+            .filter(|code| !code.is_empty())
+            .ok_or(ResponseError::MissingStatusCode)?;
+        if !is_status_code(status_code_num) {
+            return Err(ResponseError::InvalidStatusCode);
+        }
+        let status_code_num = status_code_num
+            .parse::<u8>()
+            .map_err(|_| ResponseError::InvalidStatusCode)?;
+
+        let status_code = StatusCode::from(status_code_num);
+
+        let meta_description = first_line_parts
+            .next()
+            .ok_or(ResponseError::MissingMetaDescription)?
+            .to_string();
+
+        if meta_description.len() > MAX_META_LENGTH {
+            return Err(ResponseError::MetaTooLong(meta_description.len()));
+        }
+        // A lone `\r` or a NUL would end up in the prompt or the status line
+        if meta_description
+            .chars()
+            .any(|c| c.is_control() && c != '\t')
+        {
+            return Err(ResponseError::ControlInMeta);
+        }
+
+        let essence = matches!(status_code, StatusCode::Success)
+            .then(|| Mime::from(meta_description.as_str()).essence);
+        let is_gemtext = essence
+            .as_deref()
+            .is_none_or(|essence| essence == "text/gemini");
+        let (body, body_links) = split_body(raw_body, is_gemtext);
+        let feed = essence
+            .as_deref()
+            .filter(|essence| FEED_MIME_TYPES.contains(essence))
+            .and_then(|_| parse_feed(&body));
+
+        // Other types are shown as sent, without links
+        let links = if essence.as_deref() == Some("text/markdown") {
+            markdown::extract_links(&body)
+        } else if let Some(feed) = feed {
+            gemtext_links(&feed_gemtext(&feed))
+        } else {
+            body_links
+        };
+
+        trace!(
+            "Response parsed: status_code={status_code:?}, meta={meta_description}, body={body:?}, links={links:?}",
+        );
+
+        Ok(Self {
+            status_code,
+            status_code_num,
+            meta_description,
+            body: if body.is_empty() { None } else { Some(body) },
+            links,
+            truncated: false,
+            connection: None,
+            sniffed: false,
+        })
+    }
+}
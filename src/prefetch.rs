@@ -0,0 +1,70 @@
+use log::debug;
+use std::thread;
+use url::{ParseError, Url};
+
+use crate::client::Client;
+use crate::models::{Link, Response, StatusCode};
+use crate::protocol::protocol_for;
+
+/// Most links fetched by one prefetch, whatever `prefetch_links` is set to.
+pub const MAX_PREFETCH: usize = 10;
+
+/// Prefetching more links than this asks for confirmation first.
+pub const CONFIRM_PREFETCH: usize = 5;
+
+/// The pages to prefetch among `links`: the first `count` distinct Gemini or Spartan
+/// pages they resolve to, without fragment, at most `MAX_PREFETCH`.
+pub fn prefetch_targets(
+    links: &[Link],
+    count: usize,
+    resolve: impl Fn(&str) -> Result<Url, ParseError>,
+) -> Vec<Url> {
+    let mut targets: Vec<Url> = Vec::new();
+
+    for link in links {
+        if targets.len() >= count.min(MAX_PREFETCH) {
+            break;
+        }
+        let Ok(mut url) = resolve(&link.href) else {
+            continue;
+        };
+        url.set_fragment(None);
+        if protocol_for(url.scheme()).is_some() && !targets.contains(&url) {
+            targets.push(url);
+        }
+    }
+
+    targets
+}
+
+/// Fetch `targets` one after the other, `config.crawl_delay` apart, and keep the
+/// successful responses for when they are followed.
+pub fn prefetch(client: &mut Client, targets: &[Url]) -> usize {
+    let mut fetched = 0;
+
+    for (n, url) in targets.iter().enumerate() {
+        if n > 0 {
+            thread::sleep(client.config.crawl_delay);
+        }
+        debug!("Prefetching {url}");
+
+        match client.build_request(url.clone()).send() {
+            Ok(Ok(response)) if matches!(response.status_code, StatusCode::Success) => {
+                client.prefetched.insert(url.clone(), response);
+                fetched += 1;
+            }
+            Ok(Ok(response)) => debug!("Not keeping {url}: {}", response.status_summary()),
+            Ok(Err(e)) => debug!("Could not prefetch {url}: {e}"),
+            Err(e) => debug!("Could not prefetch {url}: {e}"),
+        }
+    }
+
+    fetched
+}
+
+/// The response prefetched for `url`, taken out so that it is shown only once.
+pub fn take_prefetched(client: &mut Client, url: &Url) -> Option<Response> {
+    let mut key = url.clone();
+    key.set_fragment(None);
+    client.prefetched.remove(&key)
+}
@@ -0,0 +1,60 @@
+/// The prompt shown below a page when `prompt` isn't set in the configuration.
+pub const DEFAULT_PROMPT: &str = "Select a link by number ([.] first link) or type a new URL ([q]uit [b]ack [f]orward [r]eload [R] hard reload [e]dit [^]up [/]toggle slash [h]istory [g]o to heading [a]dd bookmark [bm] bookmarks [check] links [O] prefetch links [T] plain view): ";
+
+/// What the placeholders of a prompt template stand for.
+#[derive(Debug, Clone, Copy)]
+pub struct PromptState<'a> {
+    pub url: &'a str,
+    pub host: &'a str,
+    pub title: &'a str,
+    pub links: usize,
+}
+
+/// `template` with `{url}`, `{host}`, `{title}` and `{n}`, the number of links,
+/// replaced from `state`. `{{` and `}}` stand for braces, unknown placeholders are
+/// left as they are.
+pub fn expand_prompt(template: &str, state: &PromptState) -> String {
+    let mut prompt = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        prompt.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("{{") {
+            prompt.push('{');
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("}}") {
+            prompt.push('}');
+            rest = after;
+            continue;
+        }
+
+        let expanded = rest.strip_prefix('{').and_then(|inner| {
+            let end = inner.find('}')?;
+            let value = match &inner[..end] {
+                "url" => state.url.to_string(),
+                "host" => state.host.to_string(),
+                "title" => state.title.to_string(),
+                "n" => state.links.to_string(),
+                _ => return None,
+            };
+            Some((value, &inner[end + 1..]))
+        });
+        match expanded {
+            Some((value, after)) => {
+                prompt.push_str(&value);
+                rest = after;
+            }
+            None => {
+                prompt.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    prompt.push_str(rest);
+    prompt
+}
@@ -0,0 +1,106 @@
+use percent_encoding::percent_decode_str;
+use std::fmt::Debug;
+use url::Url;
+
+/// What differs between Gemini and its siblings on the wire.
+pub trait Protocol: Debug + Send + Sync {
+    fn default_port(&self) -> u16;
+
+    /// What is sent to the server for `url`: the request line with its line ending,
+    /// and any data uploaded with it.
+    fn build_request_line(&self, url: &Url) -> String;
+
+    /// Whether the connection is wrapped in TLS.
+    fn uses_tls(&self) -> bool {
+        true
+    }
+
+    /// The response header line rewritten with Gemini status codes, which is how
+    /// `Response` understands it.
+    fn gemini_header(&self, header: &str) -> String {
+        header.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Gemini;
+
+impl Protocol for Gemini {
+    fn default_port(&self) -> u16 {
+        1965
+    }
+
+    /// The absolute URL, with port and query but without fragment.
+    fn build_request_line(&self, url: &Url) -> String {
+        let mut url = url.clone();
+        url.set_fragment(None);
+        format!("{url}\r\n")
+    }
+}
+
+/// Spartan, Gemini over plain TCP with single digit statuses and data uploads
+/// instead of queries.
+#[derive(Debug, Clone, Copy)]
+pub struct Spartan;
+
+impl Protocol for Spartan {
+    fn default_port(&self) -> u16 {
+        300
+    }
+
+    /// `host path content-length`, followed by the query of `url` as data. A URL
+    /// without path, like `spartan://example.org`, asks for `/`.
+    fn build_request_line(&self, url: &Url) -> String {
+        let data = url
+            .query()
+            .map(|query| percent_decode_str(query).decode_utf8_lossy().into_owned())
+            .unwrap_or_default();
+        let path = match url.path() {
+            "" => "/",
+            path => path,
+        };
+        format!(
+            "{} {path} {}\r\n{data}",
+            url.host_str().unwrap_or_default(),
+            data.len()
+        )
+    }
+
+    fn uses_tls(&self) -> bool {
+        false
+    }
+
+    fn gemini_header(&self, header: &str) -> String {
+        let (status, meta) = header.split_once(' ').unwrap_or((header, ""));
+        let status =
+            spartan_status(status.trim()).map_or_else(|| status.to_string(), |s| s.to_string());
+        format!("{status} {meta}")
+    }
+}
+
+/// Gemini equivalent of a Spartan status: 2 success, 3 redirect, 4 client error and
+/// 5 server error.
+pub fn spartan_status(status: &str) -> Option<u8> {
+    match status {
+        "2" => Some(20),
+        "3" => Some(30),
+        "4" => Some(59),
+        "5" => Some(40),
+        _ => None,
+    }
+}
+
+/// The protocol of `scheme`, `None` for schemes the client can't fetch.
+pub fn protocol_for(scheme: &str) -> Option<&'static dyn Protocol> {
+    match scheme {
+        "gemini" => Some(&Gemini),
+        "spartan" => Some(&Spartan),
+        _ => None,
+    }
+}
+
+/// Port to connect to for `url`: its own, or the default one of its protocol.
+pub fn port_of(url: &Url) -> u16 {
+    url.port()
+        .unwrap_or_else(|| protocol_for(url.scheme()).unwrap_or(&Gemini).default_port())
+}
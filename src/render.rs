@@ -0,0 +1,604 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use colored::{ColoredString, Colorize};
+use crossterm::terminal;
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::time::SystemTime;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use url::Url;
+
+use crate::links::classify_link;
+use crate::markdown;
+use crate::models::{is_fence, Link, Response};
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[clap(rename_all = "lower")]
+pub enum RendererKind {
+    Plain,
+    Gemtext,
+    Html,
+}
+
+/// What happens to preformatted lines wider than the terminal.
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[clap(rename_all = "lower")]
+pub enum PreformattedOverflow {
+    // Left whole, for pagers that scroll sideways
+    #[default]
+    Scroll,
+    // Cut at the edge of the terminal with a `→`
+    Truncate,
+}
+
+/// How the dates starting link names, as in feeds, are shown.
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[clap(rename_all = "lower")]
+pub enum DateDisplay {
+    // As written, e.g. `2024-05-01`
+    #[default]
+    Absolute,
+    // How long ago, e.g. `3 days ago`
+    Relative,
+}
+
+/// How long before `today` `date` is, in words: `today`, `yesterday`, then days,
+/// weeks, months of 30 days up to 11 and years. Dates after `today` are `in N days`.
+pub fn relative_date(date: NaiveDate, today: NaiveDate) -> String {
+    let days = (today - date).num_days();
+    let (count, unit) = match days {
+        ..=-2 => return format!("in {} days", -days),
+        -1 => return "tomorrow".to_string(),
+        0 => return "today".to_string(),
+        1 => return "yesterday".to_string(),
+        2..=6 => (days, "day"),
+        7..=29 => (days / 7, "week"),
+        30..=364 => ((days / 30).min(11), "month"),
+        _ => (days / 365, "year"),
+    };
+    if count == 1 {
+        format!("1 {unit} ago")
+    } else {
+        format!("{count} {unit}s ago")
+    }
+}
+
+/// Name of `link` with its leading date shown as `dates` says, `today` being the
+/// reference of relative dates.
+pub fn dated_name(link: &Link, dates: DateDisplay, today: NaiveDate) -> Option<String> {
+    let name = link.name.as_deref()?;
+    match (dates, link.date) {
+        (DateDisplay::Relative, Some(date)) => Some(format!(
+            "{} {}",
+            relative_date(date, today),
+            name[10..].trim_start()
+        )),
+        _ => Some(name.to_string()),
+    }
+}
+
+/// Turns a response body into the text that is piped to the pager.
+pub trait Renderer {
+    fn render(&self, response: &Response, base: &Url) -> String;
+
+    /// Line of the rendered page showing `line` of the body of `response`, both 1-based.
+    fn rendered_line(&self, _response: &Response, line: usize) -> usize {
+        line
+    }
+}
+
+impl RendererKind {
+    pub fn renderer(
+        self,
+        inline_link_numbers: bool,
+        preformatted_overflow: PreformattedOverflow,
+        dates: DateDisplay,
+    ) -> Box<dyn Renderer> {
+        match self {
+            RendererKind::Plain => Box::new(PlainRenderer {
+                inline_link_numbers,
+            }),
+            RendererKind::Gemtext => Box::new(GemtextRenderer {
+                inline_link_numbers,
+                preformatted_overflow,
+                dates,
+            }),
+            RendererKind::Html => Box::new(HtmlRenderer),
+        }
+    }
+}
+
+/// The body as sent by the server, optionally with gemtext link numbers.
+pub struct PlainRenderer {
+    pub inline_link_numbers: bool,
+}
+
+impl Renderer for PlainRenderer {
+    fn render(&self, response: &Response, _base: &Url) -> String {
+        let body = response.body.as_deref().unwrap_or("No content");
+
+        if self.inline_link_numbers && !response.is_markdown() {
+            number_link_lines(body)
+        } else {
+            body.to_string()
+        }
+    }
+}
+
+/// Colored gemtext: styled headings, links, lists and quotes. Markdown bodies get
+/// the equivalent Markdown styling, feeds are shown as the list of their entries.
+pub struct GemtextRenderer {
+    pub inline_link_numbers: bool,
+    pub preformatted_overflow: PreformattedOverflow,
+    pub dates: DateDisplay,
+}
+
+impl Renderer for GemtextRenderer {
+    fn render(&self, response: &Response, _base: &Url) -> String {
+        let Some(body) = response.gemtext() else {
+            return "No content".italic().to_string();
+        };
+
+        if response.is_markdown() {
+            return markdown::render(&body, self.inline_link_numbers);
+        }
+
+        let mut preformatted = false;
+        let mut count: usize = 0;
+        let mut lines = Vec::new();
+        let today = DateTime::<Utc>::from(SystemTime::now()).date_naive();
+        // Soft wrapping would break ASCII art apart
+        let preformatted_width = match self.preformatted_overflow {
+            PreformattedOverflow::Scroll => None,
+            PreformattedOverflow::Truncate => {
+                Some(terminal::size().map_or(80, |(columns, _)| usize::from(columns)))
+            }
+        };
+
+        for line in body.lines() {
+            if is_fence(line) {
+                if let Some(alt) = fence_alt_text(line).filter(|_| !preformatted) {
+                    lines.push(alt.italic().dimmed().to_string());
+                }
+                preformatted = !preformatted;
+                continue;
+            }
+
+            if preformatted {
+                match preformatted_width {
+                    Some(width) => lines.push(truncate_preformatted(line, width)),
+                    None => lines.push(line.to_string()),
+                }
+                continue;
+            }
+
+            let rendered = if let Ok(link) = Link::try_from(line) {
+                let text = dated_name(&link, self.dates, today)
+                    .unwrap_or_else(|| link.href.clone())
+                    .bright_white();
+                let rendered = if self.inline_link_numbers {
+                    format!("{} {text}", format!("({count})").blue())
+                } else {
+                    format!("{} {text}", "=>".blue())
+                };
+                count += 1;
+                rendered
+            } else if let Some(heading) = line.strip_prefix("###") {
+                heading.trim().green().to_string()
+            } else if let Some(heading) = line.strip_prefix("##") {
+                heading.trim().green().bold().to_string()
+            } else if let Some(heading) = line.strip_prefix('#') {
+                heading.trim().bright_green().bold().underline().to_string()
+            } else if let Some(item) = line.strip_prefix("* ") {
+                format!("• {item}")
+            } else if let Some(quote) = line.strip_prefix('>') {
+                format!("> {}", quote.trim_start())
+                    .italic()
+                    .dimmed()
+                    .to_string()
+            } else {
+                line.to_string()
+            };
+
+            lines.push(rendered);
+        }
+
+        lines.join("\n")
+    }
+
+    /// Fence lines of preformatted blocks are not shown, except for the alt text of
+    /// gemtext blocks.
+    fn rendered_line(&self, response: &Response, line: usize) -> usize {
+        let markdown = response.is_markdown();
+        let mut preformatted = false;
+
+        let hidden = response
+            .body
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .take(line.saturating_sub(1))
+            .filter(|line| {
+                if !is_fence(line) {
+                    return false;
+                }
+                let caption = !markdown && !preformatted && fence_alt_text(line).is_some();
+                preformatted = !preformatted;
+                !caption
+            })
+            .count();
+        line - hidden
+    }
+}
+
+/// A standalone HTML document, with links resolved against the page URL.
+pub struct HtmlRenderer;
+
+impl Renderer for HtmlRenderer {
+    fn render(&self, response: &Response, base: &Url) -> String {
+        let mut html = String::from("<!DOCTYPE html>\n<html>\n<body>\n");
+
+        if response.is_markdown() {
+            let body = response.body.as_deref().unwrap_or("");
+            html.push_str(&format!(
+                "<pre>\n{}\n</pre>\n</body>\n</html>",
+                escape_html(body)
+            ));
+            return html;
+        }
+
+        let mut preformatted = false;
+        let mut in_list = false;
+
+        for line in response.gemtext().unwrap_or_default().lines() {
+            if is_fence(line) {
+                match fence_alt_text(line) {
+                    _ if preformatted => html.push_str("</pre>\n"),
+                    Some(alt) => html.push_str(&format!(
+                        "<pre aria-label=\"{}\" title=\"{0}\">\n",
+                        escape_html(alt)
+                    )),
+                    None => html.push_str("<pre>\n"),
+                }
+                preformatted = !preformatted;
+                continue;
+            }
+
+            if preformatted {
+                html.push_str(&escape_html(line));
+                html.push('\n');
+                continue;
+            }
+
+            let item = line.strip_prefix("* ");
+            if in_list && item.is_none() {
+                html.push_str("</ul>\n");
+            } else if !in_list && item.is_some() {
+                html.push_str("<ul>\n");
+            }
+            in_list = item.is_some();
+
+            let element = if let Ok(link) = Link::try_from(line) {
+                let href = base
+                    .join(&link.href)
+                    .map_or_else(|_| link.href.clone(), |url| url.to_string());
+                let text = link.name.as_deref().unwrap_or(&link.href);
+                format!(
+                    "<p><a href=\"{}\">{}</a></p>",
+                    escape_html(&href),
+                    escape_html(text)
+                )
+            } else if let Some(item) = item {
+                format!("<li>{}</li>", escape_html(item))
+            } else if let Some(heading) = line.strip_prefix("###") {
+                format!("<h3>{}</h3>", escape_html(heading.trim()))
+            } else if let Some(heading) = line.strip_prefix("##") {
+                format!("<h2>{}</h2>", escape_html(heading.trim()))
+            } else if let Some(heading) = line.strip_prefix('#') {
+                format!("<h1>{}</h1>", escape_html(heading.trim()))
+            } else if let Some(quote) = line.strip_prefix('>') {
+                format!("<blockquote>{}</blockquote>", escape_html(quote.trim()))
+            } else if line.trim().is_empty() {
+                continue;
+            } else {
+                format!("<p>{}</p>", escape_html(line))
+            };
+
+            html.push_str(&element);
+            html.push('\n');
+        }
+
+        if in_list {
+            html.push_str("</ul>\n");
+        }
+        if preformatted {
+            html.push_str("</pre>\n");
+        }
+
+        html.push_str("</body>\n</html>");
+        html
+    }
+}
+
+/// Alt text of the opening fence of a preformatted block: `table` for "```table".
+pub fn fence_alt_text(line: &str) -> Option<&str> {
+    let alt = line.strip_prefix("```")?.trim();
+    (!alt.is_empty()).then_some(alt)
+}
+
+/// Prefix every link line of a gemtext body with its index in `Response::links`.
+pub fn number_link_lines(body: &str) -> String {
+    let mut preformatted = false;
+    let mut count: usize = 0;
+
+    body.lines()
+        .map(|line| {
+            if is_fence(line) {
+                preformatted = !preformatted;
+            }
+
+            if !preformatted && Link::try_from(line).is_ok() {
+                let result = format!("({}) {}", count, line.trim_start());
+                count += 1;
+                result
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Prefix every line of a plain text body with its 1-based line number, right-aligned.
+pub fn number_lines(body: &str) -> String {
+    let width = body.lines().count().to_string().len();
+
+    body.lines()
+        .enumerate()
+        .map(|(i, line)| format!("{} {line}", format!("{:>width$}", i + 1).dimmed()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Widest a link name gets in the link list before being truncated.
+const MAX_NAME_WIDTH: usize = 40;
+
+/// The list of links shown below the page, one line per link: indices right-aligned
+/// and names padded so that hrefs line up, hrefs colored by what they point to.
+/// `highlighted` is shown in bold yellow. Links whose entry in `visited` is set are
+/// marked with a ✓ and dimmed, those whose entry in `spoofed` is set, links named
+/// after another URL than theirs, get a red ⚠ before their href.
+pub fn link_footer(
+    links: &[Link],
+    highlighted: Option<usize>,
+    visited: &[bool],
+    spoofed: &[bool],
+) -> Vec<String> {
+    let index_width = links.len().saturating_sub(1).to_string().len();
+    let name_width = links
+        .iter()
+        .map(|link| link.name.as_deref().unwrap_or("").width())
+        .max()
+        .unwrap_or(0)
+        .min(MAX_NAME_WIDTH);
+    let any_visited = visited.contains(&true);
+
+    links
+        .iter()
+        .enumerate()
+        .map(|(i, link)| {
+            let is_visited = visited.get(i) == Some(&true);
+            let class = classify_link(&link.href);
+            let name = fit_to_width(link.name.as_deref().unwrap_or(""), name_width);
+            let marker = match (any_visited, is_visited) {
+                (true, true) => "✓ ",
+                (true, false) => "  ",
+                (false, _) => "",
+            };
+            format!(
+                "{}: {marker}{} ({}{})",
+                format!("{i:>index_width$}").blue(),
+                style_name(name, highlighted == Some(i), is_visited),
+                spoof_warning(spoofed, i),
+                format!("{}{}", class.prefix(), display_href(&link.href)).color(class.color())
+            )
+        })
+        .collect()
+}
+
+/// The list of links packed into lines of at most `width` columns: `[N] name` for
+/// each link, or `[N] href` for links without a name. `highlighted`, `visited` and
+/// `spoofed` are shown as in `link_footer`.
+pub fn compact_link_footer(
+    links: &[Link],
+    highlighted: Option<usize>,
+    visited: &[bool],
+    spoofed: &[bool],
+    width: usize,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut used = 0;
+
+    for (i, link) in links.iter().enumerate() {
+        let is_visited = visited.get(i) == Some(&true);
+        let href = display_href(&link.href);
+        let text = link.name.as_deref().unwrap_or(&href);
+        let label = fit_to_width(text, text.width().min(MAX_NAME_WIDTH));
+        let index = if is_visited {
+            format!("[{i}] ✓")
+        } else {
+            format!("[{i}]")
+        };
+        let warning = spoof_warning(spoofed, i);
+        let item_width = index.width() + 1 + warning.chars().count() + label.width();
+
+        if used > 0 && used + 2 + item_width > width {
+            lines.push(std::mem::take(&mut line));
+            used = 0;
+        }
+        if used > 0 {
+            line.push_str("  ");
+            used += 2;
+        }
+
+        let label = style_name(label, highlighted == Some(i), is_visited);
+        line.push_str(&format!("{} {warning}{label}", index.blue()));
+        used += item_width;
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// `⚠ ` in red for a link marked in `spoofed`, nothing otherwise.
+fn spoof_warning(spoofed: &[bool], index: usize) -> ColoredString {
+    if spoofed.get(index) == Some(&true) {
+        "⚠ ".red().bold()
+    } else {
+        "".normal()
+    }
+}
+
+fn style_name(name: String, highlighted: bool, visited: bool) -> ColoredString {
+    if highlighted {
+        name.bright_yellow().bold()
+    } else if visited {
+        name.dimmed()
+    } else {
+        name.bright_white()
+    }
+}
+
+/// `href` percent-decoded to be read by a human, e.g. `/caf%C3%A9.gmi` as `/café.gmi`.
+/// It is shown as is when it doesn't decode to UTF-8 or decodes to control characters.
+/// Bidirectional formatting characters, which could make it read as another URL, are
+/// left out.
+pub fn display_href(href: &str) -> Cow<'_, str> {
+    let shown = match percent_decode_str(href).decode_utf8() {
+        Ok(decoded) if !decoded.chars().any(char::is_control) => decoded,
+        _ => Cow::Borrowed(href),
+    };
+    if shown.chars().any(is_bidi_control) {
+        Cow::Owned(shown.chars().filter(|&c| !is_bidi_control(c)).collect())
+    } else {
+        shown
+    }
+}
+
+/// Marks, embeddings, overrides and isolates changing the direction of text.
+fn is_bidi_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// `text` cut to at most `width` columns, ending with `…` when it was cut.
+pub fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    fit_to_width(text, width).trim_end().to_string()
+}
+
+/// A preformatted `line` cut to `width` columns, the last one showing `→` when it was
+/// cut. Unlike `truncate_to_width`, trailing spaces are kept, they can be part of
+/// the drawing.
+pub fn truncate_preformatted(line: &str, width: usize) -> String {
+    if line.width() <= width {
+        return line.to_string();
+    }
+
+    let mut cut = String::new();
+    let mut used = 0;
+    for c in line.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width + 1 > width {
+            break;
+        }
+        cut.push(c);
+        used += char_width;
+    }
+    cut.push('→');
+    cut
+}
+
+/// `text` truncated with `…` or padded with spaces to exactly `width` columns.
+fn fit_to_width(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return format!("{text}{}", " ".repeat(width - text.width()));
+    }
+
+    let mut fitted = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width + 1 > width {
+            break;
+        }
+        fitted.push(c);
+        used += char_width;
+    }
+    fitted.push('…');
+    used += 1;
+
+    format!("{fitted}{}", " ".repeat(width.saturating_sub(used)))
+}
+
+/// `text` without ANSI escape sequences: CSI sequences such as colors (`ESC [ ... m`),
+/// OSC sequences (`ESC ] ... BEL`) and other escapes (`ESC ( B`).
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            stripped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('[') => {
+                // Parameters and intermediates up to the final byte, `@` to `~`
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                // Terminated by BEL or by ST, `ESC \`
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            Some(c) if (' '..='/').contains(&c) => {
+                // Intermediates, e.g. the `(` of `ESC ( B`, then the final byte
+                for c in chars.by_ref() {
+                    if !(' '..='/').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    stripped
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
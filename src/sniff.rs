@@ -0,0 +1,38 @@
+/// Share of control characters above which a body is taken for binary data.
+const MAX_CONTROL_SHARE: f64 = 0.01;
+
+/// Whether `body` looks like gemtext whatever its declared type: it has a link line
+/// or a heading at the start of a line, and isn't binary.
+pub fn looks_like_gemtext(body: &str) -> bool {
+    !looks_binary(body) && body.lines().any(|line| is_link(line) || is_heading(line))
+}
+
+/// `=>` followed by a URL.
+fn is_link(line: &str) -> bool {
+    line.strip_prefix("=>")
+        .is_some_and(|rest| !rest.trim().is_empty())
+}
+
+/// One to three `#` followed by a space, a shebang or a hashtag isn't a heading.
+fn is_heading(line: &str) -> bool {
+    let text = line.trim_start_matches('#');
+    let level = line.len() - text.len();
+    (1..=3).contains(&level) && text.starts_with([' ', '\t']) && !text.trim().is_empty()
+}
+
+/// NUL bytes, replacement characters of bytes that weren't UTF-8, or many control
+/// characters.
+fn looks_binary(body: &str) -> bool {
+    if body.contains(['\0', char::REPLACEMENT_CHARACTER]) {
+        return true;
+    }
+
+    let controls = body
+        .chars()
+        .filter(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+        .count();
+    let total = body.chars().count();
+    #[allow(clippy::cast_precision_loss)]
+    let share = controls as f64 / total.max(1) as f64;
+    share > MAX_CONTROL_SHARE
+}
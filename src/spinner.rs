@@ -0,0 +1,81 @@
+use crossterm::{
+    cursor::MoveToColumn,
+    queue,
+    style::Print,
+    terminal::{Clear, ClearType},
+};
+use std::io::{stderr, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const FRAME_DURATION: Duration = Duration::from_millis(80);
+
+/// A spinner drawn on the current stderr line until stopped or dropped.
+pub struct Spinner {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    pub fn start(message: &str) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let message = message.to_string();
+
+        let handle = {
+            let running = Arc::clone(&running);
+            thread::spawn(move || {
+                let mut stderr = stderr();
+                for frame in FRAMES.iter().cycle() {
+                    if !running.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    queue!(
+                        stderr,
+                        MoveToColumn(0),
+                        Clear(ClearType::CurrentLine),
+                        Print(format!("{frame} {message}"))
+                    )
+                    .ok();
+                    stderr.flush().ok();
+                    thread::sleep(FRAME_DURATION);
+                }
+                queue!(stderr, MoveToColumn(0), Clear(ClearType::CurrentLine)).ok();
+                stderr.flush().ok();
+            })
+        };
+
+        Self {
+            running,
+            handle: Some(handle),
+        }
+    }
+
+    /// A spinner that never draws, for when output is not interactive.
+    pub fn disabled() -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(false)),
+            handle: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Stop the spinner and erase its line. Calling it again does nothing.
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            handle.join().ok();
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,164 @@
+use clap::ValueEnum;
+use log::warn;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+use crate::client::Client;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+#[clap(rename_all = "lower")]
+pub enum SessionMode {
+    /// Do not save anything
+    Off,
+    /// Save the last visited page
+    Last,
+    /// Save the last visited page and the history
+    History,
+}
+
+/// What is kept of a browsing session between launches.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SessionState {
+    pub last_url: Option<Url>,
+    pub history: Vec<Url>,
+    pub titles: HashMap<Url, String>,
+}
+
+impl SessionState {
+    /// Snapshot of `client` for `mode`, leaving out URLs that carry sensitive input.
+    pub fn from_client(client: &Client, mode: SessionMode) -> Self {
+        let keep = |url: &&Url| client.is_listable(url);
+
+        let last_url = client.last_working_url.as_ref().filter(keep).cloned();
+        let history = match mode {
+            SessionMode::History => client.history.iter().filter(keep).cloned().collect(),
+            SessionMode::Off | SessionMode::Last => vec![],
+        };
+
+        let titles = client
+            .titles
+            .iter()
+            .filter(|(url, _)| history.contains(url) || last_url.as_ref() == Some(*url))
+            .map(|(url, title)| (url.clone(), title.clone()))
+            .collect();
+
+        Self {
+            last_url,
+            history,
+            titles,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut contents = String::new();
+        for url in &self.history {
+            contents.push_str(&format!("history {url}\n"));
+        }
+        for (url, title) in &self.titles {
+            contents.push_str(&format!("title {url} {title}\n"));
+        }
+        if let Some(url) = &self.last_url {
+            contents.push_str(&format!("last {url}\n"));
+        }
+
+        fs::write(path, contents)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut state = Self::default();
+
+        for line in fs::read_to_string(path)?.lines() {
+            let Some((kind, rest)) = line.split_once(' ') else {
+                continue;
+            };
+            let (url, title) = match rest.split_once(' ') {
+                Some((url, title)) if kind == "title" => (url, title),
+                _ => (rest, ""),
+            };
+            let url = match Url::parse(url) {
+                Ok(url) => url,
+                Err(e) => {
+                    warn!("Skipping invalid URL in session state: {e}");
+                    continue;
+                }
+            };
+            match kind {
+                "history" => state.history.push(url),
+                "last" => state.last_url = Some(url),
+                "title" => {
+                    state.titles.insert(url, title.to_string());
+                }
+                _ => warn!("Unknown session state entry: {kind}"),
+            }
+        }
+
+        Ok(state)
+    }
+}
+
+/// The latest session state, kept up to date while browsing so that it can be saved
+/// from a signal handler as well as on a normal exit. Clones share the same state.
+#[derive(Debug, Clone)]
+pub struct SessionFlush {
+    path: PathBuf,
+    mode: SessionMode,
+    /// `None` before the first snapshot, nothing is saved over the previous session then.
+    latest: Arc<Mutex<Option<SessionState>>>,
+    saved: Arc<AtomicBool>,
+}
+
+impl SessionFlush {
+    pub fn new(path: PathBuf, mode: SessionMode) -> Self {
+        Self {
+            path,
+            mode,
+            latest: Arc::new(Mutex::new(None)),
+            saved: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Take a new snapshot of `client`, unless the session was saved already.
+    pub fn update(&self, client: &Client) {
+        if let Ok(mut latest) = self.latest.lock() {
+            if !self.saved.load(Ordering::SeqCst) {
+                *latest = Some(SessionState::from_client(client, self.mode));
+            }
+        }
+    }
+
+    /// Save the latest snapshot. Only the first call saves, later ones return
+    /// `Ok(false)`; a call made while another one saves waits for it to finish.
+    pub fn flush(&self) -> io::Result<bool> {
+        let Ok(mut latest) = self.latest.lock() else {
+            return Ok(false);
+        };
+        if self.saved.swap(true, Ordering::SeqCst) {
+            return Ok(false);
+        }
+        match latest.take() {
+            Some(state) => state.save(&self.path).map(|()| true),
+            None => Ok(false),
+        }
+    }
+}
+
+/// Directory for files the client keeps between launches, following the XDG base directory spec.
+pub fn state_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))
+        .map(|dir| dir.join("gemini"))
+}
+
+pub fn session_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("session"))
+}
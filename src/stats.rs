@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use crate::models::is_fence;
+
+/// Reading speed used for reading time estimates, in words per minute.
+pub const WORDS_PER_MINUTE: u64 = 200;
+
+/// Word count and estimated reading time of a gemtext body. Preformatted blocks
+/// and link lines are not prose and aren't counted.
+pub fn reading_stats(body: &str) -> (usize, Duration) {
+    let mut preformatted = false;
+
+    let words = body
+        .lines()
+        .filter(|line| {
+            if is_fence(line) {
+                preformatted = !preformatted;
+                return false;
+            }
+            !preformatted && !line.starts_with("=>")
+        })
+        .map(|line| {
+            line.split_whitespace()
+                .filter(|w| w.chars().any(char::is_alphanumeric))
+                .count()
+        })
+        .sum::<usize>();
+
+    let seconds = words as u64 * 60 / WORDS_PER_MINUTE;
+    (words, Duration::from_secs(seconds))
+}
+
+/// `523 words, 3 min read`
+pub fn format_reading_stats(words: usize, time: Duration) -> String {
+    let minutes = time.as_secs().div_ceil(60).max(1);
+    format!("{words} words, {minutes} min read")
+}
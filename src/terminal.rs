@@ -0,0 +1,42 @@
+use crossterm::{cursor, execute, style::ResetColor, terminal};
+use std::io::stdout;
+use std::panic;
+use std::sync::Once;
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Restores the terminal when dropped, and on panic.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn new() -> Self {
+        PANIC_HOOK.call_once(|| {
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                restore_terminal();
+                previous(info);
+            }));
+        });
+
+        Self
+    }
+}
+
+impl Default for TerminalGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Leave raw mode, show the cursor and reset colors. Errors are ignored, there's
+/// nothing left to do about them at this point.
+pub fn restore_terminal() {
+    terminal::disable_raw_mode().ok();
+    execute!(stdout(), cursor::Show, ResetColor).ok();
+}
@@ -0,0 +1,219 @@
+use log::warn;
+use ring::digest::{digest, SHA256};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::form_urlencoded;
+
+use crate::state::state_dir;
+
+/// The page listing the certificates trusted so far.
+pub const CERTS_URL: &str = "about:certs";
+
+/// What to do the first time a host presents a certificate, or when it presents
+/// another one than the certificate it was trusted with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TofuPolicy {
+    /// Trust it and remember it, a changed certificate is only warned about.
+    #[default]
+    Silent,
+    /// Show its fingerprint and ask whether to trust it.
+    Prompt,
+    /// Refuse to connect, only hosts already known with this certificate are allowed.
+    Strict,
+}
+
+/// Outcome of checking a certificate against the known hosts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TofuDecision {
+    /// The host is known with this certificate.
+    Trusted,
+    /// The host is known with another certificate, whose fingerprint is given:
+    /// connect anyway.
+    Changed(String),
+    /// The host is known with another certificate, ask the user.
+    AskChanged(String),
+    /// The host is known with another certificate, don't connect.
+    RefuseChanged(String),
+    /// Unknown host, trust its certificate and remember it.
+    Record,
+    /// Unknown host, ask the user.
+    Ask,
+    /// Unknown host, don't connect.
+    Refuse,
+}
+
+/// Decide about a certificate with `fingerprint`, given the one `known` for the host.
+pub fn check(policy: TofuPolicy, known: Option<&str>, fingerprint: &str) -> TofuDecision {
+    match known {
+        Some(known) if known == fingerprint => TofuDecision::Trusted,
+        Some(known) => match policy {
+            TofuPolicy::Silent => TofuDecision::Changed(known.to_string()),
+            TofuPolicy::Prompt => TofuDecision::AskChanged(known.to_string()),
+            TofuPolicy::Strict => TofuDecision::RefuseChanged(known.to_string()),
+        },
+        None => match policy {
+            TofuPolicy::Silent => TofuDecision::Record,
+            TofuPolicy::Prompt => TofuDecision::Ask,
+            TofuPolicy::Strict => TofuDecision::Refuse,
+        },
+    }
+}
+
+/// Hex SHA-256 of a DER encoded certificate.
+pub fn fingerprint(der: &[u8]) -> String {
+    digest(&SHA256, der)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// `host:port`, how hosts are identified in the store.
+pub fn host_key(host: &str, port: u16) -> String {
+    format!("{host}:{port}")
+}
+
+/// The certificate trusted for a host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownHost {
+    pub fingerprint: String,
+    /// Unix time the host was first seen, unknown for entries saved before it
+    /// was recorded.
+    pub first_seen: Option<u64>,
+}
+
+/// Certificate fingerprints of the hosts seen so far, saved to `path` when it is set.
+#[derive(Debug, Clone, Default)]
+pub struct TofuStore {
+    hosts: Arc<Mutex<HashMap<String, KnownHost>>>,
+    path: Option<PathBuf>,
+}
+
+impl TofuStore {
+    /// The store saved at `path`, empty if there is no file yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut hosts = HashMap::new();
+
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                for line in contents.lines() {
+                    let mut fields = line.split_whitespace();
+                    match (fields.next(), fields.next()) {
+                        (Some(host), Some(fingerprint)) => {
+                            let first_seen = fields.next().and_then(|secs| secs.parse().ok());
+                            hosts.insert(
+                                host.to_string(),
+                                KnownHost {
+                                    fingerprint: fingerprint.to_string(),
+                                    first_seen,
+                                },
+                            );
+                        }
+                        _ => warn!("Skipping invalid known host entry: {line}"),
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        Ok(Self {
+            hosts: Arc::new(Mutex::new(hosts)),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Fingerprint known for `key`, see `host_key`.
+    pub fn get(&self, key: &str) -> Option<String> {
+        Some(self.hosts.lock().ok()?.get(key)?.fingerprint.clone())
+    }
+
+    /// All known hosts with their certificate, sorted by host.
+    pub fn entries(&self) -> Vec<(String, KnownHost)> {
+        let Ok(hosts) = self.hosts.lock() else {
+            return Vec::new();
+        };
+        let mut entries: Vec<_> = hosts
+            .iter()
+            .map(|(key, host)| (key.clone(), host.clone()))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+
+    /// Remember `fingerprint` for `key`, replacing the one known before. A host
+    /// known already keeps the time it was first seen.
+    pub fn trust(&self, key: &str, fingerprint: &str) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .ok();
+        self.update(|hosts| {
+            let first_seen = hosts.get(key).map_or(now, |known| known.first_seen);
+            hosts.insert(
+                key.to_string(),
+                KnownHost {
+                    fingerprint: fingerprint.to_string(),
+                    first_seen,
+                },
+            );
+        });
+    }
+
+    /// Forget the certificate of `key`, the next one it presents is trusted on first
+    /// use again. Returns whether it was known.
+    pub fn forget(&self, key: &str) -> bool {
+        let mut removed = false;
+        self.update(|hosts| removed = hosts.remove(key).is_some());
+        removed
+    }
+
+    /// Apply `change` to the hosts and save them.
+    fn update(&self, change: impl FnOnce(&mut HashMap<String, KnownHost>)) {
+        let Ok(mut hosts) = self.hosts.lock() else {
+            return;
+        };
+        change(&mut hosts);
+
+        if let Some(path) = &self.path {
+            if let Err(e) = save(path, &hosts) {
+                warn!("Could not save known hosts to {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+fn save(path: &Path, hosts: &HashMap<String, KnownHost>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut entries: Vec<_> = hosts.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut contents = String::new();
+    for (host, known) in entries {
+        match known.first_seen {
+            Some(secs) => contents.push_str(&format!("{host} {} {secs}\n", known.fingerprint)),
+            None => contents.push_str(&format!("{host} {}\n", known.fingerprint)),
+        }
+    }
+
+    fs::write(path, contents)
+}
+
+pub fn known_hosts_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("known_hosts"))
+}
+
+/// `about:certs?forget=HOST`, the link forgetting the certificate of the host `key`.
+pub fn forget_link(key: &str) -> String {
+    let encoded: String = form_urlencoded::byte_serialize(key.as_bytes()).collect();
+    format!("{CERTS_URL}?forget={encoded}")
+}
@@ -0,0 +1,119 @@
+use url::Url;
+
+use crate::config::ConnectionSettings;
+use crate::protocol::{port_of, Protocol};
+
+/// Bytes of the raw response shown by `--trace-request`.
+pub const TRACE_BYTES: usize = 512;
+
+/// Stands for the query of a request whose query is secret.
+const REDACTED: &str = "REDACTED";
+
+/// How a request is traced.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Trace {
+    /// Hide the query, typed at a sensitive input prompt (status 11).
+    pub redact_query: bool,
+}
+
+/// The bytes `protocol` sends for `url`, escaped to be printed, with the query
+/// replaced when `trace` asks so.
+pub fn trace_request(protocol: &dyn Protocol, url: &Url, trace: Trace) -> String {
+    let request = if trace.redact_query {
+        protocol.build_request_line(&redacted(url))
+    } else {
+        protocol.build_request_line(url)
+    };
+
+    format!("> {}", request.escape_debug())
+}
+
+/// `url` with its query, if any, replaced so that it can be shown or logged.
+pub fn redacted(url: &Url) -> Url {
+    let mut redacted = url.clone();
+    if url.query().is_some() {
+        redacted.set_query(Some(REDACTED));
+    }
+    redacted
+}
+
+/// The first `TRACE_BYTES` of `bytes` as lines of offset, hex and printable
+/// characters, like `xxd`.
+pub fn hex_dump(bytes: &[u8]) -> String {
+    let mut dump = String::new();
+
+    for (line, chunk) in bytes[..bytes.len().min(TRACE_BYTES)].chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+        let printable: String = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    char::from(byte)
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        dump.push_str(&format!(
+            "< {:08x}  {:<47}  {printable}\n",
+            line * 16,
+            hex.join(" ")
+        ));
+    }
+
+    if bytes.len() > TRACE_BYTES {
+        dump.push_str(&format!("< … {} more bytes\n", bytes.len() - TRACE_BYTES));
+    }
+
+    dump
+}
+
+/// A shell command sending the request for `url` with `openssl s_client`, or `nc`
+/// for protocols without TLS, to reproduce what the server answers outside of the
+/// client. The path rewrite and client certificate of `settings` are applied.
+pub fn reproduce_command(
+    protocol: &dyn Protocol,
+    url: &Url,
+    settings: &ConnectionSettings,
+) -> String {
+    let mut request_url = url.clone();
+    if let Some(rewrite) = &settings.rewrite {
+        let path = rewrite.apply(request_url.path());
+        request_url.set_path(&path);
+    }
+    let request = protocol.build_request_line(&request_url);
+    let send = match request.split_once("\r\n") {
+        Some((line, "")) => format!("printf '%s\\r\\n' {}", shell_quote(line)),
+        Some((line, data)) => {
+            format!(
+                "printf '%s\\r\\n%s' {} {}",
+                shell_quote(line),
+                shell_quote(data)
+            )
+        }
+        None => format!("printf '%s' {}", shell_quote(&request)),
+    };
+
+    let host = url.host_str().unwrap_or_default();
+    let port = port_of(url);
+    if !protocol.uses_tls() {
+        return format!("{send} | nc {host} {port}");
+    }
+
+    let mut command = format!(
+        "{send} | openssl s_client -quiet -connect {host}:{port} -servername {}",
+        host.trim_start_matches('[').trim_end_matches(']')
+    );
+    if let Some(identity) = &settings.identity {
+        command.push_str(&format!(
+            " -cert {}",
+            shell_quote(&identity.to_string_lossy())
+        ));
+    }
+    command
+}
+
+/// `text` in single quotes for a POSIX shell.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
@@ -0,0 +1,26 @@
+/// The TLS implementation `native-tls` uses on this platform.
+pub const TLS_BACKEND: &str = if cfg!(any(target_os = "macos", target_os = "ios")) {
+    "Security.framework"
+} else if cfg!(target_os = "windows") {
+    "SChannel"
+} else {
+    "OpenSSL"
+};
+
+/// The TLS versions that can be set as `min_tls_version` with `TLS_BACKEND`.
+pub const TLS_VERSIONS: &str = if cfg!(any(target_os = "macos", target_os = "ios")) {
+    "1.0 to 1.2"
+} else {
+    "1.0 to 1.3"
+};
+
+/// What `--version` prints: the client version and the TLS details useful in bug reports.
+pub fn version_info() -> String {
+    format!(
+        "gemini-client {}\n\
+         TLS backend: {TLS_BACKEND}\n\
+         TLS versions: {TLS_VERSIONS} selectable as minimum\n\
+         Protocols: gemini, spartan",
+        env!("CARGO_PKG_VERSION")
+    )
+}
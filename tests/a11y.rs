@@ -0,0 +1,37 @@
+use gemini::lang::{language_name, language_preamble};
+use gemini::models::Response;
+
+fn preamble(header: &str) -> Option<String> {
+    let response = Response::try_from(format!("{header}\r\n# Bonjour\n").as_str()).unwrap();
+    language_preamble(&response)
+}
+
+#[test]
+fn preamble_names_the_page_language() {
+    assert_eq!(
+        preamble("20 text/gemini; lang=fr").as_deref(),
+        Some("Page language: French")
+    );
+    assert_eq!(
+        preamble("20 text/gemini; charset=utf-8; lang=en-GB,fr").as_deref(),
+        Some("Page language: English, French")
+    );
+    assert_eq!(
+        preamble("20 text/gemini; lang=tlh").as_deref(),
+        Some("Page language: tlh")
+    );
+}
+
+#[test]
+fn pages_without_language_have_no_preamble() {
+    assert_eq!(preamble("20 text/gemini"), None);
+    assert_eq!(preamble("20 text/gemini; lang="), None);
+    assert_eq!(preamble("51 Not found; lang=fr"), None);
+}
+
+#[test]
+fn language_names_ignore_the_region() {
+    assert_eq!(language_name("fr-CA").as_deref(), Some("French"));
+    assert_eq!(language_name("PT_br").as_deref(), Some("Portuguese"));
+    assert_eq!(language_name("xx"), None);
+}
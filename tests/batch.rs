@@ -0,0 +1,70 @@
+mod common;
+
+use common::MockServer;
+use gemini::batch::run;
+use gemini::config::Config;
+use std::time::Duration;
+
+#[test]
+fn every_url_read_gets_a_summary_line() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini\r\n# Home\n=> /a A\n=> /b B\n")
+        .route("/gone", "51 Not found\r\n")
+        .start();
+    let home = server.url("/");
+    let gone = server.url("/gone");
+    let input = format!("{home}\n\n  {gone}  \n");
+    let config = Config {
+        crawl_delay: Duration::ZERO,
+        ..Config::default()
+    };
+
+    let mut output = Vec::new();
+    let all_succeeded = run(input.as_bytes(), &mut output, &config, None).unwrap();
+
+    assert!(!all_succeeded);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        format!("{home}\t20\ttext/gemini\t22\t2\n{gone}\t51\t-\t0\t0\n")
+    );
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn failed_fetches_are_reported_and_make_the_run_fail() {
+    let config = Config {
+        crawl_delay: Duration::ZERO,
+        ..Config::default()
+    };
+
+    let mut output = Vec::new();
+    let all_succeeded = run("gemini://[::1\n".as_bytes(), &mut output, &config, None).unwrap();
+
+    assert!(!all_succeeded);
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .starts_with("gemini://[::1\tERR\t"));
+}
+
+#[test]
+fn only_successes_and_redirects_make_the_run_succeed() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini\r\n# Home\n")
+        .route("/moved", "31 /\r\n")
+        .route("/busy", "44 Slow down\r\n")
+        .start();
+    let config = Config {
+        crawl_delay: Duration::ZERO,
+        ..Config::default()
+    };
+    let run_on = |paths: &[&str]| {
+        let input: String = paths
+            .iter()
+            .map(|p| format!("{}\n", server.url(p)))
+            .collect();
+        run(input.as_bytes(), Vec::new(), &config, None).unwrap()
+    };
+
+    assert!(run_on(&["/", "/moved"]));
+    assert!(!run_on(&["/", "/busy"]));
+}
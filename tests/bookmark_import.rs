@@ -0,0 +1,51 @@
+use gemini::bookmarks::{Bookmark, Bookmarks, ImportReport};
+use std::fs;
+use url::Url;
+
+#[test]
+fn links_of_a_gemtext_file_are_imported_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("export.gmi");
+    fs::write(
+        &path,
+        "# My old bookmarks\n\
+         \n\
+         Some notes about them.\n\
+         => gemini://example.org/ Example capsule\n\
+         => gemini://other.example/docs/\n\
+         * not a link\n\
+         => gemini://example.org/ Listed twice\n\
+         ```\n\
+         => gemini://art.example/ inside a preformatted block\n\
+         ```\n\
+         => /relative.gmi Has no base\n\
+         => gemini://known.example/ Already here\n",
+    )
+    .unwrap();
+
+    let mut bookmarks = Bookmarks::default();
+    bookmarks.add(Url::parse("gemini://known.example/").unwrap(), None);
+
+    let report = bookmarks.import(&fs::read_to_string(&path).unwrap());
+    assert_eq!(
+        report,
+        ImportReport {
+            imported: 2,
+            duplicates: 2,
+            invalid: 1
+        }
+    );
+    assert_eq!(
+        bookmarks.entries[1..],
+        [
+            Bookmark {
+                url: Url::parse("gemini://example.org/").unwrap(),
+                title: Some("Example capsule".to_string()),
+            },
+            Bookmark {
+                url: Url::parse("gemini://other.example/docs/").unwrap(),
+                title: None,
+            },
+        ]
+    );
+}
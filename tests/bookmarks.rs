@@ -0,0 +1,110 @@
+use gemini::bookmarks::{moves_bookmark, remove_link, Bookmarks};
+use gemini::models::SpecificCode;
+use gemini::pages::bookmarks_page;
+use url::Url;
+
+fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+}
+
+#[test]
+fn pages_are_bookmarked_once() {
+    let mut bookmarks = Bookmarks::default();
+
+    assert!(bookmarks.add(url("gemini://example.org/"), Some("Home".to_string())));
+    assert!(!bookmarks.add(url("gemini://example.org/"), None));
+    assert!(bookmarks.add(url("gemini://other.example/"), None));
+    assert_eq!(bookmarks.entries.len(), 2);
+
+    assert!(bookmarks.remove(&url("gemini://example.org/")));
+    assert!(!bookmarks.remove(&url("gemini://example.org/")));
+    assert!(!bookmarks.contains(&url("gemini://example.org/")));
+}
+
+#[test]
+fn bookmarks_are_saved_as_gemtext_and_loaded_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("data").join("bookmarks.gmi");
+    assert_eq!(
+        Bookmarks::load_or_default(&path).unwrap(),
+        Bookmarks::default()
+    );
+
+    let mut bookmarks = Bookmarks::default();
+    bookmarks.add(url("gemini://example.org/"), Some("Home".to_string()));
+    bookmarks.add(url("gemini://other.example/a.gmi"), None);
+    bookmarks.save(&path).unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "=> gemini://example.org/ Home\n=> gemini://other.example/a.gmi\n"
+    );
+    assert_eq!(Bookmarks::load(&path).unwrap(), bookmarks);
+}
+
+#[test]
+fn the_bookmarks_page_links_to_removing_each() {
+    let mut bookmarks = Bookmarks::default();
+    assert!(bookmarks_page(&bookmarks.entries).contains("No bookmarks yet"));
+
+    let page_url = url("gemini://example.org/a b?q=1");
+    bookmarks.add(page_url.clone(), Some("A page".to_string()));
+    let page = bookmarks_page(&bookmarks.entries);
+
+    assert_eq!(
+        remove_link(&page_url),
+        "about:bookmarks?remove=gemini%3A%2F%2Fexample.org%2Fa%2520b%3Fq%3D1"
+    );
+    assert_eq!(
+        page,
+        format!(
+            "# Bookmarks\n\n=> {page_url} A page\n=> {} Remove\n",
+            remove_link(&page_url)
+        )
+    );
+}
+
+#[test]
+fn moved_pages_keep_their_bookmark_title_and_place() {
+    let mut bookmarks = Bookmarks::default();
+    bookmarks.add(url("gemini://old.example/"), Some("Capsule".to_string()));
+    bookmarks.add(url("gemini://other.example/"), None);
+
+    assert!(bookmarks.replace(&url("gemini://old.example/"), &url("gemini://new.example/")));
+    assert_eq!(bookmarks.entries[0].url, url("gemini://new.example/"));
+    assert_eq!(bookmarks.entries[0].title.as_deref(), Some("Capsule"));
+    assert!(!bookmarks.replace(&url("gemini://old.example/"), &url("gemini://new.example/")));
+}
+
+#[test]
+fn moving_to_a_bookmarked_page_drops_the_old_bookmark() {
+    let mut bookmarks = Bookmarks::default();
+    bookmarks.add(url("gemini://old.example/"), None);
+    bookmarks.add(url("gemini://new.example/"), Some("New".to_string()));
+
+    assert!(bookmarks.replace(&url("gemini://old.example/"), &url("gemini://new.example/")));
+    assert_eq!(bookmarks.entries.len(), 1);
+    assert_eq!(bookmarks.entries[0].title.as_deref(), Some("New"));
+}
+
+#[test]
+fn only_permanent_redirects_of_bookmarked_pages_move_bookmarks() {
+    let mut bookmarks = Bookmarks::default();
+    bookmarks.add(url("gemini://old.example/"), None);
+
+    assert!(moves_bookmark(
+        &bookmarks,
+        SpecificCode::PermanentRedirect,
+        &url("gemini://old.example/")
+    ));
+    assert!(!moves_bookmark(
+        &bookmarks,
+        SpecificCode::TemporaryRedirect,
+        &url("gemini://old.example/")
+    ));
+    assert!(!moves_bookmark(
+        &bookmarks,
+        SpecificCode::PermanentRedirect,
+        &url("gemini://other.example/")
+    ));
+}
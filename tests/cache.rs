@@ -0,0 +1,116 @@
+mod common;
+
+use common::MockServer;
+use gemini::batch::run;
+use gemini::cache::{content_hash, Freshness, PageCache};
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::handlers::status_line;
+use gemini::models::{Pager, Response};
+use std::fs;
+use std::time::Duration;
+use url::Url;
+
+fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+}
+
+#[test]
+fn pages_are_new_then_unchanged_or_changed() {
+    let cache = PageCache::default();
+    let page = url("gemini://example.org/");
+
+    assert_eq!(cache.record(&page, "# One"), Freshness::New);
+    let fetched = cache.get(&page).unwrap().fetched;
+    assert_eq!(cache.record(&page, "# One"), Freshness::Unchanged(fetched));
+    assert_eq!(cache.record(&page, "# Two"), Freshness::Changed);
+    assert_eq!(cache.get(&page).unwrap().hash, content_hash("# Two"));
+    assert_eq!(
+        cache.record(&url("gemini://example.org/other"), "# One"),
+        Freshness::New
+    );
+}
+
+#[test]
+fn the_cache_is_saved_and_loaded_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state").join("pages");
+    let page = url("gemini://example.org/");
+
+    let cache = PageCache::load(&path).unwrap();
+    cache.record(&page, "# One");
+    let saved = cache.get(&page).unwrap();
+
+    let loaded = PageCache::load(&path).unwrap();
+    assert_eq!(loaded.get(&page), Some(saved.clone()));
+    assert_eq!(
+        loaded.record(&page, "# One"),
+        Freshness::Unchanged(saved.fetched)
+    );
+}
+
+#[test]
+fn invalid_entries_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pages");
+    fs::write(
+        &path,
+        "gemini://example.org/ abc 12\ngemini://broken.example/ abc\ngemini://late.example/ abc soon\n",
+    )
+    .unwrap();
+
+    let cache = PageCache::load(&path).unwrap();
+    assert_eq!(
+        cache.get(&url("gemini://example.org/")).unwrap().fetched,
+        12
+    );
+    assert_eq!(cache.get(&url("gemini://broken.example/")), None);
+    assert_eq!(cache.get(&url("gemini://late.example/")), None);
+}
+
+#[test]
+fn the_status_line_tells_how_the_page_compares() {
+    let response = Response::try_from("20 image/png\r\n").unwrap();
+    let page = url("gemini://example.org/");
+    let mut client = Client::new(&page, Pager::Less, Config::default());
+    let mut line = |freshness| {
+        client.freshness = freshness;
+        status_line(&client, &response, &page, 200)
+    };
+
+    assert_eq!(line(None), "gemini://example.org/");
+    assert_eq!(line(Some(Freshness::New)), "gemini://example.org/");
+    assert_eq!(
+        line(Some(Freshness::Changed)),
+        "gemini://example.org/ — changed since last visit"
+    );
+    assert_eq!(
+        line(Some(Freshness::Unchanged(86_400))),
+        "gemini://example.org/ — unchanged since last visit on 1970-01-02"
+    );
+}
+
+#[test]
+fn batch_runs_can_leave_out_unchanged_pages() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini\r\n# Home\n")
+        .start();
+    let home = server.url("/");
+    let input = format!("{home}\n");
+    let config = Config {
+        crawl_delay: Duration::ZERO,
+        ..Config::default()
+    };
+    let cache = PageCache::default();
+
+    let mut output = Vec::new();
+    assert!(run(input.as_bytes(), &mut output, &config, Some(&cache)).unwrap());
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        format!("{home}\t20\ttext/gemini\t6\t0\n")
+    );
+
+    let mut output = Vec::new();
+    assert!(run(input.as_bytes(), &mut output, &config, Some(&cache)).unwrap());
+    assert!(output.is_empty());
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use common::MockServer;
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::models::{CancelHandle, Pager, Request};
+use std::thread;
+use std::time::{Duration, Instant};
+use url::Url;
+
+#[test]
+fn cancelling_aborts_a_slow_request() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini\r\n# Slow\n")
+        .delay(Duration::from_secs(5))
+        .start();
+    let cancel = CancelHandle::default();
+    let request = Request::new(server.url("/")).with_cancel_handle(cancel.clone());
+
+    let started = Instant::now();
+    let worker = thread::spawn(move || request.send());
+    thread::sleep(Duration::from_millis(200));
+    cancel.cancel();
+
+    let result = worker.join().unwrap();
+    assert!(cancel.is_cancelled());
+    assert!(result.is_err());
+    assert!(
+        started.elapsed() < Duration::from_secs(3),
+        "{:?}",
+        started.elapsed()
+    );
+}
+
+#[test]
+fn requests_are_not_cancelled_by_default() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini\r\n# Fast\n")
+        .start();
+    let cancel = CancelHandle::default();
+
+    let response = Request::new(server.url("/"))
+        .with_cancel_handle(cancel.clone())
+        .send()
+        .unwrap()
+        .unwrap();
+    assert_eq!(response.title().as_deref(), Some("Fast"));
+    assert!(!cancel.is_cancelled());
+}
+
+fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+}
+
+#[test]
+fn cancelled_requests_are_left_out_of_the_history() {
+    let home = url("gemini://example.org/");
+    let mut client = Client::new(&home, Pager::Less, Config::default());
+    client.prepare_request(home.clone());
+    client.last_working_url = Some(home.clone());
+
+    let slow = url("gemini://example.org/slow");
+    client.prepare_request(slow);
+    assert_eq!(client.cancel_request(home.clone()), Some(home.clone()));
+    assert_eq!(client.current_url, home);
+    // The page gone back to is pushed again when it is requested
+    assert!(client.history.is_empty());
+    client.prepare_request(home.clone());
+    assert_eq!(client.history, [home]);
+}
+
+#[test]
+fn cancelling_the_first_page_has_nothing_to_go_back_to() {
+    let start = url("gemini://example.org/slow");
+    let mut client = Client::new(&start, Pager::Less, Config::default());
+
+    client.prepare_request(start.clone());
+    assert_eq!(client.cancel_request(start.clone()), None);
+    assert!(client.history.is_empty());
+    assert_eq!(client.current_url, start);
+}
@@ -0,0 +1,80 @@
+use gemini::pages::certs_page;
+use gemini::tofu::{forget_link, KnownHost, TofuStore};
+
+#[test]
+fn lists_pinned_hosts_with_forget_links() {
+    let hosts = vec![
+        (
+            "example.org:1965".to_string(),
+            KnownHost {
+                fingerprint: "ab12".to_string(),
+                first_seen: Some(1_700_000_000),
+            },
+        ),
+        (
+            "old.example:1965".to_string(),
+            KnownHost {
+                fingerprint: "cd34".to_string(),
+                first_seen: None,
+            },
+        ),
+    ];
+
+    let page = certs_page(&hosts);
+
+    assert!(page.starts_with("# Certificates\n"));
+    assert!(page.contains("## example.org:1965\n\nab12\nFirst seen on 2023-11-14\n"));
+    assert!(page.contains("=> about:certs?forget=example.org%3A1965 Forget\n"));
+    assert!(page.contains("## old.example:1965\n\ncd34\n=> about:certs?forget=old.example%3A1965"));
+}
+
+#[test]
+fn empty_store_says_so() {
+    assert!(certs_page(&[]).contains("No certificates trusted yet."));
+}
+
+#[test]
+fn forgetting_removes_the_pin_from_every_clone() {
+    let store = TofuStore::default();
+    let shared = store.clone();
+    store.trust("example.org:1965", "ab12");
+    assert_eq!(shared.get("example.org:1965").as_deref(), Some("ab12"));
+
+    assert!(shared.forget("example.org:1965"));
+    assert_eq!(store.get("example.org:1965"), None);
+    assert!(store.entries().is_empty());
+    assert!(!store.forget("example.org:1965"));
+}
+
+#[test]
+fn forget_link_encodes_the_host() {
+    assert_eq!(
+        forget_link("[::1]:1965"),
+        "about:certs?forget=%5B%3A%3A1%5D%3A1965"
+    );
+}
+
+#[test]
+fn trusting_a_changed_certificate_keeps_when_the_host_was_first_seen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("known_hosts");
+    std::fs::write(&path, "example.org:1965 ab12 1700000000\n").unwrap();
+
+    let store = TofuStore::load(&path).unwrap();
+    store.trust("example.org:1965", "cd34");
+    let expected = KnownHost {
+        fingerprint: "cd34".to_string(),
+        first_seen: Some(1_700_000_000),
+    };
+    assert_eq!(
+        store.entries(),
+        vec![("example.org:1965".to_string(), expected.clone())]
+    );
+    assert_eq!(
+        TofuStore::load(&path).unwrap().entries(),
+        vec![("example.org:1965".to_string(), expected)]
+    );
+
+    store.trust("example.com:1965", "ef56");
+    assert!(store.entries()[0].1.first_seen.is_some());
+}
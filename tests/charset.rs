@@ -0,0 +1,18 @@
+use gemini::models::{decode_response, Response};
+
+#[test]
+fn invalid_utf8_in_meta_is_replaced() {
+    let response = Response::try_from(b"51 Introuvable \xe9t\xe9\r\n".as_slice()).unwrap();
+    assert_eq!(response.status_code_num, 51);
+    assert_eq!(response.meta_description, "Introuvable \u{FFFD}t\u{FFFD}");
+}
+
+#[test]
+fn body_must_still_be_utf8() {
+    let bytes = b"20 text/gemini\r\n# caf\xe9\n";
+    assert!(Response::try_from(bytes.as_slice()).is_err());
+    assert_eq!(
+        decode_response(bytes, true).unwrap(),
+        "20 text/gemini\r\n# caf\u{FFFD}\n"
+    );
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::MockServer;
+use gemini::checker::{check_links, report_page, CheckResult, LinkStatus};
+use gemini::models::Response;
+use std::time::Duration;
+use url::Url;
+
+#[test]
+fn every_gemini_link_is_checked() {
+    let server = MockServer::builder()
+        .route("/ok", "20 text/gemini\r\n# Fine\n")
+        .route("/moved", "31 /ok\r\n")
+        .start();
+    let mut client = server.client("/");
+    client.config.crawl_delay = Duration::ZERO;
+    let response = Response::try_from(
+        "20 text/gemini\r\n\
+         => /ok Fine\n\
+         => /moved\n\
+         => /missing Missing\n\
+         => https://example.org/ The web\n",
+    )
+    .unwrap();
+
+    let results = check_links(&client, &response, 2);
+    let statuses: Vec<_> = results.iter().map(|result| &result.status).collect();
+    assert_eq!(
+        statuses,
+        [
+            &LinkStatus::Status(20, "text/gemini".to_string()),
+            &LinkStatus::Status(31, "/ok".to_string()),
+            &LinkStatus::Status(51, "Not found".to_string()),
+            &LinkStatus::Skipped,
+        ]
+    );
+    assert_eq!(server.requests().len(), 3);
+
+    let page = report_page(&results);
+    assert!(page.starts_with("# Link check\n\n4 links, 1 broken\n\n"));
+    // Broken links come first
+    let missing = server.url("/missing");
+    assert!(page.contains(&format!("\n\n=> {missing} [51 Not found] Missing\n")));
+    assert!(page.ends_with("=> https://example.org/ [not checked] The web\n"));
+}
+
+#[test]
+fn only_failures_and_unreachable_links_are_broken() {
+    assert!(LinkStatus::Status(51, String::new()).is_broken());
+    assert!(LinkStatus::Status(44, String::new()).is_broken());
+    assert!(LinkStatus::Unreachable("refused".to_string()).is_broken());
+    assert!(!LinkStatus::Status(31, String::new()).is_broken());
+    assert!(!LinkStatus::Status(60, String::new()).is_broken());
+    assert!(!LinkStatus::Skipped.is_broken());
+}
+
+#[test]
+fn the_report_is_plain_gemtext() {
+    colored::control::set_override(true);
+    let result = |href: &str, status| CheckResult {
+        url: Url::parse(href).unwrap(),
+        name: None,
+        status,
+    };
+    let page = report_page(&[
+        result(
+            "gemini://example.org/gone",
+            LinkStatus::Status(51, "Not found".to_string()),
+        ),
+        result(
+            "gemini://example.com/",
+            LinkStatus::Unreachable("refused".to_string()),
+        ),
+    ]);
+    colored::control::unset_override();
+
+    assert!(!page.contains('\x1b'));
+    assert!(
+        page.contains("=> gemini://example.org/gone [51 Not found] gemini://example.org/gone\n")
+    );
+    assert!(
+        page.contains("=> gemini://example.com/ [unreachable: refused] gemini://example.com/\n")
+    );
+}
@@ -0,0 +1,87 @@
+use gemini::client::{Client, LinkTarget};
+use gemini::config::{Config, Mailto, SelfLink};
+use gemini::models::Pager;
+use url::Url;
+
+fn client() -> Client {
+    let url = Url::parse("gemini://example.org/docs/").unwrap();
+    let config = Config {
+        mailto: Mailto::Show,
+        handlers: [("gopher".to_string(), "true %u".to_string())].into(),
+        ..Config::default()
+    };
+    let mut client = Client::new(&url, Pager::Less, config);
+    client.history.push(url);
+    client
+}
+
+#[test]
+fn external_links_leave_the_client_on_the_page() {
+    let mut client = client();
+
+    for href in ["mailto:me@example.org", "gopher://example.org/1/"] {
+        assert_eq!(client.click_link(href), Ok(LinkTarget::External), "{href}");
+    }
+    assert_eq!(client.current_url.as_str(), "gemini://example.org/docs/");
+    assert_eq!(client.history.len(), 1);
+}
+
+#[test]
+fn gemini_and_client_pages_are_navigated_to() {
+    let mut client = client();
+
+    assert_eq!(
+        client.click_link("page.gmi"),
+        Ok(LinkTarget::Navigate(
+            Url::parse("gemini://example.org/docs/page.gmi").unwrap()
+        ))
+    );
+    assert_eq!(
+        client.click_link("about:bookmarks"),
+        Ok(LinkTarget::Navigate(Url::parse("about:bookmarks").unwrap()))
+    );
+}
+
+#[test]
+fn back_and_forward_stop_at_the_ends_of_the_history() {
+    let mut client = client();
+    assert_eq!(client.go_back(), None);
+    assert_eq!(client.go_forward(), None);
+    assert_eq!(client.history.len(), 1);
+
+    let next = Url::parse("gemini://example.org/docs/next.gmi").unwrap();
+    client.history.push(next.clone());
+    let back = client.go_back().unwrap();
+    assert_eq!(back.as_str(), "gemini://example.org/docs/");
+    // The page gone back to is pushed again when it is requested
+    assert!(client.history.is_empty());
+    client.history.push(back);
+
+    assert_eq!(client.go_forward(), Some(next));
+    assert_eq!(client.go_forward(), None);
+}
+
+#[test]
+fn links_to_the_current_page_follow_the_self_link_setting() {
+    let here = Url::parse("gemini://example.org/docs/").unwrap();
+
+    let mut client = client();
+    assert_eq!(
+        client.click_link("./"),
+        Ok(LinkTarget::Navigate(here.clone()))
+    );
+
+    client.config.self_link = SelfLink::Ignore;
+    assert_eq!(client.click_link(""), Ok(LinkTarget::Stay));
+    assert_eq!(client.history.len(), 1);
+
+    client.config.self_link = SelfLink::Back;
+    // Nothing to go back to
+    assert!(client.click_link("/docs/").is_err());
+    let previous = Url::parse("gemini://example.org/").unwrap();
+    client.history = vec![previous.clone(), here.clone()];
+    assert_eq!(
+        client.click_link("/docs/"),
+        Ok(LinkTarget::Navigate(previous))
+    );
+}
@@ -0,0 +1,123 @@
+//! A scriptable Gemini server for integration tests.
+
+// Each test crate uses its own part of the helpers
+#![allow(dead_code)]
+
+use native_tls::{Identity, TlsAcceptor};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use url::Url;
+
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::models::Pager;
+
+const CERT: &[u8] = include_bytes!("../fixtures/cert.pem");
+const KEY: &[u8] = include_bytes!("../fixtures/key.pem");
+
+/// Builds a `MockServer` answering canned responses by request path (and query).
+#[derive(Default)]
+pub struct MockServerBuilder {
+    routes: HashMap<String, String>,
+    delay: Duration,
+    stall: Duration,
+}
+
+impl MockServerBuilder {
+    /// Answer requests for `path` (including `?query` if any) with the raw `response`.
+    pub fn route(mut self, path: &str, response: &str) -> Self {
+        self.routes.insert(path.to_string(), response.to_string());
+        self
+    }
+
+    /// Wait this long after each connection before starting the TLS handshake.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Keep the connection open this long after answering, without ending the TLS
+    /// session, so that a client timing out sees the response cut short.
+    pub fn stall(mut self, stall: Duration) -> Self {
+        self.stall = stall;
+        self
+    }
+
+    pub fn start(self) -> MockServer {
+        let identity = Identity::from_pkcs8(CERT, KEY).expect("Invalid fixture certificate");
+        let acceptor = Arc::new(TlsAcceptor::new(identity).expect("Failed to build acceptor"));
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let routes = self.routes;
+        let delay = self.delay;
+        let stall = self.stall;
+        let received = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                thread::sleep(delay);
+                let Ok(mut stream) = acceptor.accept(stream) else {
+                    continue;
+                };
+
+                let mut line = String::new();
+                if BufReader::new(&mut stream).read_line(&mut line).is_err() {
+                    continue;
+                }
+                let line = line.trim_end().to_string();
+                received.lock().unwrap().push(line.clone());
+
+                let key = Url::parse(&line).map_or_else(
+                    |_| String::new(),
+                    |url| match url.query() {
+                        Some(query) => format!("{}?{query}", url.path()),
+                        None => url.path().to_string(),
+                    },
+                );
+                let response = routes.get(&key).map_or("51 Not found\r\n", String::as_str);
+
+                stream.write_all(response.as_bytes()).ok();
+                stream.flush().ok();
+                thread::sleep(stall);
+                stream.shutdown().ok();
+            }
+        });
+
+        MockServer { port, requests }
+    }
+}
+
+pub struct MockServer {
+    port: u16,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder::default()
+    }
+
+    /// URL of `path` on this server.
+    pub fn url(&self, path: &str) -> Url {
+        Url::parse(&format!("gemini://127.0.0.1:{}{path}", self.port)).unwrap()
+    }
+
+    /// A client whose current page is `path` on this server.
+    pub fn client(&self, path: &str) -> Client {
+        let config = Config {
+            spinner: false,
+            ..Config::default()
+        };
+        Client::new(&self.url(path), Pager::Less, config)
+    }
+
+    /// Request lines received so far, without their CRLF.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,123 @@
+use gemini::config::{Config, ConfigFile, SelfLink, TlsVersion};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn load(toml: &str) -> Result<Config, String> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, toml).unwrap();
+
+    let file = ConfigFile::load(&path).map_err(|e| e.to_string())?;
+    let mut config = Config::default();
+    file.apply(&mut config);
+    Ok(config)
+}
+
+#[test]
+fn host_settings_override_the_global_ones() {
+    let config = load(
+        "[connection]\n\
+         timeout = 10\n\
+         dns_retries = 3\n\
+         \n\
+         [hosts.\"slow.example\"]\n\
+         timeout = 60\n\
+         min_tls_version = \"1.3\"\n\
+         identity = \"/tmp/slow.p12\"\n",
+    )
+    .unwrap();
+
+    let slow = config.connection_settings("slow.example");
+    assert_eq!(slow.timeout(), Some(Duration::from_secs(60)));
+    assert_eq!(slow.dns_retries, Some(3));
+    assert_eq!(slow.min_tls_version, Some(TlsVersion::Tls13));
+    assert_eq!(slow.identity, Some(PathBuf::from("/tmp/slow.p12")));
+
+    let other = config.connection_settings("other.example");
+    assert_eq!(other.timeout(), Some(Duration::from_secs(10)));
+    assert_eq!(other.min_tls_version, None);
+    assert_eq!(other.identity, None);
+}
+
+#[test]
+fn updating_bookmarks_is_off_unless_configured() {
+    assert!(!load("").unwrap().update_bookmarks);
+    assert!(load("update_bookmarks = true\n").unwrap().update_bookmarks);
+}
+
+#[test]
+fn handlers_are_configured_by_scheme() {
+    let config = load("[handlers]\ngopher = \"lynx %u\"\n").unwrap();
+    assert_eq!(
+        config.handlers.get("gopher").map(String::as_str),
+        Some("lynx %u")
+    );
+    assert!(!config.handlers.contains_key("finger"));
+}
+
+#[test]
+fn input_clean_up_is_configured() {
+    let config = load("[input]\ncollapse_whitespace = true\n").unwrap();
+    assert!(config.input.trim);
+    assert!(config.input.collapse_whitespace);
+
+    let config = load("[input]\ntrim = false\n").unwrap();
+    assert!(!config.input.trim);
+    assert!(!config.input.collapse_whitespace);
+}
+
+#[test]
+fn self_links_reload_unless_configured() {
+    assert_eq!(load("").unwrap().self_link, SelfLink::Reload);
+    assert_eq!(
+        load("self_link = \"back\"\n").unwrap().self_link,
+        SelfLink::Back
+    );
+    assert!(load("self_link = \"stay\"\n").is_err());
+}
+
+#[test]
+fn unknown_tls_versions_and_settings_are_rejected() {
+    let error = load("[hosts.\"a.example\"]\nmin_tls_version = \"1.4\"\n").unwrap_err();
+    assert!(error.contains("1.4"), "{error}");
+
+    let error = load("[hosts.\"a.example\"]\ntimeuot = 5\n").unwrap_err();
+    assert!(error.contains("timeuot"), "{error}");
+}
+
+#[test]
+fn rewrites_are_compiled_when_loading() {
+    let config = load(
+        "[hosts.\"mirror.example\".rewrite]\n\
+         prefix = \"/mirror/\"\n\
+         \n\
+         [hosts.\"docs.example\".rewrite]\n\
+         pattern = \"^/docs/(.*)\"\n\
+         replacement = \"/v2/$1\"\n",
+    )
+    .unwrap();
+    let rewrite = |host: &str, path: &str| {
+        config
+            .connection_settings(host)
+            .rewrite
+            .unwrap()
+            .apply(path)
+    };
+
+    assert_eq!(rewrite("mirror.example", "/a.gmi"), "/mirror/a.gmi");
+    assert_eq!(rewrite("docs.example", "/docs/a.gmi"), "/v2/a.gmi");
+    assert_eq!(rewrite("docs.example", "/other.gmi"), "/other.gmi");
+}
+
+#[test]
+fn invalid_rewrites_are_rejected() {
+    let rewrite = |rule: &str| load(&format!("[connection.rewrite]\n{rule}\n"));
+
+    let error = rewrite("prefix = \"/a\"\npattern = \"^/b\"").unwrap_err();
+    assert!(error.contains("not both"), "{error}");
+    let error = rewrite("replacement = \"/a\"").unwrap_err();
+    assert!(error.contains("needs a `prefix` or a `pattern`"), "{error}");
+    let error = rewrite("pattern = \"(\"").unwrap_err();
+    assert!(error.contains("unclosed group"), "{error}");
+}
@@ -0,0 +1,11 @@
+use gemini::handlers::is_yes;
+
+#[test]
+fn only_yes_confirms() {
+    for answer in ["y", "Y", " y\n", "yes", "YES"] {
+        assert!(is_yes(answer), "{answer:?}");
+    }
+    for answer in ["", "\n", "n", "no", "yep", "q"] {
+        assert!(!is_yes(answer), "{answer:?}");
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::NaiveDate;
+use gemini::models::{leading_date, Link, Response};
+use gemini::render::{
+    dated_name, relative_date, strip_ansi, DateDisplay, GemtextRenderer, PreformattedOverflow,
+    Renderer,
+};
+use url::Url;
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn ago(s: &str) -> String {
+    relative_date(date(s), date("2024-06-15"))
+}
+
+#[test]
+fn recent_dates_are_days() {
+    assert_eq!(ago("2024-06-15"), "today");
+    assert_eq!(ago("2024-06-14"), "yesterday");
+    assert_eq!(ago("2024-06-13"), "2 days ago");
+    assert_eq!(ago("2024-06-09"), "6 days ago");
+    assert_eq!(ago("2024-06-16"), "tomorrow");
+    assert_eq!(ago("2024-06-20"), "in 5 days");
+}
+
+#[test]
+fn older_dates_are_weeks_months_and_years() {
+    assert_eq!(ago("2024-06-08"), "1 week ago");
+    assert_eq!(ago("2024-06-01"), "2 weeks ago");
+    assert_eq!(ago("2024-05-17"), "4 weeks ago");
+    assert_eq!(ago("2024-05-16"), "1 month ago");
+    assert_eq!(ago("2023-06-17"), "11 months ago");
+    assert_eq!(ago("2023-06-16"), "1 year ago");
+    assert_eq!(ago("2021-06-15"), "3 years ago");
+}
+
+#[test]
+fn only_relative_display_changes_names() {
+    let today = date("2024-06-15");
+    let dated = Link::new("/post".to_string(), Some("2024-06-12 A post".to_string()));
+    let undated = Link::new("/about".to_string(), Some("About".to_string()));
+
+    assert_eq!(
+        dated_name(&dated, DateDisplay::Relative, today).as_deref(),
+        Some("3 days ago A post")
+    );
+    assert_eq!(
+        dated_name(&dated, DateDisplay::Absolute, today).as_deref(),
+        Some("2024-06-12 A post")
+    );
+    assert_eq!(
+        dated_name(&undated, DateDisplay::Relative, today).as_deref(),
+        Some("About")
+    );
+}
+
+#[test]
+fn feed_entries_are_rendered_relative() {
+    let response = Response::try_from("20 text/gemini\r\n=> /post 2001-01-01 Old post\n").unwrap();
+    let url = Url::parse("gemini://example.org/").unwrap();
+    let renderer = GemtextRenderer {
+        inline_link_numbers: false,
+        preformatted_overflow: PreformattedOverflow::Scroll,
+        dates: DateDisplay::Relative,
+    };
+
+    let rendered = strip_ansi(&renderer.render(&response, &url));
+    assert!(rendered.starts_with("=> 2"), "{rendered}");
+    assert!(rendered.ends_with("years ago Old post"), "{rendered}");
+}
+
+#[test]
+fn link_names_starting_with_a_date_are_dated() {
+    assert_eq!(leading_date("2024-05-01 A post"), Some(date("2024-05-01")));
+    assert_eq!(leading_date("2024-05-01"), Some(date("2024-05-01")));
+    assert_eq!(leading_date("2024-05-01: A post"), Some(date("2024-05-01")));
+    assert_eq!(leading_date("2024-05-011 A post"), None);
+    assert_eq!(leading_date("2024-13-01 A post"), None);
+    assert_eq!(leading_date("2024/05/01 A post"), None);
+    assert_eq!(leading_date("Post of 2024-05-01"), None);
+    assert_eq!(leading_date("2024-05"), None);
+    assert_eq!(leading_date("２０２４-05-01"), None);
+
+    let response =
+        Response::try_from("20 text/gemini\r\n=> /post.gmi 2024-05-01 A post\n=> /about About\n")
+            .unwrap();
+    assert_eq!(response.links[0].date, Some(date("2024-05-01")));
+    assert_eq!(response.links[1].date, None);
+}
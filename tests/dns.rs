@@ -0,0 +1,93 @@
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::dns::{is_dns_failure, DnsCache};
+use gemini::models::Pager;
+use std::io::{Error, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+use url::Url;
+
+#[test]
+fn unresolvable_hosts_are_dns_failures() {
+    // `.invalid` never resolves (RFC 6761)
+    let error = ("no-such-host.invalid", 1965)
+        .to_socket_addrs()
+        .unwrap_err();
+    assert!(is_dns_failure(&error));
+    assert!(is_dns_failure(&Error::from(ErrorKind::NotFound)));
+}
+
+#[test]
+fn refused_connections_are_not_dns_failures() {
+    // A port that was free a moment ago
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let error = TcpStream::connect(("127.0.0.1", port)).unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::ConnectionRefused);
+    assert!(!is_dns_failure(&error));
+    assert!(!is_dns_failure(&Error::from(ErrorKind::TimedOut)));
+}
+
+#[test]
+fn cached_addresses_expire_after_the_ttl() {
+    let addrs: Vec<SocketAddr> = vec!["192.0.2.1:1965".parse().unwrap()];
+    let cache = DnsCache::with_ttl(Duration::from_millis(50));
+    let shared = cache.clone();
+
+    cache.insert("example.org", 1965, addrs.clone());
+    assert_eq!(shared.lookup("example.org", 1965), Some(addrs));
+    assert_eq!(shared.lookup("example.org", 1966), None);
+    assert_eq!(shared.lookup("other.example", 1965), None);
+
+    thread::sleep(Duration::from_millis(60));
+    assert_eq!(cache.lookup("example.org", 1965), None);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn hosts_can_be_forgotten() {
+    let addrs: Vec<SocketAddr> = vec!["192.0.2.1:1965".parse().unwrap()];
+    let cache = DnsCache::default();
+
+    cache.insert("empty.example", 1965, vec![]);
+    assert!(cache.is_empty());
+
+    cache.insert("a.example", 1965, addrs.clone());
+    cache.insert("b.example", 1965, addrs.clone());
+    cache.invalidate("a.example", 1965);
+    assert_eq!(cache.lookup("a.example", 1965), None);
+    assert_eq!(cache.lookup("b.example", 1965), Some(addrs));
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn hard_reloads_resolve_the_host_again_once() {
+    let url = Url::parse("gemini://example.org/").unwrap();
+    let mut client = Client::new(&url, Pager::Less, Config::default());
+    let addrs: Vec<SocketAddr> = vec!["127.0.0.1:1965".parse().unwrap()];
+    client.dns.insert("example.org", 1965, addrs.clone());
+    client.dns.insert("other.example", 1965, addrs.clone());
+
+    client.prepare_request(url.clone());
+    assert_eq!(client.dns.lookup("example.org", 1965), Some(addrs.clone()));
+
+    client.force_reload = true;
+    client.prepare_request(url.clone());
+    assert!(!client.force_reload);
+    assert_eq!(client.dns.lookup("example.org", 1965), None);
+    assert_eq!(
+        client.dns.lookup("other.example", 1965),
+        Some(addrs.clone())
+    );
+
+    client.dns.insert("example.org", 1965, addrs.clone());
+    client.prepare_request(url);
+    assert_eq!(client.dns.lookup("example.org", 1965), Some(addrs));
+}
@@ -0,0 +1,67 @@
+use gemini::client::{edited_url, Client};
+use gemini::config::Config;
+use gemini::errors::EditError;
+use gemini::models::Pager;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use url::Url;
+
+fn current() -> Url {
+    Url::parse("gemini://example.org/page.gmi").unwrap()
+}
+
+#[test]
+fn empty_content_stays_on_the_page() {
+    assert_eq!(
+        edited_url(&current(), Some(String::new())),
+        Err(EditError::Empty)
+    );
+    assert_eq!(
+        edited_url(&current(), Some(" \n".to_string())),
+        Err(EditError::Empty)
+    );
+}
+
+#[test]
+fn abort_is_told_apart_from_empty() {
+    assert_eq!(edited_url(&current(), None), Err(EditError::Aborted));
+}
+
+#[test]
+fn edited_urls_are_parsed() {
+    assert_eq!(
+        edited_url(
+            &current(),
+            Some("gemini://example.org/other.gmi\n".to_string())
+        ),
+        Ok(Url::parse("gemini://example.org/other.gmi").unwrap())
+    );
+    assert_eq!(
+        edited_url(&current(), Some(format!("{}\n", current()))),
+        Err(EditError::Unchanged)
+    );
+    assert!(matches!(
+        edited_url(&current(), Some("not a url".to_string())),
+        Err(EditError::InvalidUrl(_))
+    ));
+}
+
+// The editor comes from `$EDITOR`, shared by the whole process, so every case
+// using it is in this one test
+#[test]
+fn editor_exits_are_handled() {
+    let mut client = Client::new(&current(), Pager::Less, Config::default());
+
+    std::env::set_var("EDITOR", "false");
+    assert_eq!(client.edit_url(), Err(EditError::Aborted));
+
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("empty-editor");
+    fs::write(&script, "#!/bin/sh\n: > \"$1\"\n").unwrap();
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    std::env::set_var("EDITOR", &script);
+    assert_eq!(client.edit_url(), Err(EditError::Empty));
+
+    std::env::set_var("EDITOR", "true");
+    assert_eq!(client.edit_url(), Err(EditError::Unchanged));
+}
@@ -0,0 +1,44 @@
+use gemini::errors::{io_error_message, RequestError};
+use gemini::models::Request;
+use std::io::{Error, ErrorKind};
+use std::net::TcpListener;
+use url::Url;
+
+#[test]
+fn common_connection_failures_are_explained() {
+    assert_eq!(
+        io_error_message(ErrorKind::ConnectionRefused),
+        Some("Connection refused — the capsule may be down.")
+    );
+    assert_eq!(
+        io_error_message(ErrorKind::WouldBlock),
+        io_error_message(ErrorKind::TimedOut)
+    );
+    assert_eq!(io_error_message(ErrorKind::InvalidData), None);
+
+    let error = RequestError::IoError(Error::from(ErrorKind::InvalidData));
+    assert_eq!(error.user_message(), error.to_string());
+    assert_eq!(
+        RequestError::UnresolvedHost("nowhere.example".to_string()).user_message(),
+        "Could not find nowhere.example — check the address."
+    );
+}
+
+#[test]
+fn refused_connections_say_the_capsule_may_be_down() {
+    // A port that was free a moment ago
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let url = Url::parse(&format!("gemini://127.0.0.1:{port}/")).unwrap();
+
+    let Err(error) = Request::new(url).send() else {
+        panic!("Nothing listens on port {port}");
+    };
+    assert_eq!(
+        error.user_message(),
+        "Connection refused — the capsule may be down."
+    );
+}
@@ -0,0 +1,38 @@
+use gemini::links::handler_command;
+use std::collections::HashMap;
+use url::Url;
+
+fn handlers() -> HashMap<String, String> {
+    [
+        ("gopher".to_string(), "lynx %u".to_string()),
+        ("finger".to_string(), "finger-client --raw".to_string()),
+    ]
+    .into()
+}
+
+#[test]
+fn configured_handlers_get_the_url() {
+    let url = Url::parse("gopher://example.org/1/").unwrap();
+    assert_eq!(
+        handler_command(&handlers(), &url),
+        ["lynx", "gopher://example.org/1/"]
+    );
+}
+
+#[test]
+fn the_url_is_appended_without_a_placeholder() {
+    let url = Url::parse("finger://example.org/user").unwrap();
+    assert_eq!(
+        handler_command(&handlers(), &url),
+        ["finger-client", "--raw", "finger://example.org/user"]
+    );
+}
+
+#[test]
+fn other_schemes_are_opened_with_xdg_open() {
+    let url = Url::parse("https://example.org/").unwrap();
+    assert_eq!(
+        handler_command(&handlers(), &url),
+        ["xdg-open", "https://example.org/"]
+    );
+}
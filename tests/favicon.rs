@@ -0,0 +1,37 @@
+mod common;
+
+use common::MockServer;
+use gemini::favicon::{parse_favicon, Favicons};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn a_favicon_is_a_single_grapheme() {
+    assert_eq!(parse_favicon("🦀\n").as_deref(), Some("🦀"));
+    // A family emoji is several code points but one grapheme
+    assert_eq!(parse_favicon("👩‍👩‍👧").as_deref(), Some("👩‍👩‍👧"));
+    assert_eq!(parse_favicon("🦀🦀"), None);
+    assert_eq!(parse_favicon("# My capsule"), None);
+    assert_eq!(parse_favicon(""), None);
+}
+
+#[test]
+fn favicons_are_fetched_once_per_host() {
+    let server = MockServer::builder()
+        .route("/favicon.txt", "20 text/plain\r\n🦀\n")
+        .start();
+    let client = server.client("/");
+    let favicons = Favicons::default();
+
+    favicons.fetch(&server.url("/docs/page.gmi?q"), |url| {
+        client.build_request(url)
+    });
+    let started = Instant::now();
+    while favicons.get("127.0.0.1").is_none() && started.elapsed() < Duration::from_secs(5) {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(favicons.get("127.0.0.1").as_deref(), Some("🦀"));
+
+    favicons.fetch(&server.url("/other.gmi"), |url| client.build_request(url));
+    assert_eq!(server.requests(), [server.url("/favicon.txt").to_string()]);
+}
@@ -0,0 +1,80 @@
+use chrono::NaiveDate;
+use gemini::feed::{feed_gemtext, parse_feed, FeedEntry};
+use gemini::models::Response;
+
+const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Example capsule</title>
+  <link href="gemini://example.org/"/>
+  <entry>
+    <title>Second post</title>
+    <link rel="alternate" href="gemini://example.org/2.gmi"/>
+    <updated>2024-02-01T10:00:00Z</updated>
+  </entry>
+  <entry>
+    <title type="html">First   post</title>
+    <link href="gemini://example.org/1.gmi"/>
+  </entry>
+  <entry>
+    <title>No link</title>
+  </entry>
+</feed>"#;
+
+#[test]
+fn parses_atom_entries() {
+    let feed = parse_feed(ATOM).unwrap();
+
+    assert_eq!(feed.title.as_deref(), Some("Example capsule"));
+    assert_eq!(
+        feed.entries,
+        vec![
+            FeedEntry {
+                title: "Second post".to_string(),
+                link: "gemini://example.org/2.gmi".to_string(),
+                updated: NaiveDate::from_ymd_opt(2024, 2, 1),
+            },
+            FeedEntry {
+                title: "First post".to_string(),
+                link: "gemini://example.org/1.gmi".to_string(),
+                updated: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn parses_rss_items() {
+    let rss = r#"<rss version="2.0"><channel><title>News</title>
+        <item><title>Hello</title><link>gemini://example.org/hello.gmi</link>
+        <pubDate>Tue, 05 Mar 2024 08:00:00 +0000</pubDate></item>
+        </channel></rss>"#;
+    let feed = parse_feed(rss).unwrap();
+
+    assert_eq!(feed.title.as_deref(), Some("News"));
+    assert_eq!(feed.entries[0].link, "gemini://example.org/hello.gmi");
+    assert_eq!(feed.entries[0].updated, NaiveDate::from_ymd_opt(2024, 3, 5));
+}
+
+#[test]
+fn feeds_are_listed_as_dated_links() {
+    let feed = parse_feed(ATOM).unwrap();
+    assert_eq!(
+        feed_gemtext(&feed),
+        "# Example capsule\n\n\
+         => gemini://example.org/2.gmi 2024-02-01 Second post\n\
+         => gemini://example.org/1.gmi First post\n"
+    );
+
+    let response =
+        Response::try_from(format!("20 application/atom+xml\r\n{ATOM}").as_str()).unwrap();
+    assert!(response.is_feed());
+    assert_eq!(response.links.len(), 2);
+    assert_eq!(response.links[0].href, "gemini://example.org/2.gmi");
+    assert_eq!(response.links[0].date, NaiveDate::from_ymd_opt(2024, 2, 1));
+}
+
+#[test]
+fn invalid_xml_is_not_a_feed() {
+    assert_eq!(parse_feed("<feed><entry>"), None);
+    assert_eq!(parse_feed("<html></html>"), None);
+}
@@ -0,0 +1,85 @@
+use gemini::models::Link;
+use gemini::render::{compact_link_footer, display_href, link_footer, strip_ansi};
+
+#[test]
+fn footer_shows_decoded_hrefs() {
+    let links = vec![Link::new("/caf%C3%A9.gmi".to_string(), None)];
+
+    let footer = strip_ansi(&link_footer(&links, None, &[], &[]).join("\n"));
+    assert_eq!(footer, "0:  (/café.gmi)");
+    let compact = strip_ansi(&compact_link_footer(&links, None, &[], &[], 80).join("\n"));
+    assert_eq!(compact, "[0] /café.gmi");
+
+    assert_eq!(links[0].href, "/caf%C3%A9.gmi");
+}
+
+#[test]
+fn undecodable_hrefs_are_shown_as_is() {
+    assert_eq!(display_href("/a%20b"), "/a b");
+    assert_eq!(display_href("/%FF.gmi"), "/%FF.gmi");
+    assert_eq!(display_href("/%1B[31m"), "/%1B[31m");
+}
+
+#[test]
+fn bidi_controls_are_left_out_of_hrefs() {
+    assert_eq!(display_href("/%E2%80%AEgmi.exe"), "/gmi.exe");
+    assert_eq!(display_href("/\u{202E}gmi.exe"), "/gmi.exe");
+    assert_eq!(display_href("/a\u{2066}b\u{2069}%E2%80%8F.gmi"), "/ab.gmi");
+    assert_eq!(display_href("/%D8%9C%E2%80%8E"), "/");
+    // Undecodable hrefs are still shown as is, without their bidi controls
+    assert_eq!(display_href("/%FF\u{202B}.gmi"), "/%FF.gmi");
+}
+
+#[test]
+fn indices_and_hrefs_line_up() {
+    let mut links: Vec<Link> = (0..11)
+        .map(|i| Link::new(format!("/{i}.gmi"), Some("Page".to_string())))
+        .collect();
+    links[1].name = Some("A longer name".to_string());
+    links[2].name = None;
+
+    let footer: Vec<String> = link_footer(&links, None, &[], &[])
+        .iter()
+        .map(|line| strip_ansi(line))
+        .collect();
+
+    assert_eq!(footer[0], " 0: Page          (/0.gmi)");
+    assert_eq!(footer[1], " 1: A longer name (/1.gmi)");
+    assert_eq!(footer[2], " 2:               (/2.gmi)");
+    assert_eq!(footer[10], "10: Page          (/10.gmi)");
+
+    // Names are cut at 40 columns
+    let long = vec![Link::new("/long.gmi".to_string(), Some("x".repeat(50)))];
+    assert_eq!(
+        strip_ansi(&link_footer(&long, None, &[], &[])[0]),
+        format!("0: {}… (/long.gmi)", "x".repeat(39))
+    );
+}
+
+#[test]
+fn compact_footers_pack_links_into_the_width() {
+    let links: Vec<Link> = ["One", "Two", "Three", "Four"]
+        .iter()
+        .map(|name| Link::new(format!("/{name}.gmi"), Some(name.to_string())))
+        .collect();
+
+    let footer = |width| {
+        compact_link_footer(&links, None, &[], &[], width)
+            .iter()
+            .map(|line| strip_ansi(line))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(footer(80), ["[0] One  [1] Two  [2] Three  [3] Four"]);
+    assert_eq!(footer(20), ["[0] One  [1] Two", "[2] Three  [3] Four"]);
+    // A link wider than the terminal still gets a line of its own
+    assert_eq!(footer(5), ["[0] One", "[1] Two", "[2] Three", "[3] Four"]);
+}
+
+#[test]
+fn compact_footers_shorten_long_names() {
+    let links = vec![Link::new("/long.gmi".to_string(), Some("x".repeat(60)))];
+
+    let footer = strip_ansi(&compact_link_footer(&links, None, &[], &[], 200).join("\n"));
+    assert_eq!(footer.chars().count(), "[0] ".len() + 40);
+    assert!(footer.ends_with('…'));
+}
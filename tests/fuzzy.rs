@@ -0,0 +1,37 @@
+use gemini::links::{best_link_match, fuzzy_score};
+use gemini::models::Link;
+
+fn links() -> Vec<Link> {
+    vec![
+        Link::new("/news.gmi".to_string(), Some("Latest news".to_string())),
+        Link::new("/archive/".to_string(), Some("Archive".to_string())),
+        Link::new("gemini://other.example/".to_string(), None),
+    ]
+}
+
+#[test]
+fn prefixes_beat_substrings_beat_scattered_letters() {
+    assert_eq!(fuzzy_score("arc", "Archive"), Some(1.0));
+    assert_eq!(fuzzy_score("news", "Latest news"), Some(0.9));
+    let scattered = fuzzy_score("lnws", "Latest news").unwrap();
+    assert!(scattered < 0.9, "{scattered}");
+    assert_eq!(fuzzy_score("xyz", "Archive"), None);
+    assert_eq!(fuzzy_score("", "Archive"), None);
+}
+
+#[test]
+fn the_best_matching_link_is_followed() {
+    assert_eq!(best_link_match(&links(), "ARCH"), Some(1));
+    assert_eq!(best_link_match(&links(), "news"), Some(0));
+    assert_eq!(best_link_match(&links(), "other"), Some(2));
+    assert_eq!(best_link_match(&links(), "nothing like it"), None);
+}
+
+#[test]
+fn short_queries_match_no_link() {
+    // A mistyped command letter is in nearly every link
+    assert_eq!(best_link_match(&links(), "a"), None);
+    assert_eq!(best_link_match(&links(), "ar"), None);
+    assert_eq!(best_link_match(&links(), " ar "), None);
+    assert_eq!(best_link_match(&links(), "arc"), Some(1));
+}
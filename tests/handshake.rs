@@ -0,0 +1,56 @@
+mod common;
+
+use native_tls::TlsConnector;
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use common::MockServer;
+use gemini::errors::RequestError;
+use gemini::models::tls_handshake;
+
+fn connector() -> TlsConnector {
+    TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap()
+}
+
+fn nonblocking_stream(server: &MockServer) -> TcpStream {
+    let url = server.url("/");
+    let stream = TcpStream::connect(("127.0.0.1", url.port().unwrap())).unwrap();
+    stream.set_nonblocking(true).unwrap();
+    stream
+}
+
+#[test]
+fn handshake_resumes_until_a_slow_server_answers() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini\r\nhello\n")
+        .delay(Duration::from_millis(200))
+        .start();
+    let stream = nonblocking_stream(&server);
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut stream = tls_handshake(&connector(), "127.0.0.1", stream, Some(deadline)).unwrap();
+
+    stream.get_ref().set_nonblocking(false).unwrap();
+    write!(stream, "{}\r\n", server.url("/")).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok();
+    assert_eq!(response, "20 text/gemini\r\nhello\n");
+}
+
+#[test]
+fn handshake_gives_up_at_the_deadline() {
+    let server = MockServer::builder().delay(Duration::from_secs(2)).start();
+    let stream = nonblocking_stream(&server);
+
+    let started = Instant::now();
+    let deadline = started + Duration::from_millis(100);
+    match tls_handshake(&connector(), "127.0.0.1", stream, Some(deadline)) {
+        Err(RequestError::IoError(e)) => assert_eq!(e.kind(), ErrorKind::TimedOut),
+        other => panic!("expected a timeout, got {other:?}"),
+    }
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use common::MockServer;
+use gemini::errors::HeadlessError;
+use gemini::headless::{exit_code, fetch, follow_link, head, run};
+
+#[test]
+fn redirect_chains_are_followed() {
+    let server = MockServer::builder()
+        .route("/a", "30 /b\r\n")
+        .route("/b", "31 c\r\n")
+        .route("/c", "20 text/gemini\r\n# End\r\n")
+        .start();
+    let mut client = server.client("/a");
+
+    let (url, response) = fetch(&mut client, &server.url("/a")).unwrap();
+    assert_eq!(url, server.url("/c"));
+    assert_eq!(response.title().as_deref(), Some("End"));
+    assert_eq!(client.last_working_url, Some(server.url("/c")));
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn following_stops_at_the_limit() {
+    let mut builder = MockServer::builder();
+    for n in 0..10 {
+        builder = builder.route(&format!("/{n}"), &format!("30 /{}\r\n", n + 1));
+    }
+    let server = builder.start();
+    let mut client = server.client("/0");
+    client.max_redirects = 3;
+
+    let result = fetch(&mut client, &server.url("/0"));
+    assert!(matches!(result, Err(HeadlessError::TooManyRedirects(3))));
+    // The first request and the three redirects
+    assert_eq!(server.requests().len(), 4);
+}
+
+#[test]
+fn loops_are_refused() {
+    let server = MockServer::builder()
+        .route("/ping", "30 /pong\r\n")
+        .route("/pong", "30 /ping\r\n")
+        .start();
+    let mut client = server.client("/ping");
+
+    let result = fetch(&mut client, &server.url("/ping"));
+    assert!(matches!(result, Err(HeadlessError::RedirectLoop(url)) if url == server.url("/ping")));
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn cross_host_redirects_are_refused_unless_allowed() {
+    let server = MockServer::builder()
+        .route("/away", "30 gemini://elsewhere.example/\r\n")
+        .start();
+    let mut client = server.client("/away");
+
+    let result = fetch(&mut client, &server.url("/away"));
+    assert!(
+        matches!(result, Err(HeadlessError::CrossHostRedirect(url)) if url.as_str() == "gemini://elsewhere.example/")
+    );
+
+    // Allowed, the redirect is followed and fails to resolve the made up host
+    client.config.allow_cross_host_redirects = true;
+    client.config.connection.dns_retries = Some(0);
+    let result = fetch(&mut client, &server.url("/away"));
+    assert!(matches!(result, Err(HeadlessError::Request(_))));
+}
+
+#[test]
+fn links_are_followed_in_turn() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini\r\n=> /a A\n=> docs/ Docs\n")
+        .route("/docs/", "20 text/gemini\r\n=> b.gmi B\n")
+        .route("/docs/b.gmi", "20 text/gemini\r\n# B\n")
+        .start();
+    let mut client = server.client("/");
+
+    let mut output = Vec::new();
+    run(&mut client, &server.url("/"), &[1, 0], true, &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "# B\n");
+    assert_eq!(client.last_working_url, Some(server.url("/docs/b.gmi")));
+
+    let result = run(&mut client, &server.url("/"), &[2], true, Vec::new());
+    assert!(matches!(
+        result,
+        Err(HeadlessError::LinkOutOfRange { index: 2, count: 2 })
+    ));
+}
+
+#[test]
+fn head_prints_only_the_header() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini; lang=en\r\n# A long body\n")
+        .route("/old", "31 /new\r\n")
+        .start();
+    let mut client = server.client("/");
+
+    let mut output = Vec::new();
+    assert_eq!(
+        head(&mut client, &server.url("/"), &mut output).unwrap(),
+        20
+    );
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "20 text/gemini; lang=en\n"
+    );
+
+    // Redirects aren't followed
+    let mut output = Vec::new();
+    assert_eq!(
+        head(&mut client, &server.url("/old"), &mut output).unwrap(),
+        31
+    );
+    assert_eq!(String::from_utf8(output).unwrap(), "31 /new\n");
+}
+
+#[test]
+fn exit_codes_are_status_categories() {
+    assert_eq!(exit_code(20), 0);
+    assert_eq!(exit_code(29), 0);
+    assert_eq!(exit_code(10), 1);
+    assert_eq!(exit_code(31), 3);
+    assert_eq!(exit_code(44), 4);
+    assert_eq!(exit_code(51), 5);
+    assert_eq!(exit_code(62), 6);
+}
+
+#[test]
+fn links_resolve_against_the_page_redirected_to() {
+    let server = MockServer::builder()
+        .route("/", "30 /docs/\r\n")
+        .route("/docs/", "20 text/gemini\r\n=> a.gmi A\n")
+        .start();
+    let mut client = server.client("/");
+
+    let (_, response) = fetch(&mut client, &server.url("/")).unwrap();
+    assert_eq!(
+        follow_link(&client, &response, 0).unwrap(),
+        server.url("/docs/a.gmi")
+    );
+
+    assert!(matches!(
+        follow_link(&client, &response, 3),
+        Err(HeadlessError::LinkOutOfRange { index: 3, count: 1 })
+    ));
+}
+
+#[test]
+fn links_the_client_cannot_fetch_are_not_followed() {
+    let server = MockServer::builder()
+        .route(
+            "/",
+            "20 text/gemini\r\n=> https://example.org/ Web\n=> mailto:me@example.org Mail\n",
+        )
+        .start();
+    let mut client = server.client("/");
+
+    let mut output = Vec::new();
+    let error = run(&mut client, &server.url("/"), &[0], false, &mut output).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Link 0 is not a Gemini or Spartan link: https://example.org/"
+    );
+    assert!(matches!(
+        run(&mut client, &server.url("/"), &[1], false, &mut output),
+        Err(HeadlessError::UnfetchableLink { index: 1, .. })
+    ));
+    // Only the page itself was requested
+    assert_eq!(server.requests().len(), 2);
+    assert!(output.is_empty());
+}
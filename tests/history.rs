@@ -0,0 +1,36 @@
+use gemini::models::Response;
+use gemini::pages::history_page;
+use std::collections::HashMap;
+use url::Url;
+
+#[test]
+fn title_is_the_first_heading_outside_preformatted_blocks() {
+    let response = Response::try_from(
+        "20 text/gemini\r\n\
+         Intro\n\
+         ```\n\
+         # Not a heading\n\
+         ```\n\
+         ##  The title \n\
+         # Later\n",
+    )
+    .unwrap();
+    assert_eq!(response.title().as_deref(), Some("The title"));
+
+    let response = Response::try_from("20 text/gemini\r\nNo heading\n").unwrap();
+    assert_eq!(response.title(), None);
+}
+
+#[test]
+fn history_lists_the_latest_page_first_with_known_titles() {
+    let home = Url::parse("gemini://example.org/").unwrap();
+    let news = Url::parse("gemini://example.org/news.gmi").unwrap();
+    let titles = HashMap::from([(news.clone(), "News".to_string())]);
+
+    assert_eq!(
+        history_page(&[home, news], &titles),
+        "# History\n\n\
+         => gemini://example.org/news.gmi News\n\
+         => gemini://example.org/\n"
+    );
+}
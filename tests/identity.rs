@@ -0,0 +1,26 @@
+use gemini::errors::RequestError;
+use gemini::models::load_identity;
+use std::path::Path;
+
+const IDENTITY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/identity.p12");
+
+#[test]
+fn identity_unlocks_with_its_passphrase() {
+    assert!(load_identity(Path::new(IDENTITY), "secret").is_ok());
+}
+
+#[test]
+fn wrong_passphrase_locks_the_identity() {
+    for passphrase in ["wrong", ""] {
+        match load_identity(Path::new(IDENTITY), passphrase) {
+            Err(RequestError::LockedIdentity(path)) => assert_eq!(path, Path::new(IDENTITY)),
+            other => panic!("Expected a locked identity, got {:?}", other.err()),
+        }
+    }
+}
+
+#[test]
+fn missing_identity_is_an_io_error() {
+    let result = load_identity(Path::new("/nonexistent/identity.p12"), "secret");
+    assert!(matches!(result, Err(RequestError::IoError(_))));
+}
@@ -0,0 +1,47 @@
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::dns::IdleTimer;
+use gemini::models::Pager;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use url::Url;
+
+const INTERVAL: Duration = Duration::from_secs(300);
+
+#[test]
+fn idle_only_after_the_interval() {
+    let start = Instant::now();
+    let mut timer = IdleTimer::new(Some(INTERVAL), start);
+
+    assert!(!timer.touch(start + Duration::from_secs(200)));
+    // Counted from the last activity, not from the start
+    assert!(!timer.touch(start + Duration::from_secs(450)));
+    assert!(timer.touch(start + Duration::from_secs(451) + INTERVAL));
+}
+
+#[test]
+fn never_idle_without_an_interval() {
+    let start = Instant::now();
+    let mut timer = IdleTimer::new(None, start);
+    assert!(!timer.touch(start + Duration::from_secs(86_400)));
+}
+
+#[test]
+fn dns_cache_is_cleared_after_idling() {
+    let url = Url::parse("gemini://example.org/").unwrap();
+    let config = Config {
+        refresh_interval: Some(INTERVAL),
+        ..Config::default()
+    };
+    let mut client = Client::new(&url, Pager::Less, config);
+    let start = Instant::now();
+    client.idle = IdleTimer::new(Some(INTERVAL), start);
+    let addr: SocketAddr = "192.0.2.1:1965".parse().unwrap();
+    client.dns.insert("example.org", 1965, vec![addr]);
+
+    assert!(!client.refresh_if_idle(start + Duration::from_secs(10)));
+    assert!(!client.dns.is_empty());
+
+    assert!(client.refresh_if_idle(start + Duration::from_secs(11) + INTERVAL));
+    assert!(client.dns.is_empty());
+}
@@ -0,0 +1,54 @@
+use gemini::config::{Config, ConfigFile};
+use gemini::index::{index_candidates, looks_like_directory};
+use url::Url;
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| (*name).to_string()).collect()
+}
+
+fn candidates(url: &str, list: &[&str]) -> Vec<String> {
+    index_candidates(&Url::parse(url).unwrap(), &names(list))
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn index_files_are_tried_in_a_directory() {
+    assert_eq!(
+        candidates("gemini://example.org/docs/", &["index.gmi", "index.gemini"]),
+        [
+            "gemini://example.org/docs/index.gmi",
+            "gemini://example.org/docs/index.gemini"
+        ]
+    );
+    assert_eq!(
+        candidates("gemini://example.org/docs#top", &["index.gmi"]),
+        ["gemini://example.org/docs/index.gmi"]
+    );
+    assert_eq!(
+        candidates("gemini://example.org", &["index.gmi"]),
+        ["gemini://example.org/index.gmi"]
+    );
+}
+
+#[test]
+fn files_are_not_directories() {
+    let url = |s: &str| Url::parse(s).unwrap();
+    assert!(!looks_like_directory(&url("gemini://example.org/page.gmi")));
+    assert!(!looks_like_directory(&url("gemini://example.org/search?q")));
+    assert!(candidates("gemini://example.org/page.gmi", &["index.gmi"]).is_empty());
+    assert!(candidates("gemini://example.org/docs/", &["", "../secret.gmi"]).is_empty());
+}
+
+#[test]
+fn index_files_default_to_index_gmi() {
+    let mut config = Config::default();
+    assert_eq!(config.index_files, ["index.gmi"]);
+
+    let file: ConfigFile =
+        toml::from_str("try_index_files = true\nindex_files = [\"index.gemini\"]").unwrap();
+    file.apply(&mut config);
+    assert!(config.try_index_files);
+    assert_eq!(config.index_files, ["index.gemini"]);
+}
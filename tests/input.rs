@@ -0,0 +1,85 @@
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::input::{prefill_value, sanitize_input, InputSettings};
+use gemini::models::Pager;
+use url::Url;
+
+#[test]
+fn prefill_is_the_decoded_query() {
+    let url = Url::parse("gemini://example.org/search?caf%C3%A9%20au%20lait").unwrap();
+    assert_eq!(prefill_value(&url).as_deref(), Some("café au lait"));
+}
+
+#[test]
+fn no_query_means_no_prefill() {
+    let url = Url::parse("gemini://example.org/search").unwrap();
+    assert_eq!(prefill_value(&url), None);
+    let url = Url::parse("gemini://example.org/search?").unwrap();
+    assert_eq!(prefill_value(&url), None);
+}
+
+#[test]
+fn failed_answers_are_kept_for_the_prompt() {
+    let prompt = Url::parse("gemini://example.org/search").unwrap();
+    let mut client = Client::new(&prompt, Pager::Less, Config::default());
+    assert_eq!(client.last_input(&prompt), None);
+
+    let sent = Url::parse("gemini://example.org/search?caf%C3%A9").unwrap();
+    client.remember_input(&sent, "café".to_string());
+    assert_eq!(client.last_input(&prompt), Some("café"));
+    // Prompting again from the failed URL itself finds it too
+    assert_eq!(client.last_input(&sent), Some("café"));
+    assert_eq!(
+        client.last_input(&Url::parse("gemini://example.org/other").unwrap()),
+        None
+    );
+
+    client.forget_input(&sent);
+    assert_eq!(client.last_input(&prompt), None);
+}
+
+#[test]
+fn input_is_trimmed_by_default() {
+    let settings = InputSettings::default();
+    assert_eq!(
+        sanitize_input("  two  words \t", settings).as_deref(),
+        Some("two  words")
+    );
+}
+
+#[test]
+fn whitespace_is_collapsed_when_asked() {
+    let settings = InputSettings {
+        trim: true,
+        collapse_whitespace: true,
+        ..InputSettings::default()
+    };
+    assert_eq!(
+        sanitize_input(" two \t\n words ", settings).as_deref(),
+        Some("two words")
+    );
+
+    let settings = InputSettings {
+        trim: false,
+        collapse_whitespace: true,
+        ..InputSettings::default()
+    };
+    assert_eq!(
+        sanitize_input("  two   words ", settings).as_deref(),
+        Some(" two words ")
+    );
+}
+
+#[test]
+fn blank_input_is_not_sent() {
+    assert_eq!(sanitize_input("", InputSettings::default()), None);
+    assert_eq!(sanitize_input(" \t ", InputSettings::default()), None);
+
+    let verbatim = InputSettings {
+        trim: false,
+        collapse_whitespace: false,
+        ..InputSettings::default()
+    };
+    assert_eq!(sanitize_input(" \t ", verbatim).as_deref(), Some(" \t "));
+    assert_eq!(sanitize_input("", verbatim), None);
+}
@@ -0,0 +1,34 @@
+use gemini::lang::{link_language, preferred_language_link};
+use gemini::models::Link;
+
+fn links(names: &[&str]) -> Vec<Link> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| Link::new(format!("/{i}.gmi"), Some(name.to_string())))
+        .collect()
+}
+
+#[test]
+fn language_names_are_recognized_as_words() {
+    assert_eq!(link_language("Version en Français"), Some("fr"));
+    assert_eq!(link_language("[English]"), Some("en"));
+    assert_eq!(link_language("日本語"), Some("ja"));
+    assert_eq!(link_language("Frenchness"), None);
+    assert_eq!(link_language("Home"), None);
+}
+
+#[test]
+fn preferred_version_is_the_first_offered() {
+    let page = links(&["Home", "English", "Deutsch", "Français"]);
+    let preferred = ["eu".to_string(), "FR".to_string(), "de".to_string()];
+
+    assert_eq!(preferred_language_link(&page, &preferred), Some(3));
+    assert_eq!(preferred_language_link(&page, &["eu".to_string()]), None);
+}
+
+#[test]
+fn a_single_language_is_not_a_choice_of_versions() {
+    let page = links(&["English grammar", "English spelling", "Home"]);
+    assert_eq!(preferred_language_link(&page, &["en".to_string()]), None);
+}
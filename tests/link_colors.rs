@@ -0,0 +1,30 @@
+use colored::Color;
+use gemini::links::{classify_link, LinkKind};
+
+#[test]
+fn links_are_classified_by_scheme() {
+    let kind = |href: &str| classify_link(href).kind;
+
+    assert_eq!(kind("gemini://example.org/"), LinkKind::Gemini);
+    assert_eq!(kind("https://example.org/"), LinkKind::Web);
+    assert_eq!(kind("http://example.org/"), LinkKind::Web);
+    assert_eq!(kind("mailto:me@example.org"), LinkKind::Mail);
+    assert_eq!(kind("gopher://example.org/"), LinkKind::Gopher);
+    assert_eq!(kind("finger://me@example.org"), LinkKind::Finger);
+    assert_eq!(kind("irc://example.org/"), LinkKind::Other);
+    assert_eq!(kind("docs/page.gmi"), LinkKind::Gemini);
+}
+
+#[test]
+fn relative_and_absolute_gemini_links_differ_in_color() {
+    let relative = classify_link("/page.gmi");
+    let absolute = classify_link("gemini://example.org/page.gmi");
+
+    assert!(relative.relative);
+    assert!(!absolute.relative);
+    assert_eq!(relative.color(), Color::Cyan);
+    assert_eq!(absolute.color(), Color::Blue);
+    assert_eq!(classify_link("ftp://example.org/").color(), Color::Red);
+    assert_eq!(classify_link("mailto:me@example.org").prefix(), "✉ ");
+    assert_eq!(classify_link("https://example.org/").prefix(), "");
+}
@@ -0,0 +1,34 @@
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::models::{Pager, Response};
+use gemini::pages::link_list;
+use url::Url;
+
+#[test]
+fn links_are_listed_as_absolute_gemtext() {
+    let base = Url::parse("gemini://example.org/docs/index.gmi").unwrap();
+    let client = Client::new(&base, Pager::Less, Config::default());
+    let response = Response::try_from(
+        "20 text/gemini\r\n\
+         # Docs\n\
+         => intro.gmi Introduction\n\
+         => /about.gmi\n\
+         => gemini://other.example/ Elsewhere\n\
+         => https://example.com/ The web\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        link_list(&response.links, |href| client.resolve_link(href)),
+        "=> gemini://example.org/docs/intro.gmi Introduction\n\
+         => gemini://example.org/about.gmi\n\
+         => gemini://other.example/ Elsewhere\n\
+         => https://example.com/ The web\n"
+    );
+}
+
+#[test]
+fn no_links_make_an_empty_list() {
+    let response = Response::try_from("20 text/gemini\r\nJust text\n").unwrap();
+    assert_eq!(link_list(&response.links, Url::parse), "");
+}
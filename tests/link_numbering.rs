@@ -0,0 +1,53 @@
+use gemini::models::Response;
+use gemini::render::{number_link_lines, PlainRenderer, Renderer};
+use url::Url;
+
+const PAGE: &str = "20 text/gemini\r\n\
+                    # Title\n\
+                    => /first.gmi First\n\
+                    ```\n\
+                    => /not-a-link\n\
+                    ```\n\
+                    \x20  => /indented.gmi Indented\n";
+
+#[test]
+fn links_are_the_same_with_and_without_inline_numbers() {
+    let response = Response::try_from(PAGE).unwrap();
+    let url = Url::parse("gemini://example.org/").unwrap();
+    let hrefs: Vec<_> = response.links.iter().map(|l| l.href.as_str()).collect();
+    assert_eq!(hrefs, ["/first.gmi", "/indented.gmi"]);
+
+    let numbered = PlainRenderer {
+        inline_link_numbers: true,
+    }
+    .render(&response, &url);
+    let unnumbered = PlainRenderer {
+        inline_link_numbers: false,
+    }
+    .render(&response, &url);
+
+    assert!(numbered.contains("(0) => /first.gmi First\n"));
+    assert_eq!(unnumbered, response.body.as_deref().unwrap());
+}
+
+#[test]
+fn numbers_follow_the_order_of_links_outside_preformatted_blocks() {
+    let response = Response::try_from(PAGE).unwrap();
+    let numbered = number_link_lines(response.body.as_deref().unwrap());
+
+    assert_eq!(
+        numbered,
+        "# Title\n\
+         (0) => /first.gmi First\n\
+         ```\n\
+         => /not-a-link\n\
+         ```\n\
+         (1) => /indented.gmi Indented"
+    );
+}
+
+#[test]
+fn link_lines_without_a_target_are_not_numbered() {
+    let numbered = number_link_lines("=>\n=> /page.gmi\n=>   ");
+    assert_eq!(numbered, "=>\n(0) => /page.gmi\n=>   ");
+}
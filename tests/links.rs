@@ -0,0 +1,35 @@
+use gemini::handlers::{link_index, out_of_range_link};
+
+#[test]
+fn out_of_range_numbers_state_the_valid_range() {
+    assert_eq!(
+        out_of_range_link("42", 8).as_deref(),
+        Some("No link 42 — this page has 8 links (0–7)")
+    );
+    assert_eq!(
+        out_of_range_link("1", 1).as_deref(),
+        Some("No link 1 — this page has 1 link (0)")
+    );
+    assert_eq!(
+        out_of_range_link("0", 0).as_deref(),
+        Some("No link 0 — this page has no links")
+    );
+}
+
+#[test]
+fn links_on_the_page_and_other_input_are_not_out_of_range() {
+    assert_eq!(out_of_range_link("7", 8), None);
+    assert_eq!(out_of_range_link("0", 8), None);
+    assert_eq!(out_of_range_link("q", 8), None);
+    assert_eq!(out_of_range_link("gemini://example.org/", 0), None);
+}
+
+#[test]
+fn a_dot_or_a_space_selects_the_first_link() {
+    assert_eq!(link_index("."), Some(0));
+    assert_eq!(link_index(" "), Some(0));
+    assert_eq!(link_index("3"), Some(3));
+    assert_eq!(link_index(""), None);
+    assert_eq!(link_index(".."), None);
+    assert_eq!(link_index("q"), None);
+}
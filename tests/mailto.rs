@@ -0,0 +1,44 @@
+use gemini::config::Mailto;
+use gemini::links::{classify_link, external_link, ExternalLink, LinkKind};
+use gemini::models::Link;
+use gemini::render::{link_footer, strip_ansi};
+use std::collections::HashMap;
+use url::Url;
+
+#[test]
+fn mailto_links_are_mail() {
+    let class = classify_link("mailto:me@example.org");
+    assert_eq!(class.kind, LinkKind::Mail);
+    assert_eq!(class.prefix(), "✉ ");
+    assert_eq!(classify_link("https://example.org/").prefix(), "");
+
+    let links = vec![Link::new("mailto:me@example.org".to_string(), None)];
+    let footer = strip_ansi(&link_footer(&links, None, &[], &[]).join("\n"));
+    assert_eq!(footer, "0:  (✉ mailto:me@example.org)");
+}
+
+#[test]
+fn mailto_links_are_shown_or_launched() {
+    let handlers = HashMap::new();
+    let url = Url::parse("mailto:caf%C3%A9@example.org").unwrap();
+
+    assert_eq!(
+        external_link(&handlers, Mailto::Show, &url),
+        ExternalLink::Show("café@example.org".to_string())
+    );
+    assert_eq!(
+        external_link(&handlers, Mailto::Open, &url),
+        ExternalLink::Launch(vec!["xdg-open".to_string(), url.to_string()])
+    );
+}
+
+#[test]
+fn other_links_are_always_launched() {
+    let handlers = HashMap::new();
+    let url = Url::parse("https://example.org/").unwrap();
+
+    assert_eq!(
+        external_link(&handlers, Mailto::Show, &url),
+        ExternalLink::Launch(vec!["xdg-open".to_string(), url.to_string()])
+    );
+}
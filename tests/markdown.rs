@@ -0,0 +1,70 @@
+use gemini::markdown::{extract_links, render};
+use gemini::models::Response;
+
+const PAGE: &str = "# Notes\n\
+                    See [the spec](gemini://example.org/spec \"Spec\") and [](/bare).\n\
+                    Not `[code](/code)` here.\n\
+                    ```\n\
+                    [fenced](/fenced)\n\
+                    ```\n\
+                    **Bold** then [last](/last)";
+
+#[test]
+fn links_are_found_outside_code() {
+    let links = extract_links(PAGE);
+    let found: Vec<_> = links
+        .iter()
+        .map(|link| (link.href.as_str(), link.name.as_deref()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("gemini://example.org/spec", Some("the spec")),
+            ("/bare", None),
+            ("/last", Some("last")),
+        ]
+    );
+}
+
+#[test]
+fn markdown_responses_get_their_links_extracted() {
+    let response = Response::try_from(format!("20 text/markdown\r\n{PAGE}").as_str()).unwrap();
+    assert!(response.is_markdown());
+    assert_eq!(response.links.len(), 3);
+}
+
+#[test]
+fn rendering_numbers_links_and_drops_markup() {
+    colored::control::set_override(false);
+
+    assert_eq!(
+        render(PAGE, true),
+        "Notes\n\
+         See the spec [0] and /bare [1].\n\
+         Not [code](/code) here.\n\
+         [fenced](/fenced)\n\
+         Bold then last [2]"
+    );
+    assert!(render(PAGE, false).starts_with("Notes\nSee the spec and /bare.\n"));
+}
+
+#[test]
+fn inline_numbers_are_the_indices_of_the_links() {
+    colored::control::set_override(false);
+    let body = "# See [the intro](/intro)\n\
+                Read [one](/one).\n\
+                ## Then [two](/two) and [three](/three)\n\
+                Last [four](/four)";
+
+    let rendered = render(body, true);
+    let links = extract_links(body);
+    assert_eq!(links.len(), 5);
+    for (i, link) in links.iter().enumerate() {
+        let name = link.name.as_deref().unwrap();
+        assert!(
+            rendered.contains(&format!("{name} [{i}]")),
+            "{i}: {rendered}"
+        );
+    }
+    assert!(rendered.starts_with("See the intro [0]\n"), "{rendered}");
+}
@@ -0,0 +1,44 @@
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::handlers::footer_lines;
+use gemini::models::{Pager, Response};
+use gemini::render::strip_ansi;
+use url::Url;
+
+const PAGE: &str = "20 text/gemini\r\n\
+                    => /0.gmi Zero\n\
+                    => /1.gmi One\n\
+                    => /2.gmi Two\n";
+
+fn list(max_links: Option<usize>) -> Vec<String> {
+    let url = Url::parse("gemini://example.org/").unwrap();
+    let config = Config {
+        max_links,
+        ..Config::default()
+    };
+    let client = Client::new(&url, Pager::Less, config);
+    let response = Response::try_from(PAGE).unwrap();
+
+    footer_lines(&client, &response, None)
+        .iter()
+        .map(|line| strip_ansi(line))
+        .collect()
+}
+
+#[test]
+fn all_links_are_listed_by_default() {
+    assert_eq!(
+        list(None),
+        ["0: Zero (/0.gmi)", "1: One  (/1.gmi)", "2: Two  (/2.gmi)"]
+    );
+    assert_eq!(list(Some(3)).len(), 3);
+}
+
+#[test]
+fn links_past_the_maximum_are_counted() {
+    assert_eq!(
+        list(Some(2)),
+        ["0: Zero (/0.gmi)", "1: One  (/1.gmi)", "…and 1 more"]
+    );
+    assert_eq!(list(Some(0)), ["…and 3 more"]);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::MockServer;
+use gemini::handlers::handle_request;
+use gemini::models::{SpecificCode, StatusCode};
+
+#[test]
+fn follows_redirects() {
+    let server = MockServer::builder()
+        .route("/old", "30 /new\r\n")
+        .route("/new", "20 text/gemini\r\n# New\r\n")
+        .start();
+    let mut client = server.client("/old");
+
+    let next = handle_request(&mut client, &server.url("/old"));
+    assert_eq!(next, Some(server.url("/new")));
+    assert_eq!(client.redirects, 1);
+
+    let response = client.request(server.url("/new")).unwrap().unwrap();
+    assert!(matches!(response.status_code, StatusCode::Success));
+    assert_eq!(response.title().as_deref(), Some("New"));
+}
+
+#[test]
+fn too_many_redirects_go_back_to_last_working_url() {
+    let server = MockServer::builder().route("/loop", "30 /loop\r\n").start();
+    let mut client = server.client("/loop");
+    client.last_working_url = Some(server.url("/home"));
+
+    let mut url = server.url("/loop");
+    for _ in 1..client.max_redirects {
+        url = handle_request(&mut client, &url).unwrap();
+        assert_eq!(url, server.url("/loop"));
+    }
+
+    assert_eq!(handle_request(&mut client, &url), Some(server.url("/home")));
+}
+
+#[test]
+fn input_is_sent_as_query() {
+    let server = MockServer::builder()
+        .route("/search", "10 Search for\r\n")
+        .route("/search?gemini%20client", "20 text/gemini\r\nfound\r\n")
+        .start();
+    let mut client = server.client("/search");
+
+    let prompt = client.request(server.url("/search")).unwrap().unwrap();
+    assert_eq!(prompt.specific_code(), SpecificCode::Input);
+    assert_eq!(prompt.meta_description, "Search for");
+
+    let mut answer_url = server.url("/search");
+    answer_url.set_query(Some("gemini client"));
+    let answer = client.request(answer_url).unwrap().unwrap();
+
+    assert_eq!(answer.body.as_deref(), Some("found"));
+    assert_eq!(
+        server.requests().last().map(String::as_str),
+        Some(server.url("/search?gemini%20client").as_str())
+    );
+}
+
+#[test]
+fn missing_pages_are_permanent_failures() {
+    let server = MockServer::builder().start();
+    let mut client = server.client("/");
+
+    let response = client.request(server.url("/nothing")).unwrap().unwrap();
+    assert_eq!(response.specific_code(), SpecificCode::NotFound);
+    assert_eq!(handle_request(&mut client, &server.url("/nothing")), None);
+}
@@ -0,0 +1,88 @@
+use clap::ValueEnum;
+use gemini::handlers::write_page;
+use gemini::models::Pager;
+use gemini::pager::{max_top, scroll, visible_lines, Scroll};
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+#[test]
+fn pagers_cycle_in_declaration_order() {
+    assert_eq!(Pager::Less.next(), Pager::More);
+    assert_eq!(Pager::More.next(), Pager::Bat);
+    assert_eq!(Pager::Bat.next(), Pager::Neovim);
+    assert_eq!(Pager::Neovim.next(), Pager::Internal);
+    assert_eq!(Pager::Internal.next(), Pager::Less);
+}
+
+#[test]
+fn cycling_visits_every_pager() {
+    let variants = Pager::value_variants();
+    for (i, pager) in variants.iter().enumerate() {
+        assert_eq!(pager.next(), variants[(i + 1) % variants.len()]);
+    }
+}
+
+#[test]
+fn scrolling_stops_at_the_last_screenful() {
+    // 100 lines, 23 shown at a time
+    assert_eq!(max_top(100, 23), 77);
+    assert_eq!(scroll(0, Scroll::LineDown, 100, 23), 1);
+    assert_eq!(scroll(0, Scroll::PageDown, 100, 23), 23);
+    assert_eq!(scroll(70, Scroll::PageDown, 100, 23), 77);
+    assert_eq!(scroll(77, Scroll::LineDown, 100, 23), 77);
+    assert_eq!(scroll(40, Scroll::Bottom, 100, 23), 77);
+}
+
+#[test]
+fn scrolling_stops_at_the_top() {
+    assert_eq!(scroll(0, Scroll::LineUp, 100, 23), 0);
+    assert_eq!(scroll(10, Scroll::PageUp, 100, 23), 0);
+    assert_eq!(scroll(50, Scroll::PageUp, 100, 23), 27);
+    assert_eq!(scroll(50, Scroll::Top, 100, 23), 0);
+}
+
+#[test]
+fn short_pages_do_not_scroll() {
+    assert_eq!(max_top(10, 23), 0);
+    assert_eq!(scroll(0, Scroll::PageDown, 10, 23), 0);
+    assert_eq!(visible_lines(0, 10, 23), 0..10);
+}
+
+#[test]
+fn window_follows_the_terminal_size() {
+    assert_eq!(visible_lines(77, 100, 23), 77..100);
+    // After the terminal grew, the same top shows fewer lines than the window
+    assert_eq!(visible_lines(77, 100, 40), 77..100);
+    assert_eq!(scroll(77, Scroll::LineDown, 100, 40), 60);
+    assert_eq!(visible_lines(0, 0, 23), 0..0);
+}
+
+#[test]
+fn pagers_quit_early_end_the_page() {
+    let mut child = Command::new("true").stdin(Stdio::piped()).spawn().unwrap();
+    let stdin = child.stdin.take().unwrap();
+    child.wait().unwrap();
+
+    let page = "A line of the page\n".repeat(100_000);
+    assert!(write_page(stdin, &page).is_ok());
+}
+
+#[test]
+fn other_write_errors_are_reported() {
+    struct Full;
+    impl Write for Full {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::StorageFull.into())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let error = write_page(Full, "page").unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::StorageFull);
+
+    let mut written = Vec::new();
+    write_page(&mut written, "page").unwrap();
+    assert_eq!(written, b"page");
+}
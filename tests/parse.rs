@@ -0,0 +1,247 @@
+use std::time::{Duration, Instant};
+
+use gemini::errors::ResponseError;
+use gemini::parse::{Link, Response, StatusCode, MAX_META_LENGTH};
+
+#[test]
+fn a_1_mb_body_is_parsed_quickly() {
+    let mut page = String::from("20 text/gemini\r\n");
+    let mut n = 0;
+    while page.len() < 1024 * 1024 {
+        page.push_str(&format!("Some prose.\n=> /page/{n}.gmi Link {n}\n"));
+        n += 1;
+    }
+
+    let started = Instant::now();
+    let response = Response::try_from(page.as_str()).unwrap();
+    // Generous for unoptimized builds, a release build takes a few milliseconds
+    assert!(
+        started.elapsed() < Duration::from_secs(2),
+        "{:?}",
+        started.elapsed()
+    );
+    assert!(matches!(response.status_code, StatusCode::Success));
+    assert_eq!(response.links.len(), n);
+    assert_eq!(response.links[n - 1].href, format!("/page/{}.gmi", n - 1));
+}
+
+/// Use everything a parsed response offers, none of it may panic.
+fn exercise(input: &str) {
+    if let Ok(mut response) = Response::try_from(input) {
+        response.headings();
+        response.title();
+        response.link_line(0);
+        response.gemtext();
+        response.status_summary();
+        response.sniff_gemtext();
+    }
+    Response::try_from(input.as_bytes()).ok();
+}
+
+#[test]
+fn malformed_headers_are_errors() {
+    let error = |input: &str| Response::try_from(input).err().map(|e| e.to_string());
+
+    assert_eq!(error(""), Some(ResponseError::EmptyResponse.to_string()));
+    assert_eq!(
+        error(" text/gemini\r\n"),
+        Some(ResponseError::MissingStatusCode.to_string())
+    );
+    for header in [
+        "+2 text/gemini\r\n",
+        "2 text/gemini\r\n",
+        "200 ok\r\n",
+        "٢٠ x\r\n",
+    ] {
+        assert_eq!(
+            error(header),
+            Some(ResponseError::InvalidStatusCode.to_string())
+        );
+    }
+    assert_eq!(
+        error("20\r\n"),
+        Some(ResponseError::MissingMetaDescription.to_string())
+    );
+    assert_eq!(
+        error("20 text/gemini\rbody"),
+        Some(ResponseError::ControlInMeta.to_string())
+    );
+    assert_eq!(
+        error("51 not\0found\r\n"),
+        Some(ResponseError::ControlInMeta.to_string())
+    );
+}
+
+#[test]
+fn odd_bodies_are_parsed() {
+    let response = Response::try_from("20 text/gemini").unwrap();
+    assert_eq!(response.body, None);
+
+    let response = Response::try_from("20 text/gemini\r\n=> /a\0 A\rB\n\0=> /b\n").unwrap();
+    assert_eq!(response.body.as_deref(), Some("=> /a\0 A\rB\n\0=> /b"));
+    assert_eq!(response.links.len(), 1);
+    assert_eq!(response.links[0].href, "/a\0");
+    assert_eq!(response.links[0].name.as_deref(), Some("A\rB"));
+
+    let line = "=> /long ".to_string() + &"é".repeat(512 * 1024);
+    let response = Response::try_from(format!("20 text/gemini\r\n{line}").as_str()).unwrap();
+    assert_eq!(response.links.len(), 1);
+    assert_eq!(response.body.as_deref(), Some(line.as_str()));
+}
+
+#[test]
+fn hostile_inputs_do_not_panic() {
+    let long = "x".repeat(100_000);
+    let inputs = [
+        "\0".to_string(),
+        "\r".to_string(),
+        "\r\n".to_string(),
+        "20 \r\n".to_string(),
+        format!("20 {long}"),
+        format!("20 text/gemini\r\n{long}"),
+        "20 text/markdown\r\n[`é`](`é`) ![](é) [é](\n[a](b \"c\")".to_string(),
+        "20 text/markdown\r\n`[x](y)` ![[()](!)] ```\n[a](b)".to_string(),
+        "20 application/atom+xml\r\n<feed><entry><link href=\"/a\"/><updated>é</updated>"
+            .to_string(),
+        "20 application/rss+xml\r\n<rss><channel><item><link>x</link><pubDate>\0".to_string(),
+        "20 text/plain\r\n# é\n=> x\n```".to_string(),
+        "10 \u{202e}evil\r\n".to_string(),
+        "20 text/gemini\r\n#\n##\n###é\n=>\n=> \n```\n=> /in\n```\n2024-01-0é".to_string(),
+    ];
+    for input in &inputs {
+        exercise(input);
+    }
+}
+
+#[test]
+fn generated_inputs_do_not_panic() {
+    const HEADERS: [&str; 8] = [
+        "20 text/gemini\r\n",
+        "20 text/markdown\r\n",
+        "20 application/atom+xml\r\n",
+        "20 text/plain; lang=é\r\n",
+        "31 =>\r\n",
+        "10 ?\r\n",
+        "2",
+        "",
+    ];
+    const PIECES: [&str; 20] = [
+        "=>",
+        " ",
+        "\n",
+        "\r",
+        "\r\n",
+        "\0",
+        "```",
+        "#",
+        "[",
+        "]",
+        "(",
+        ")",
+        "`",
+        "!",
+        "é",
+        "2024-01-01",
+        "/a",
+        "<entry>",
+        "\u{1F600}",
+        "\t",
+    ];
+
+    // A fixed linear congruential generator, the same inputs on every run
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = |bound: usize| {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        usize::try_from(state >> 33).unwrap() % bound
+    };
+
+    for _ in 0..2000 {
+        let mut input = HEADERS[next(HEADERS.len())].to_string();
+        for _ in 0..next(40) {
+            input.push_str(PIECES[next(PIECES.len())]);
+        }
+        exercise(&input);
+    }
+}
+
+#[test]
+fn fuzz_corpus_does_not_panic() {
+    let corpus = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/response");
+    let mut seeds = 0;
+    for entry in std::fs::read_dir(corpus).unwrap() {
+        let data = std::fs::read(entry.unwrap().path()).unwrap();
+        exercise(&String::from_utf8_lossy(&data));
+        Response::try_from(data.as_slice()).ok();
+        seeds += 1;
+    }
+    assert!(seeds > 0);
+}
+
+#[test]
+fn meta_of_1024_bytes_is_the_longest_allowed() {
+    let header = |len: usize| format!("10 {}\r\n", "é".repeat(len / 2) + &"x".repeat(len % 2));
+
+    let response = Response::try_from(header(MAX_META_LENGTH).as_str()).unwrap();
+    assert_eq!(response.meta_description.len(), MAX_META_LENGTH);
+
+    assert_eq!(
+        Response::try_from(header(MAX_META_LENGTH + 1).as_str())
+            .err()
+            .map(|e| e.to_string()),
+        Some(ResponseError::MetaTooLong(MAX_META_LENGTH + 1).to_string())
+    );
+}
+
+#[test]
+fn header_ends_at_the_first_line_ending() {
+    let response = Response::try_from("20 text/gemini\r\n=> /only A single line").unwrap();
+    assert_eq!(response.meta_description, "text/gemini");
+    assert_eq!(response.body.as_deref(), Some("=> /only A single line"));
+    assert_eq!(response.links.len(), 1);
+
+    let response = Response::try_from("20 text/plain\nbody\r\nmore").unwrap();
+    assert_eq!(response.meta_description, "text/plain");
+    assert_eq!(response.body.as_deref(), Some("body\nmore"));
+
+    for header_only in ["20 text/gemini\r\n", "20 text/gemini\r", "30 /next"] {
+        let response = Response::try_from(header_only).unwrap();
+        assert_eq!(response.body, None, "{header_only:?}");
+    }
+}
+
+#[test]
+fn only_unindented_backticks_are_fences() {
+    let response = Response::try_from(
+        "20 text/gemini\r\n\
+         \x20```\n\
+         # Heading\n\
+         => /first.gmi First\n\
+         ```\n\
+         # Hidden\n\
+         => /hidden.gmi Hidden\n\
+         ```\n",
+    )
+    .unwrap();
+
+    let headings: Vec<_> = response.headings().into_iter().map(|h| h.text).collect();
+    assert_eq!(headings, ["Heading"]);
+    assert_eq!(response.title().as_deref(), Some("Heading"));
+    assert_eq!(response.links.len(), 1);
+    assert_eq!(response.link_line(0), Some(3));
+    assert_eq!(response.link_line(1), None);
+}
+
+#[test]
+fn link_lines_without_a_target_are_not_links() {
+    for line in ["=>", "=>   ", "  =>", "=>\t"] {
+        assert!(Link::try_from(line).is_err(), "{line:?}");
+    }
+    let link = Link::try_from("=>  /page.gmi A page").ok();
+    assert_eq!(link.map(|l| l.href).as_deref(), Some("/page.gmi"));
+
+    let response = Response::try_from("20 text/gemini\r\n=>\n=> /page.gmi\n=>  \n").unwrap();
+    let hrefs: Vec<_> = response.links.iter().map(|l| l.href.as_str()).collect();
+    assert_eq!(hrefs, ["/page.gmi"]);
+}
@@ -0,0 +1,44 @@
+use gemini::client::{parent_url, toggle_trailing_slash};
+use url::Url;
+
+fn parent(url: &str) -> String {
+    parent_url(&Url::parse(url).unwrap()).to_string()
+}
+
+#[test]
+fn parent_of_a_file_is_its_directory() {
+    assert_eq!(
+        parent("gemini://example.org/docs/page.gmi?q=1#top"),
+        "gemini://example.org/docs/"
+    );
+}
+
+#[test]
+fn parent_of_a_directory_is_the_one_above() {
+    assert_eq!(
+        parent("gemini://example.org/docs/specs/"),
+        "gemini://example.org/docs/"
+    );
+    assert_eq!(parent("gemini://example.org/docs"), "gemini://example.org/");
+}
+
+#[test]
+fn root_is_its_own_parent() {
+    assert_eq!(parent("gemini://example.org/"), "gemini://example.org/");
+    assert_eq!(parent("gemini://example.org"), "gemini://example.org/");
+}
+
+#[test]
+fn trailing_slash_is_toggled_except_on_the_root() {
+    let toggle = |url: &str| toggle_trailing_slash(&Url::parse(url).unwrap()).to_string();
+
+    assert_eq!(
+        toggle("gemini://example.org/docs?q"),
+        "gemini://example.org/docs/?q"
+    );
+    assert_eq!(
+        toggle("gemini://example.org/docs/"),
+        "gemini://example.org/docs"
+    );
+    assert_eq!(toggle("gemini://example.org/"), "gemini://example.org/");
+}
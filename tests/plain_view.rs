@@ -0,0 +1,37 @@
+use gemini::models::Response;
+use gemini::render::{DateDisplay, GemtextRenderer, PlainRenderer, PreformattedOverflow, Renderer};
+use url::Url;
+
+#[test]
+fn plain_render_has_no_ansi_escapes() {
+    // Colors are off when stdout isn't a terminal, as under `cargo test`
+    colored::control::set_override(true);
+
+    let response = Response::try_from(
+        "20 text/gemini\r\n\
+         # Title\n\
+         ## Section\n\
+         > A quote\n\
+         * An item\n\
+         => /next.gmi Next\n\
+         => /prev.gmi Previous\n",
+    )
+    .unwrap();
+    let url = Url::parse("gemini://example.org/").unwrap();
+
+    let styled = GemtextRenderer {
+        inline_link_numbers: true,
+        preformatted_overflow: PreformattedOverflow::Scroll,
+        dates: DateDisplay::Absolute,
+    }
+    .render(&response, &url);
+    assert!(styled.contains('\x1b'));
+
+    let plain = PlainRenderer {
+        inline_link_numbers: true,
+    }
+    .render(&response, &url);
+    assert!(!plain.contains('\x1b'), "{plain:?}");
+    assert!(plain.contains("(0) => /next.gmi Next"));
+    assert!(plain.contains("(1) => /prev.gmi Previous"));
+}
@@ -0,0 +1,65 @@
+use gemini::models::Link;
+use gemini::prefetch::{prefetch_targets, MAX_PREFETCH};
+use url::Url;
+
+fn links(hrefs: &[&str]) -> Vec<Link> {
+    hrefs
+        .iter()
+        .map(|href| Link::new((*href).to_string(), None))
+        .collect()
+}
+
+fn resolver(href: &str) -> Result<Url, url::ParseError> {
+    Url::parse("gemini://example.org/dir/").unwrap().join(href)
+}
+
+#[test]
+fn first_pages_are_selected() {
+    let links = links(&["one", "two", "three", "four"]);
+    let targets: Vec<String> = prefetch_targets(&links, 2, resolver)
+        .into_iter()
+        .map(String::from)
+        .collect();
+    assert_eq!(
+        targets,
+        [
+            "gemini://example.org/dir/one",
+            "gemini://example.org/dir/two"
+        ]
+    );
+}
+
+#[test]
+fn other_schemes_and_duplicates_are_skipped() {
+    let links = links(&[
+        "https://example.com/",
+        "mailto:me@example.org",
+        "one#top",
+        "one",
+        "spartan://example.net/",
+        "two",
+    ]);
+    let targets: Vec<String> = prefetch_targets(&links, 3, resolver)
+        .into_iter()
+        .map(String::from)
+        .collect();
+    assert_eq!(
+        targets,
+        [
+            "gemini://example.org/dir/one",
+            "spartan://example.net/",
+            "gemini://example.org/dir/two"
+        ]
+    );
+}
+
+#[test]
+fn count_is_capped() {
+    let hrefs: Vec<String> = (0..MAX_PREFETCH * 2).map(|n| format!("page{n}")).collect();
+    let hrefs: Vec<&str> = hrefs.iter().map(String::as_str).collect();
+    assert_eq!(
+        prefetch_targets(&links(&hrefs), usize::MAX, resolver).len(),
+        MAX_PREFETCH
+    );
+    assert!(prefetch_targets(&links(&hrefs), 0, resolver).is_empty());
+}
@@ -0,0 +1,105 @@
+use gemini::models::Response;
+use gemini::render::{
+    fence_alt_text, truncate_preformatted, DateDisplay, GemtextRenderer, HtmlRenderer,
+    PreformattedOverflow, Renderer,
+};
+use unicode_width::UnicodeWidthStr;
+use url::Url;
+
+#[test]
+fn over_wide_lines_end_with_a_marker_at_the_edge() {
+    assert_eq!(truncate_preformatted("+----------+", 8), "+------→");
+    assert_eq!(truncate_preformatted("+----------+", 8).width(), 8);
+    // Wide characters aren't split
+    assert_eq!(truncate_preformatted("日本語のアート", 6), "日本→");
+}
+
+#[test]
+fn lines_that_fit_are_kept_with_their_spaces() {
+    assert_eq!(truncate_preformatted("| ok |   ", 9), "| ok |   ");
+    assert_eq!(truncate_preformatted("", 4), "");
+}
+
+#[test]
+fn only_preformatted_lines_are_truncated() {
+    let wide = "#".repeat(1000);
+    let prose = "word ".repeat(400);
+    let response =
+        Response::try_from(format!("20 text/gemini\r\n```\n{wide}\n```\n{prose}\n").as_str())
+            .unwrap();
+    let url = Url::parse("gemini://example.org/").unwrap();
+
+    let renderer = GemtextRenderer {
+        inline_link_numbers: true,
+        preformatted_overflow: PreformattedOverflow::Truncate,
+        dates: DateDisplay::Absolute,
+    };
+    let rendered = renderer.render(&response, &url);
+    let mut lines = rendered.lines();
+    let art = lines.next().unwrap();
+    assert!(art.ends_with('→'), "{art}");
+    assert!(art.width() < wide.width());
+    assert_eq!(lines.next(), Some(prose.as_str()));
+
+    let renderer = GemtextRenderer {
+        inline_link_numbers: true,
+        preformatted_overflow: PreformattedOverflow::Scroll,
+        dates: DateDisplay::Absolute,
+    };
+    assert!(renderer
+        .render(&response, &url)
+        .starts_with(&format!("{wide}\n")));
+}
+
+const CAPTIONED: &str = "20 text/gemini\r\n\
+                         ```A <table>\n\
+                         | a | b |\n\
+                         ``` closing text is not a caption\n\
+                         ```\n\
+                         plain\n\
+                         ```\n\
+                         After\n";
+
+fn gemtext_renderer() -> GemtextRenderer {
+    GemtextRenderer {
+        inline_link_numbers: false,
+        preformatted_overflow: PreformattedOverflow::Scroll,
+        dates: DateDisplay::Absolute,
+    }
+}
+
+#[test]
+fn alt_text_follows_the_backticks() {
+    assert_eq!(fence_alt_text("```table"), Some("table"));
+    assert_eq!(fence_alt_text("```  ASCII art  "), Some("ASCII art"));
+    assert_eq!(fence_alt_text("```"), None);
+    assert_eq!(fence_alt_text("```   "), None);
+    assert_eq!(fence_alt_text("text"), None);
+}
+
+#[test]
+fn opening_fences_with_alt_text_are_shown_as_captions() {
+    colored::control::set_override(false);
+    let response = Response::try_from(CAPTIONED).unwrap();
+    let url = Url::parse("gemini://example.org/").unwrap();
+
+    assert_eq!(
+        gemtext_renderer().render(&response, &url),
+        "A <table>\n| a | b |\nplain\nAfter"
+    );
+    // The caption takes the place of its fence, the other fences are hidden
+    assert_eq!(gemtext_renderer().rendered_line(&response, 2), 2);
+    assert_eq!(gemtext_renderer().rendered_line(&response, 5), 3);
+    assert_eq!(gemtext_renderer().rendered_line(&response, 7), 4);
+}
+
+#[test]
+fn html_labels_captioned_blocks() {
+    let response = Response::try_from(CAPTIONED).unwrap();
+    let url = Url::parse("gemini://example.org/").unwrap();
+
+    let html = HtmlRenderer.render(&response, &url);
+    assert!(html.contains(
+        "<pre aria-label=\"A &lt;table&gt;\" title=\"A &lt;table&gt;\">\n| a | b |\n</pre>\n<pre>\nplain\n</pre>\n"
+    ), "{html}");
+}
@@ -0,0 +1,33 @@
+use gemini::config::Config;
+use gemini::prompt::{expand_prompt, PromptState, DEFAULT_PROMPT};
+
+const STATE: PromptState = PromptState {
+    url: "gemini://example.org/notes/",
+    host: "example.org",
+    title: "Notes",
+    links: 12,
+};
+
+#[test]
+fn placeholders_are_filled_in() {
+    assert_eq!(
+        expand_prompt("[{n} links] {url}> ", &STATE),
+        "[12 links] gemini://example.org/notes/> "
+    );
+    assert_eq!(
+        expand_prompt("{title} on {host}: ", &STATE),
+        "Notes on example.org: "
+    );
+}
+
+#[test]
+fn braces_and_unknown_placeholders_are_kept() {
+    assert_eq!(expand_prompt("{{n}} {tab} {n", &STATE), "{n} {tab} {n");
+    assert_eq!(expand_prompt("} {} {n}}", &STATE), "} {} 12}");
+}
+
+#[test]
+fn the_default_prompt_is_unchanged() {
+    assert_eq!(Config::default().prompt, DEFAULT_PROMPT);
+    assert_eq!(expand_prompt(DEFAULT_PROMPT, &STATE), DEFAULT_PROMPT);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::MockServer;
+use gemini::models::{Response, StatusCode};
+use gemini::protocol::{port_of, protocol_for, Gemini, Protocol, Spartan};
+use url::Url;
+
+#[test]
+fn gemini_request_line_is_the_url_without_fragment() {
+    let url = Url::parse("gemini://example.org:1966/search?q=a%20b#results").unwrap();
+    assert_eq!(
+        Gemini.build_request_line(&url),
+        "gemini://example.org:1966/search?q=a%20b\r\n"
+    );
+    assert_eq!(Gemini.gemini_header("20 text/gemini\r"), "20 text/gemini\r");
+}
+
+#[test]
+fn ports_default_to_the_protocol_one() {
+    let port = |url: &str| port_of(&Url::parse(url).unwrap());
+    assert_eq!(port("gemini://example.org/"), 1965);
+    assert_eq!(port("gemini://example.org:1966/"), 1966);
+    assert_eq!(port("spartan://example.org/"), 300);
+    assert_eq!(protocol_for("gopher").map(|p| p.default_port()), None);
+    assert_eq!(protocol_for("about").map(|p| p.default_port()), None);
+}
+
+#[test]
+fn spartan_request_line_has_host_path_and_length() {
+    let url = Url::parse("spartan://example.org/docs/").unwrap();
+    assert_eq!(Spartan.build_request_line(&url), "example.org /docs/ 0\r\n");
+}
+
+#[test]
+fn spartan_asks_for_the_root_of_a_bare_host() {
+    let url = Url::parse("spartan://example.org").unwrap();
+    assert_eq!(url.path(), "");
+    assert_eq!(Spartan.build_request_line(&url), "example.org / 0\r\n");
+    let url = Url::parse("spartan://example.org?hi").unwrap();
+    assert_eq!(Spartan.build_request_line(&url), "example.org / 2\r\nhi");
+}
+
+#[test]
+fn spartan_query_is_sent_as_data() {
+    let url = Url::parse("spartan://example.org/search?caf%C3%A9%20au%20lait").unwrap();
+    assert_eq!(
+        Spartan.build_request_line(&url),
+        "example.org /search 13\r\ncafé au lait"
+    );
+}
+
+#[test]
+fn spartan_links_use_plain_tcp_on_port_300() {
+    let protocol = protocol_for("spartan").unwrap();
+    assert!(!protocol.uses_tls());
+    assert_eq!(protocol.default_port(), 300);
+    assert!(protocol_for("gemini").unwrap().uses_tls());
+}
+
+#[test]
+fn spartan_statuses_map_to_gemini_ones() {
+    let header = Spartan.gemini_header("2 text/gemini\r");
+    let response = Response::try_from(format!("{header}\n# Hello\n").as_str()).unwrap();
+    assert!(matches!(response.status_code, StatusCode::Success));
+    assert_eq!(response.meta_description, "text/gemini");
+    assert_eq!(response.title().as_deref(), Some("Hello"));
+
+    for (spartan, gemini) in [("3 /elsewhere", 30), ("4 Not found", 59), ("5 Oops", 40)] {
+        let header = Spartan.gemini_header(&format!("{spartan}\r"));
+        let response = Response::try_from(format!("{header}\n").as_str()).unwrap();
+        assert_eq!(response.status_code_num, gemini);
+    }
+}
+
+#[test]
+fn requests_send_the_protocol_request_line() {
+    let server = MockServer::builder()
+        .route("/page", "20 text/gemini\r\n# Page\n")
+        .start();
+    let mut client = server.client("/");
+    let mut url = server.url("/page");
+    url.set_fragment(Some("part"));
+
+    client.request(url).unwrap().ok().unwrap();
+    assert_eq!(server.requests(), [server.url("/page").to_string()]);
+}
@@ -0,0 +1,38 @@
+use gemini::input::{encode_query, QueryEncoding};
+
+#[test]
+fn raw_mode_percent_encodes_the_whole_input() {
+    assert_eq!(
+        encode_query("gemini client", QueryEncoding::Raw),
+        "gemini%20client"
+    );
+    assert_eq!(
+        encode_query("name=Jo Doe&lang=fr", QueryEncoding::Raw),
+        "name%3DJo%20Doe%26lang%3Dfr"
+    );
+    assert_eq!(
+        encode_query("café-1.0_~", QueryEncoding::Raw),
+        "caf%C3%A9-1.0_~"
+    );
+}
+
+#[test]
+fn form_mode_encodes_pairs() {
+    assert_eq!(
+        encode_query("name=Jo Doe&lang=fr", QueryEncoding::Form),
+        "name=Jo+Doe&lang=fr"
+    );
+    assert_eq!(
+        encode_query("q=a+b&x=1=2", QueryEncoding::Form),
+        "q=a%2Bb&x=1%3D2"
+    );
+    assert_eq!(
+        encode_query("gemini client", QueryEncoding::Form),
+        "gemini+client"
+    );
+}
+
+#[test]
+fn raw_is_the_default() {
+    assert_eq!(QueryEncoding::default(), QueryEncoding::Raw);
+}
@@ -0,0 +1,25 @@
+use gemini::output::notice_to;
+use std::process::Command;
+
+#[test]
+fn quiet_mode_suppresses_notices() {
+    let mut out = Vec::new();
+    notice_to(&mut out, true, "Goodbye!");
+    assert!(out.is_empty());
+
+    notice_to(&mut out, false, "Goodbye!");
+    assert_eq!(out, b"Goodbye!\n");
+}
+
+#[test]
+fn errors_still_reach_stderr_in_quiet_mode() {
+    // Nothing listens on port 1
+    let output = Command::new(env!("CARGO_BIN_EXE_gemini"))
+        .args(["--quiet", "--raw", "gemini://127.0.0.1:1/"])
+        .env("RUST_LOG", "error")
+        .output()
+        .unwrap();
+
+    assert!(output.stdout.is_empty());
+    assert!(!output.stderr.is_empty());
+}
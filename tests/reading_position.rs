@@ -0,0 +1,158 @@
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::handlers::{reading_position, save_reading_position, shown_line};
+use gemini::models::{Pager, Response};
+use gemini::render::RendererKind;
+use url::Url;
+
+const PAGE: &str = "20 text/gemini\r\n\
+    # Essay\n\
+    \n\
+    Some text with a reference.\n\
+    => /ref1.gmi [1] First reference\n\
+    ```\n\
+    => not a link\n\
+    ```\n\
+    More text.\n\
+    => /ref2.gmi [2] Second reference\n";
+
+fn client(url: &Url) -> Client {
+    Client::new(url, Pager::Less, Config::default())
+}
+
+#[test]
+fn links_are_found_on_their_body_line() {
+    let response = Response::try_from(PAGE).unwrap();
+    assert_eq!(response.link_line(0), Some(4));
+    assert_eq!(response.link_line(1), Some(9));
+    assert_eq!(response.link_line(2), None);
+}
+
+#[test]
+fn reading_resumes_at_the_followed_reference() {
+    let url = Url::parse("gemini://example.org/essay.gmi").unwrap();
+    let mut client = client(&url);
+    let response = Response::try_from(PAGE).unwrap();
+
+    assert_eq!(reading_position(&mut client, &response, &url), None);
+
+    save_reading_position(&mut client, &response, &url, 1);
+    // Fence lines aren't shown, the second reference is 2 lines higher once rendered
+    assert_eq!(reading_position(&mut client, &response, &url), Some(7));
+    // Back at the top the next time the page is shown
+    assert_eq!(reading_position(&mut client, &response, &url), None);
+}
+
+#[test]
+fn other_pages_keep_their_own_position() {
+    let url = Url::parse("gemini://example.org/essay.gmi").unwrap();
+    let other = Url::parse("gemini://example.org/ref2.gmi").unwrap();
+    let mut client = client(&url);
+    let response = Response::try_from(PAGE).unwrap();
+
+    save_reading_position(&mut client, &response, &url, 0);
+    assert_eq!(reading_position(&mut client, &response, &other), None);
+    assert_eq!(reading_position(&mut client, &response, &url), Some(4));
+}
+
+#[test]
+fn plain_views_keep_the_body_line() {
+    let url = Url::parse("gemini://example.org/essay.gmi").unwrap();
+    let response = Response::try_from(PAGE).unwrap();
+
+    let mut client = client(&url);
+    client.plain_view = true;
+    save_reading_position(&mut client, &response, &url, 1);
+    assert_eq!(reading_position(&mut client, &response, &url), Some(9));
+
+    let mut client = Client::new(
+        &url,
+        Pager::Less,
+        Config {
+            renderer: RendererKind::Plain,
+            ..Config::default()
+        },
+    );
+    save_reading_position(&mut client, &response, &url, 1);
+    assert_eq!(reading_position(&mut client, &response, &url), Some(9));
+}
+
+#[test]
+fn headings_are_found_on_their_shown_line() {
+    let url = Url::parse("gemini://example.org/essay.gmi").unwrap();
+    let response = Response::try_from(
+        "20 text/gemini\r\n\
+         # Essay\n\
+         ```\n\
+         code\n\
+         ```\n\
+         ## Notes\n",
+    )
+    .unwrap();
+    let notes = response.headings()[1].line;
+    assert_eq!(notes, 5);
+
+    let mut client = client(&url);
+    assert_eq!(shown_line(&client, &response, notes), 3);
+    client.plain_view = true;
+    assert_eq!(shown_line(&client, &response, notes), 5);
+}
+
+#[test]
+fn empty_and_indented_links_are_counted_like_the_links_of_the_page() {
+    let response = Response::try_from(
+        "20 text/gemini\r\n\
+         =>\n\
+         \x20 => /indented.gmi Indented\n\
+         =>   \n\
+         => /last.gmi Last\n",
+    )
+    .unwrap();
+
+    assert_eq!(response.links.len(), 2);
+    assert_eq!(response.link_line(0), Some(2));
+    assert_eq!(response.link_line(1), Some(4));
+    assert_eq!(response.link_line(2), None);
+}
+
+#[test]
+fn the_accessibility_preamble_moves_the_page_down() {
+    let url = Url::parse("gemini://example.org/essay.gmi").unwrap();
+    let page = PAGE.replace("20 text/gemini", "20 text/gemini; lang=fr");
+    let response = Response::try_from(page.as_str()).unwrap();
+    let mut client = Client::new(
+        &url,
+        Pager::Less,
+        Config {
+            a11y: true,
+            ..Config::default()
+        },
+    );
+
+    save_reading_position(&mut client, &response, &url, 1);
+    // "Page language: French" and a blank line come before the page
+    assert_eq!(reading_position(&mut client, &response, &url), Some(9));
+    assert_eq!(shown_line(&client, &response, 1), 3);
+
+    // Without a declared language there is no preamble
+    let response = Response::try_from(PAGE).unwrap();
+    assert_eq!(shown_line(&client, &response, 1), 1);
+}
+
+#[test]
+fn links_of_pages_built_by_the_client_keep_no_position() {
+    let url = Url::parse("gemini://example.org/essay.gmi").unwrap();
+    let mut client = client(&url);
+    let history = Response::try_from("20 text/gemini\r\n# History\n=> /a.gmi\n").unwrap();
+
+    let page = Response::try_from(PAGE).unwrap();
+    save_reading_position(&mut client, &page, &url, 1);
+
+    client.synthetic_page = true;
+    save_reading_position(&mut client, &history, &url, 0);
+    assert_eq!(reading_position(&mut client, &history, &url), None);
+
+    // The page itself still opens at the followed link
+    client.synthetic_page = false;
+    assert_eq!(reading_position(&mut client, &page, &url), Some(7));
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use common::MockServer;
+use gemini::client::{starts_with_host, Client};
+use gemini::config::Config;
+use gemini::errors::RedirectError;
+use gemini::handlers::handle_request;
+use gemini::models::{Pager, MAX_META_LENGTH};
+use url::Url;
+
+fn client_on(url: &str) -> Client {
+    Client::new(&Url::parse(url).unwrap(), Pager::Less, Config::default())
+}
+
+#[test]
+fn host_without_scheme_gets_the_gemini_scheme() {
+    let client = client_on("gemini://old.example/docs/page.gmi");
+    assert_eq!(
+        client.resolve_redirect("example.org/new").unwrap().as_str(),
+        "gemini://example.org/new"
+    );
+    assert_eq!(
+        client
+            .resolve_redirect("example.org:1966/")
+            .unwrap()
+            .as_str(),
+        "gemini://example.org:1966/"
+    );
+}
+
+#[test]
+fn host_without_scheme_keeps_the_protocol_of_the_page() {
+    let client = client_on("spartan://old.example/docs/");
+    assert_eq!(
+        client.resolve_redirect("example.org/new").unwrap().as_str(),
+        "spartan://example.org/new"
+    );
+    let client = client_on("about:bookmarks");
+    assert_eq!(
+        client.resolve_redirect("example.org/new").unwrap().as_str(),
+        "gemini://example.org/new"
+    );
+}
+
+#[test]
+fn absolute_path_stays_on_the_host() {
+    let client = client_on("gemini://old.example/docs/page.gmi");
+    assert_eq!(
+        client.resolve_redirect("/new").unwrap().as_str(),
+        "gemini://old.example/new"
+    );
+}
+
+#[test]
+fn relative_path_resolves_against_the_request() {
+    let client = client_on("gemini://old.example/docs/page.gmi");
+    assert_eq!(
+        client.resolve_redirect("other.gmi").unwrap().as_str(),
+        "gemini://old.example/docs/other.gmi"
+    );
+    assert_eq!(
+        client.resolve_redirect("../up/").unwrap().as_str(),
+        "gemini://old.example/up/"
+    );
+}
+
+#[test]
+fn only_host_names_followed_by_a_path_look_like_hosts() {
+    assert!(starts_with_host("example.org/"));
+    assert!(starts_with_host("sub.example.org/a?b"));
+    assert!(starts_with_host("example.org:1966/"));
+    assert!(!starts_with_host("example.org"));
+    assert!(!starts_with_host("page.gmi"));
+    assert!(!starts_with_host("new"));
+    assert!(!starts_with_host("v1.2/notes"));
+    assert!(!starts_with_host("/example.org"));
+}
+
+#[test]
+fn file_names_of_any_extension_are_relative() {
+    let client = client_on("gemini://old.example/docs/page.gmi");
+    for file in ["page.php", "doc.pdf", "notes.txt.bak", "example.org"] {
+        assert_eq!(
+            client.resolve_redirect(file).unwrap().as_str(),
+            format!("gemini://old.example/docs/{file}")
+        );
+    }
+}
+
+#[test]
+fn a_host_name_leading_back_to_the_page_is_a_host() {
+    let client = client_on("gemini://old.example/docs/example.org");
+    assert_eq!(
+        client.resolve_redirect("example.org").unwrap().as_str(),
+        "gemini://example.org"
+    );
+    assert_eq!(
+        client.resolve_redirect("other.org").unwrap().as_str(),
+        "gemini://old.example/docs/other.org"
+    );
+}
+
+#[test]
+fn redirect_to_a_relative_path_is_followed() {
+    let server = MockServer::builder()
+        .route("/docs/old", "31 new\r\n")
+        .start();
+    let mut client = server.client("/docs/old");
+
+    let next = handle_request(&mut client, &server.url("/docs/old"));
+    assert_eq!(next, Some(server.url("/docs/new")));
+}
+
+#[test]
+fn redirects_need_a_target() {
+    let client = client_on("gemini://old.example/docs/page.gmi");
+    assert_eq!(client.resolve_redirect(""), Err(RedirectError::Empty));
+    assert_eq!(client.resolve_redirect("  "), Err(RedirectError::Empty));
+}
+
+#[test]
+fn over_long_redirect_targets_are_refused() {
+    let client = client_on("gemini://old.example/docs/page.gmi");
+    let target = format!("/{}", "a".repeat(MAX_META_LENGTH));
+    assert_eq!(
+        client.resolve_redirect(&target),
+        Err(RedirectError::TooLong(MAX_META_LENGTH + 1))
+    );
+    let longest = format!("/{}", "a".repeat(MAX_META_LENGTH - 1));
+    assert!(client.resolve_redirect(&longest).is_ok());
+}
+
+#[test]
+fn malformed_redirects_are_not_followed() {
+    let long = format!("30 /{}\r\n", "a".repeat(MAX_META_LENGTH));
+    let server = MockServer::builder()
+        .route("/empty", "30 \r\n")
+        .route("/long", &long)
+        .start();
+    let mut client = server.client("/empty");
+
+    assert_eq!(handle_request(&mut client, &server.url("/empty")), None);
+    assert_eq!(client.redirects, 1);
+    assert_eq!(handle_request(&mut client, &server.url("/long")), None);
+    assert_eq!(client.redirects, 1);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn redirects_to_client_pages_are_refused() {
+    let client = client_on("gemini://evil.example/");
+    let target = Url::parse("about:certs?forget=bank.example").unwrap();
+    assert_eq!(
+        client.resolve_redirect("about:certs?forget=bank.example"),
+        Err(RedirectError::ClientPage(target))
+    );
+}
+
+#[test]
+fn only_client_pages_change_their_own_lists() {
+    assert!(!client_on("gemini://evil.example/").on_about_page("certs"));
+    assert!(!client_on("about:bookmarks").on_about_page("certs"));
+    assert!(client_on("about:certs").on_about_page("certs"));
+}
+
+#[test]
+fn redirect_removing_a_bookmark_is_not_followed() {
+    let server = MockServer::builder()
+        .route(
+            "/remove",
+            "30 about:bookmarks?remove=gemini%3A%2F%2Fbank.example%2F\r\n",
+        )
+        .start();
+    let mut client = server.client("/remove");
+
+    assert_eq!(handle_request(&mut client, &server.url("/remove")), None);
+    assert_eq!(client.current_url, server.url("/remove"));
+    assert!(!client.on_about_page("bookmarks"));
+}
@@ -0,0 +1,91 @@
+use gemini::models::{Pager, Response};
+use gemini::render::{
+    number_lines, strip_ansi, DateDisplay, GemtextRenderer, HtmlRenderer, PreformattedOverflow,
+    Renderer,
+};
+use url::Url;
+
+const PAGE: &str = "20 text/gemini\r\n\
+                    # Title\n\
+                    Some <prose> & more\n\
+                    * One\n\
+                    * Two\n\
+                    => sub/page.gmi A \"page\"\n\
+                    ```\n\
+                    => not a link\n\
+                    ```\n\
+                    > A quote\n";
+
+fn base() -> Url {
+    Url::parse("gemini://example.org/docs/index.gmi").unwrap()
+}
+
+#[test]
+fn html_has_elements_escaped_and_links_resolved() {
+    let response = Response::try_from(PAGE).unwrap();
+
+    assert_eq!(
+        HtmlRenderer.render(&response, &base()),
+        "<!DOCTYPE html>\n<html>\n<body>\n\
+         <h1>Title</h1>\n\
+         <p>Some &lt;prose&gt; &amp; more</p>\n\
+         <ul>\n<li>One</li>\n<li>Two</li>\n</ul>\n\
+         <p><a href=\"gemini://example.org/docs/sub/page.gmi\">A &quot;page&quot;</a></p>\n\
+         <pre>\n=&gt; not a link\n</pre>\n\
+         <blockquote>A quote</blockquote>\n\
+         </body>\n</html>"
+    );
+}
+
+#[test]
+fn gemtext_without_colors_shows_the_structure() {
+    colored::control::set_override(false);
+    let response = Response::try_from(PAGE).unwrap();
+    let renderer = |inline_link_numbers| GemtextRenderer {
+        inline_link_numbers,
+        preformatted_overflow: PreformattedOverflow::Scroll,
+        dates: DateDisplay::Absolute,
+    };
+
+    assert_eq!(
+        renderer(true).render(&response, &base()),
+        "Title\nSome <prose> & more\n• One\n• Two\n(0) A \"page\"\n=> not a link\n> A quote"
+    );
+    assert!(renderer(false)
+        .render(&response, &base())
+        .contains("\n=> A \"page\"\n"));
+}
+
+#[test]
+fn plain_text_lines_are_numbered_right_aligned() {
+    colored::control::set_override(false);
+    let body = (1..=10)
+        .map(|n| format!("line {n}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let numbered = number_lines(&body);
+    let lines: Vec<&str> = numbered.lines().collect();
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[0], " 1 line 1");
+    assert_eq!(lines[9], "10 line 10");
+    assert_eq!(number_lines("only"), "1 only");
+}
+
+#[test]
+fn ansi_escapes_are_stripped() {
+    assert_eq!(strip_ansi("\x1b[1;31mred\x1b[0m text"), "red text");
+    assert_eq!(
+        strip_ansi("\x1b]8;;gemini://example.org/\x07link\x1b]8;;\x1b\\"),
+        "link"
+    );
+    assert_eq!(strip_ansi("\x1b(Bplain"), "plain");
+    assert_eq!(strip_ansi("no escapes, é"), "no escapes, é");
+    // Cut short at the end of the text
+    assert_eq!(strip_ansi("end\x1b[31"), "end");
+    assert_eq!(strip_ansi("end\x1b"), "end");
+
+    // Only `more` shows the escapes instead of colors
+    assert!(!Pager::More.renders_ansi());
+    assert!(Pager::Less.renders_ansi());
+}
@@ -0,0 +1,40 @@
+use gemini::config::{ConnectionSettings, PathRewrite};
+use gemini::protocol::{Gemini, Spartan};
+use gemini::trace::reproduce_command;
+use std::path::PathBuf;
+use url::Url;
+
+#[test]
+fn gemini_request_with_a_query_and_a_port() {
+    let url = Url::parse("gemini://example.org:1966/search?caf%C3%A9").unwrap();
+    assert_eq!(
+        reproduce_command(&Gemini, &url, &ConnectionSettings::default()),
+        "printf '%s\\r\\n' 'gemini://example.org:1966/search?caf%C3%A9' \
+         | openssl s_client -quiet -connect example.org:1966 -servername example.org"
+    );
+}
+
+#[test]
+fn client_certificate_and_rewrite_are_included() {
+    let url = Url::parse("gemini://example.org/it's.gmi").unwrap();
+    let settings = ConnectionSettings {
+        identity: Some(PathBuf::from("/home/me/my cert.p12")),
+        rewrite: Some(PathRewrite::Prefix("/mirror".to_string())),
+        ..ConnectionSettings::default()
+    };
+    assert_eq!(
+        reproduce_command(&Gemini, &url, &settings),
+        "printf '%s\\r\\n' 'gemini://example.org/mirror/it'\\''s.gmi' \
+         | openssl s_client -quiet -connect example.org:1965 -servername example.org \
+         -cert '/home/me/my cert.p12'"
+    );
+}
+
+#[test]
+fn spartan_uses_plain_tcp() {
+    let url = Url::parse("spartan://example.org:3000/post?hello%20world").unwrap();
+    assert_eq!(
+        reproduce_command(&Spartan, &url, &ConnectionSettings::default()),
+        "printf '%s\\r\\n%s' 'example.org /post 11' 'hello world' | nc example.org 3000"
+    );
+}
@@ -0,0 +1,43 @@
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::models::Pager;
+use url::Url;
+
+fn resolve(current: &str, link: &str) -> String {
+    let client = Client::new(
+        &Url::parse(current).unwrap(),
+        Pager::Less,
+        Config::default(),
+    );
+    client.resolve_link(link).unwrap().to_string()
+}
+
+#[test]
+fn empty_authority_stays_on_the_current_host() {
+    assert_eq!(
+        resolve(
+            "gemini://example.org:1966/docs/",
+            "gemini:///news.gmi?q#top"
+        ),
+        "gemini://example.org:1966/news.gmi?q#top"
+    );
+    assert_eq!(
+        resolve("gemini://example.org/docs/page.gmi", "gemini:///"),
+        "gemini://example.org/"
+    );
+}
+
+#[test]
+fn other_links_resolve_as_usual() {
+    let current = "gemini://example.org/docs/page.gmi";
+    assert_eq!(
+        resolve(current, "gemini://other.example/"),
+        "gemini://other.example/"
+    );
+    assert_eq!(
+        resolve(current, "//other.example/a"),
+        "gemini://other.example/a"
+    );
+    assert_eq!(resolve(current, "/a"), "gemini://example.org/a");
+    assert_eq!(resolve(current, "b.gmi"), "gemini://example.org/docs/b.gmi");
+}
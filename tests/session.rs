@@ -0,0 +1,104 @@
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::models::Pager;
+use gemini::state::{SessionFlush, SessionMode, SessionState};
+use std::fs;
+use url::Url;
+
+fn client() -> Client {
+    let url = Url::parse("gemini://example.org/").unwrap();
+    let mut client = Client::new(&url, Pager::Less, Config::default());
+    client.history = vec![url.clone(), url.join("/next.gmi").unwrap()];
+    client.last_working_url = Some(url.join("/next.gmi").unwrap());
+    client
+}
+
+#[test]
+fn shutdown_flush_saves_the_latest_state_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session");
+    let session = SessionFlush::new(path.clone(), SessionMode::History);
+    let mut client = client();
+    session.update(&client);
+
+    // The signal handler holds a clone
+    assert!(session.clone().flush().unwrap());
+    let saved = SessionState::load(&path).unwrap();
+    assert_eq!(
+        saved,
+        SessionState::from_client(&client, SessionMode::History)
+    );
+
+    // Nothing changes once saved, even if browsing went on
+    client.history.clear();
+    session.update(&client);
+    assert!(!session.flush().unwrap());
+    assert_eq!(SessionState::load(&path).unwrap(), saved);
+}
+
+#[test]
+fn nothing_is_saved_before_the_first_page() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session");
+    fs::write(&path, "last gemini://example.org/previous.gmi\n").unwrap();
+
+    let session = SessionFlush::new(path.clone(), SessionMode::Last);
+    assert!(!session.flush().unwrap());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "last gemini://example.org/previous.gmi\n"
+    );
+}
+
+#[test]
+fn saved_session_loads_back_as_it_was() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state").join("session");
+    let mut client = client();
+    let next = Url::parse("gemini://example.org/next.gmi").unwrap();
+    client.titles.insert(next.clone(), "Next page".to_string());
+
+    let state = SessionState::from_client(&client, SessionMode::History);
+    state.save(&path).unwrap();
+    assert_eq!(SessionState::load(&path).unwrap(), state);
+    assert_eq!(
+        state.titles.get(&next).map(String::as_str),
+        Some("Next page")
+    );
+
+    let last_only = SessionState::from_client(&client, SessionMode::Last);
+    assert_eq!(last_only.last_url, Some(next));
+    assert!(last_only.history.is_empty());
+}
+
+#[test]
+fn sensitive_pages_are_not_saved() {
+    let mut client = client();
+    let login = Url::parse("gemini://example.org/login?hunter2").unwrap();
+    client.history.push(login.clone());
+    client.last_working_url = Some(login.clone());
+    client.sensitive_urls.insert(login.clone());
+
+    let state = SessionState::from_client(&client, SessionMode::History);
+    assert_eq!(state.last_url, None);
+    assert!(!state.history.contains(&login));
+    assert_eq!(state.history.len(), 2);
+}
+
+#[test]
+fn invalid_entries_are_skipped_when_loading() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session");
+    fs::write(
+        &path,
+        "history not a url\nbogus gemini://example.org/\nlast gemini://example.org/a.gmi\n",
+    )
+    .unwrap();
+
+    let state = SessionState::load(&path).unwrap();
+    assert!(state.history.is_empty());
+    assert_eq!(
+        state.last_url.as_ref().map(Url::as_str),
+        Some("gemini://example.org/a.gmi")
+    );
+}
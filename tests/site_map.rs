@@ -0,0 +1,41 @@
+use gemini::pages::site_map_page;
+use url::Url;
+
+fn urls(urls: &[&str]) -> Vec<Url> {
+    urls.iter().map(|url| Url::parse(url).unwrap()).collect()
+}
+
+#[test]
+fn pages_are_grouped_by_path_segment() {
+    let visited = urls(&[
+        "gemini://example.org/docs/b.gmi",
+        "gemini://example.org/",
+        "gemini://example.org/docs/a.gmi",
+        "gemini://example.org/about.gmi",
+        "gemini://example.org/docs/",
+    ]);
+
+    assert_eq!(
+        site_map_page("example.org", &visited),
+        "# Site map of example.org\n\n\
+         => / example.org\n\
+         => /about.gmi   about.gmi\n\
+         => /docs/   docs/\n\
+         => /docs/a.gmi     a.gmi\n\
+         => /docs/b.gmi     b.gmi\n"
+    );
+}
+
+#[test]
+fn unvisited_directories_are_listed() {
+    let visited = urls(&["gemini://example.org/a/b/c.gmi"]);
+
+    assert_eq!(
+        site_map_page("example.org", &visited),
+        "# Site map of example.org\n\n\
+         => / example.org\n\
+         => /a/   a/\n\
+         => /a/b/     b/\n\
+         => /a/b/c.gmi       c.gmi\n"
+    );
+}
@@ -0,0 +1,49 @@
+use gemini::models::Response;
+use gemini::sniff::looks_like_gemtext;
+
+#[test]
+fn gemtext_is_recognised() {
+    assert!(looks_like_gemtext("# Capsule\n\nWelcome.\n"));
+    assert!(looks_like_gemtext("Some notes\n=> /about.gmi About\n"));
+    assert!(looks_like_gemtext("text\n### Third level\n"));
+}
+
+#[test]
+fn prose_is_not_gemtext() {
+    assert!(!looks_like_gemtext(
+        "Dear reader,\nthis is a letter.\n#hashtag in the middle => of things\n"
+    ));
+    assert!(!looks_like_gemtext("#!/bin/sh\necho hello\n"));
+    assert!(!looks_like_gemtext("#### Too deep\n=>\n"));
+    assert!(!looks_like_gemtext(""));
+}
+
+#[test]
+fn binary_is_not_gemtext() {
+    assert!(!looks_like_gemtext("# PNG\0\0\0\rIHDR\n=> x"));
+    assert!(!looks_like_gemtext("# Header\n\u{FFFD}\u{FFFD}garbage\n"));
+    let noisy: String = "# Title\n"
+        .chars()
+        .chain("\u{1}\u{2}\u{3}ab".chars())
+        .collect();
+    assert!(!looks_like_gemtext(&noisy));
+}
+
+#[test]
+fn declared_types_other_than_gemtext_have_no_links_unless_sniffed() {
+    let mut response = Response::try_from("20 text/plain\r\n# Notes\n=> /next.gmi Next\n").unwrap();
+    assert!(response.links.is_empty());
+    assert!(!response.is_markup());
+
+    response.sniff_gemtext();
+    assert!(response.is_markup());
+    assert_eq!(response.links.len(), 1);
+    assert_eq!(response.links[0].href, "/next.gmi");
+}
+
+#[test]
+fn prose_stays_plain_after_sniffing() {
+    let mut response = Response::try_from("20 application/octet-stream\r\nplain words\n").unwrap();
+    response.sniff_gemtext();
+    assert!(!response.is_markup());
+}
@@ -0,0 +1,21 @@
+use gemini::spinner::Spinner;
+
+#[test]
+fn spinner_runs_until_stopped() {
+    let mut spinner = Spinner::start("Loading");
+    assert!(spinner.is_running());
+
+    spinner.stop();
+    assert!(!spinner.is_running());
+    // Stopping again is harmless, as is dropping it afterwards
+    spinner.stop();
+    assert!(!spinner.is_running());
+}
+
+#[test]
+fn disabled_spinner_never_runs() {
+    let mut spinner = Spinner::disabled();
+    assert!(!spinner.is_running());
+    spinner.stop();
+    assert!(!spinner.is_running());
+}
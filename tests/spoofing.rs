@@ -0,0 +1,48 @@
+use gemini::links::spoofed_name;
+use gemini::models::Link;
+use gemini::render::{compact_link_footer, link_footer, strip_ansi};
+use url::Url;
+
+fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+}
+
+#[test]
+fn names_showing_the_link_target_are_fine() {
+    let href = url("gemini://bank.example/login");
+    assert!(!spoofed_name("gemini://bank.example/login", &href));
+    assert!(!spoofed_name("Log in at gemini://bank.example/.", &href));
+    assert!(!spoofed_name("(gemini://bank.example:1965)", &href));
+    assert!(!spoofed_name("My bank", &href));
+    assert!(!spoofed_name("bank.example", &href));
+}
+
+#[test]
+fn names_showing_another_url_are_spoofed() {
+    let href = url("gemini://evil.example/login");
+    assert!(spoofed_name("gemini://bank.example/login", &href));
+    assert!(spoofed_name("Log in at gemini://bank.example/", &href));
+    assert!(spoofed_name("https://evil.example/login", &href));
+    assert!(spoofed_name("gemini://evil.example:1966/login", &href));
+    assert!(spoofed_name("site:gemini://bank.example", &href));
+}
+
+#[test]
+fn spoofed_links_are_marked_in_the_footer() {
+    let links = vec![
+        Link::new(
+            "gemini://evil.example/".to_string(),
+            Some("gemini://bank.example/".to_string()),
+        ),
+        Link::new("/about".to_string(), Some("About".to_string())),
+    ];
+
+    let footer = strip_ansi(&link_footer(&links, None, &[], &[true, false]).join("\n"));
+    assert_eq!(
+        footer,
+        "0: gemini://bank.example/ (⚠ gemini://evil.example/)\n1: About                  (/about)"
+    );
+    let compact =
+        strip_ansi(&compact_link_footer(&links, None, &[], &[true, false], 80).join("\n"));
+    assert_eq!(compact, "[0] ⚠ gemini://bank.example/  [1] About");
+}
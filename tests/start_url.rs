@@ -0,0 +1,45 @@
+use gemini::client::start_url;
+
+fn some(url: &str) -> Option<String> {
+    Some(url.to_string())
+}
+
+#[test]
+fn the_first_set_source_wins_in_every_combination() {
+    let names = ["argument", "file", "env", "resumed", "home"];
+
+    for set in 0..1u32 << names.len() {
+        let source = |i: usize| (set & 1 << i != 0).then(|| format!("{}.example", names[i]));
+        let [argument, file, env, resumed, home] = [0, 1, 2, 3, 4].map(source);
+        let expected = (0..names.len()).find_map(source);
+
+        assert_eq!(
+            start_url(argument, file, env, resumed, home),
+            expected,
+            "sources set: {set:05b}"
+        );
+    }
+}
+
+#[test]
+fn nothing_set_gives_no_url() {
+    assert_eq!(start_url(None, None, None, None, None), None);
+}
+
+#[test]
+fn blank_sources_are_skipped_and_urls_trimmed() {
+    assert_eq!(
+        start_url(
+            None,
+            some(" \n"),
+            some(""),
+            some(" resumed.example\n"),
+            None
+        ),
+        some("resumed.example")
+    );
+    assert_eq!(
+        start_url(some("  "), None, None, None, some("home.example")),
+        some("home.example")
+    );
+}
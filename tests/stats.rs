@@ -0,0 +1,28 @@
+use gemini::stats::{format_reading_stats, reading_stats, WORDS_PER_MINUTE};
+use std::time::Duration;
+
+#[test]
+fn only_prose_words_are_counted() {
+    let body = "# A title\n\
+                Some prose, with - punctuation.\n\
+                => /link.gmi Not counted\n\
+                ```\n\
+                let code = not_counted;\n\
+                ```\n\
+                * An item";
+    let (words, time) = reading_stats(body);
+    assert_eq!(words, 8);
+    assert_eq!(time, Duration::from_secs(8 * 60 / WORDS_PER_MINUTE));
+}
+
+#[test]
+fn reading_time_is_rounded_up_to_a_minute() {
+    let body = "word ".repeat(250);
+    let (words, time) = reading_stats(&body);
+    assert_eq!(format_reading_stats(words, time), "250 words, 2 min read");
+
+    assert_eq!(
+        format_reading_stats(0, Duration::ZERO),
+        "0 words, 1 min read"
+    );
+}
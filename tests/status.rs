@@ -0,0 +1,89 @@
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::handlers::status_line;
+use gemini::links::link_summary;
+use gemini::models::{Link, Pager, Response, SpecificCode};
+use url::Url;
+
+fn client(url: &Url) -> Client {
+    Client::new(url, Pager::Less, Config::default())
+}
+
+#[test]
+fn specific_codes_have_labels() {
+    assert_eq!(SpecificCode::from(51).label(), "Not Found");
+    assert_eq!(SpecificCode::from(44).label(), "Slow Down");
+    assert_eq!(SpecificCode::from(62).label(), "Certificate Not Valid");
+    assert_eq!(SpecificCode::from(11).label(), "Sensitive Input");
+    // Undefined codes fall back to their category
+    assert_eq!(SpecificCode::from(57).label(), "Permanent Failure");
+    assert_eq!(SpecificCode::from(99).label(), "Unknown Status");
+}
+
+#[test]
+fn summary_shows_the_number_label_and_meta() {
+    let response = Response::try_from("51 No such page\r\n").unwrap();
+    assert_eq!(response.status_summary(), "51 Not Found: No such page");
+
+    let response = Response::try_from("42 script crashed\r\n").unwrap();
+    assert_eq!(response.status_summary(), "42 CGI Error: script crashed");
+}
+
+#[test]
+fn status_line_fits_the_terminal_width() {
+    let response = Response::try_from("20 text/gemini\r\nthree short words\n").unwrap();
+    let url = Url::parse("gemini://example.org/page.gmi").unwrap();
+    let client = client(&url);
+    let full = "gemini://example.org/page.gmi — 3 words, 1 min read";
+
+    assert_eq!(status_line(&client, &response, &url, 120), full);
+    assert_eq!(
+        status_line(&client, &response, &url, full.chars().count()),
+        full
+    );
+    assert_eq!(
+        status_line(&client, &response, &url, 30),
+        "gemini://example.org/page.gmi…"
+    );
+    assert_eq!(status_line(&client, &response, &url, 10), "gemini://…");
+}
+
+#[test]
+fn links_are_summarized_by_kind() {
+    let base = Url::parse("gemini://example.org/dir/").unwrap();
+    let resolve = |href: &str| base.join(href);
+    let links: Vec<Link> = [
+        "/a.gmi",
+        "b.gmi",
+        "https://example.com/",
+        "mailto:me@example.org",
+        "gemini://other.example/",
+        "http://example.net/",
+        "spartan://example.org/",
+        "http://[::1",
+    ]
+    .iter()
+    .map(|href| Link::new((*href).to_string(), None))
+    .collect();
+
+    assert_eq!(
+        link_summary(&links, resolve).as_deref(),
+        Some("8 links: 3 gemini, 2 http, 1 mailto, 1 spartan, 1 invalid")
+    );
+    assert_eq!(
+        link_summary(&links[..1], resolve).as_deref(),
+        Some("1 link: 1 gemini")
+    );
+    assert_eq!(link_summary(&[], resolve), None);
+}
+
+#[test]
+fn status_line_shows_the_link_summary() {
+    let response =
+        Response::try_from("20 text/gemini\r\n=> /a.gmi A\n=> https://example.com/ B\n").unwrap();
+    let url = Url::parse("gemini://example.org/").unwrap();
+    assert_eq!(
+        status_line(&client(&url), &response, &url, 200),
+        "gemini://example.org/ — 0 words, 1 min read — 2 links: 1 gemini, 1 http"
+    );
+}
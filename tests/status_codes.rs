@@ -0,0 +1,72 @@
+mod common;
+
+use common::MockServer;
+use gemini::handlers::handle_request;
+use gemini::models::SpecificCode;
+
+/// The code of the spec `code` stands for, the generic one of its category if it
+/// has no meaning of its own.
+fn expected(code: u8) -> SpecificCode {
+    match code {
+        10 | 12..=19 => SpecificCode::Input,
+        11 => SpecificCode::SensitiveInput,
+        20..=29 => SpecificCode::Success,
+        30 | 32..=39 => SpecificCode::TemporaryRedirect,
+        31 => SpecificCode::PermanentRedirect,
+        40 | 45..=49 => SpecificCode::TemporaryFailure,
+        41 => SpecificCode::ServerUnavailable,
+        42 => SpecificCode::CgiError,
+        43 => SpecificCode::ProxyError,
+        44 => SpecificCode::SlowDown,
+        50 | 54..=58 => SpecificCode::PermanentFailure,
+        51 => SpecificCode::NotFound,
+        52 => SpecificCode::Gone,
+        53 => SpecificCode::ProxyRequestRefused,
+        59 => SpecificCode::BadRequest,
+        60 | 63..=69 => SpecificCode::ClientCertificateRequired,
+        61 => SpecificCode::CertificateNotAuthorized,
+        62 => SpecificCode::CertificateNotValid,
+        _ => SpecificCode::Unknown(code),
+    }
+}
+
+#[test]
+fn every_code_maps_to_its_specific_code() {
+    for code in 0..=u8::MAX {
+        assert_eq!(SpecificCode::from(code), expected(code), "code {code}");
+    }
+}
+
+#[test]
+fn specific_codes_have_the_names_of_the_spec() {
+    assert_eq!(SpecificCode::from(11).label(), "Sensitive Input");
+    assert_eq!(SpecificCode::from(44).label(), "Slow Down");
+    assert_eq!(SpecificCode::from(51).label(), "Not Found");
+    assert_eq!(SpecificCode::from(62).label(), "Certificate Not Valid");
+    assert_eq!(SpecificCode::from(7).label(), "Unknown Status");
+}
+
+#[test]
+fn only_unavailable_servers_and_proxy_errors_are_retried() {
+    let retried: Vec<u8> = (0..=u8::MAX)
+        .filter(|&code| SpecificCode::from(code).is_retryable())
+        .collect();
+    assert_eq!(retried, [41, 43]);
+}
+
+#[test]
+fn unavailable_servers_are_asked_again() {
+    let server = MockServer::builder()
+        .route("/busy", "41 Busy\r\n")
+        .route("/broken", "42 Script failed\r\n")
+        .start();
+    let mut client = server.client("/");
+    client.config.retries = 1;
+
+    handle_request(&mut client, &server.url("/busy"));
+    assert_eq!(server.requests().len(), 2);
+
+    // CGI errors are likely to happen again
+    handle_request(&mut client, &server.url("/broken"));
+    assert_eq!(server.requests().len(), 3);
+}
@@ -0,0 +1,20 @@
+use gemini::terminal::{restore_terminal, TerminalGuard};
+use std::panic;
+
+#[test]
+fn restoring_without_a_terminal_is_harmless() {
+    restore_terminal();
+    drop(TerminalGuard::new());
+    drop(TerminalGuard::new());
+}
+
+#[test]
+fn panics_still_unwind_through_the_guard() {
+    let result = panic::catch_unwind(|| {
+        let _terminal = TerminalGuard::new();
+        panic!("while browsing");
+    });
+
+    let payload = result.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"while browsing"));
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::MockServer;
+use gemini::handlers::status_line;
+
+#[test]
+fn fetched_pages_are_timed() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini\r\n# Home\r\n")
+        .start();
+    let mut client = server.client("/");
+
+    let response = client.request(server.url("/")).unwrap().unwrap();
+    let connection = response.connection.expect("no connection info");
+    assert!(!connection.elapsed.is_zero());
+    assert_eq!(
+        connection.to_string(),
+        format!("fetched in {} ms", connection.elapsed.as_millis())
+    );
+
+    let line = status_line(&client, &response, &server.url("/"), 200);
+    assert!(!line.contains("fetched in"), "{line}");
+    client.config.timing = true;
+    let line = status_line(&client, &response, &server.url("/"), 200);
+    assert!(line.ends_with(&format!(" — {connection}")), "{line}");
+    client.config.timing = false;
+    let line = status_line(&client, &response, &server.url("/"), 200);
+    assert!(!line.contains("fetched in"), "{line}");
+}
+
+#[test]
+fn built_pages_are_not_timed() {
+    let response = gemini::models::Response::try_from("20 text/gemini\r\n# Home\n").unwrap();
+    assert!(response.connection.is_none());
+}
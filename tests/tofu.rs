@@ -0,0 +1,37 @@
+use gemini::tofu::{check, TofuDecision, TofuPolicy};
+
+#[test]
+fn known_certificate_is_trusted_under_every_policy() {
+    for policy in [TofuPolicy::Silent, TofuPolicy::Prompt, TofuPolicy::Strict] {
+        assert_eq!(check(policy, Some("ab12"), "ab12"), TofuDecision::Trusted);
+    }
+}
+
+#[test]
+fn unknown_host_follows_the_policy() {
+    assert_eq!(
+        check(TofuPolicy::Silent, None, "ab12"),
+        TofuDecision::Record
+    );
+    assert_eq!(check(TofuPolicy::Prompt, None, "ab12"), TofuDecision::Ask);
+    assert_eq!(
+        check(TofuPolicy::Strict, None, "ab12"),
+        TofuDecision::Refuse
+    );
+}
+
+#[test]
+fn changed_certificate_follows_the_policy() {
+    assert_eq!(
+        check(TofuPolicy::Silent, Some("ab12"), "cd34"),
+        TofuDecision::Changed("ab12".to_string())
+    );
+    assert_eq!(
+        check(TofuPolicy::Prompt, Some("ab12"), "cd34"),
+        TofuDecision::AskChanged("ab12".to_string())
+    );
+    assert_eq!(
+        check(TofuPolicy::Strict, Some("ab12"), "cd34"),
+        TofuDecision::RefuseChanged("ab12".to_string())
+    );
+}
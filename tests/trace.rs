@@ -0,0 +1,103 @@
+mod common;
+
+use common::MockServer;
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::handlers::{prompt_text, status_line};
+use gemini::models::Pager;
+use gemini::protocol::{Gemini, Spartan};
+use gemini::trace::{hex_dump, redacted, trace_request, Trace, TRACE_BYTES};
+use url::Url;
+
+#[test]
+fn trace_shows_the_request_line() {
+    let url = Url::parse("gemini://example.org/search?rust").unwrap();
+    assert_eq!(
+        trace_request(&Gemini, &url, Trace::default()),
+        "> gemini://example.org/search?rust\\r\\n"
+    );
+}
+
+#[test]
+fn sensitive_queries_are_redacted() {
+    let url = Url::parse("gemini://example.org/login?hunter2").unwrap();
+    let trace = Trace { redact_query: true };
+
+    let traced = trace_request(&Gemini, &url, trace);
+    assert!(!traced.contains("hunter2"));
+    assert_eq!(traced, "> gemini://example.org/login?REDACTED\\r\\n");
+
+    let url = Url::parse("spartan://example.org/login?hunter2").unwrap();
+    let traced = trace_request(&Spartan, &url, trace);
+    assert!(!traced.contains("hunter2"));
+    assert_eq!(traced, "> example.org /login 8\\r\\nREDACTED");
+}
+
+#[test]
+fn dump_shows_hex_and_printable_bytes() {
+    let dump = hex_dump(b"20 text/gemini\r\n# Hi\n");
+    assert_eq!(
+        dump,
+        "< 00000000  32 30 20 74 65 78 74 2f 67 65 6d 69 6e 69 0d 0a  20 text/gemini..\n\
+         < 00000010  23 20 48 69 0a                                   # Hi.\n"
+    );
+}
+
+#[test]
+fn dump_is_limited() {
+    let dump = hex_dump(&vec![b'a'; TRACE_BYTES + 10]);
+    assert_eq!(dump.lines().count(), TRACE_BYTES / 16 + 1);
+    assert!(dump.ends_with("… 10 more bytes\n"));
+}
+
+#[test]
+fn redacted_urls_keep_everything_but_the_query() {
+    let url = Url::parse("gemini://example.org/login?hunter2#top").unwrap();
+    assert_eq!(
+        redacted(&url).as_str(),
+        "gemini://example.org/login?REDACTED#top"
+    );
+    let url = Url::parse("gemini://example.org/").unwrap();
+    assert_eq!(redacted(&url), url);
+}
+
+#[test]
+fn sensitive_urls_are_hidden_from_what_the_client_shows() {
+    let home = Url::parse("gemini://example.org/").unwrap();
+    let login = Url::parse("gemini://example.org/login?hunter2").unwrap();
+    let mut client = Client::new(&home, Pager::Less, Config::default());
+    client.sensitive_urls.insert(login.clone());
+
+    assert_eq!(
+        client.shown_url(&login).as_str(),
+        "gemini://example.org/login?REDACTED"
+    );
+    assert_eq!(client.shown_url(&home), home);
+    assert!(!client.is_listable(&login));
+    assert!(client.is_listable(&home));
+}
+
+#[test]
+fn sensitive_input_is_hidden_from_the_status_line_and_prompt() {
+    let server = MockServer::builder()
+        .route("/login?hunter2", "20 text/gemini\r\n# Welcome\n")
+        .start();
+    let mut client = server.client("/login");
+    client.config.prompt = "{url}> ".to_string();
+    let login = server.url("/login?hunter2");
+    client.sensitive_urls.insert(login.clone());
+
+    let response = client.request(login.clone()).unwrap().ok().unwrap();
+    // The server gets the input, only what is shown hides it
+    assert_eq!(server.requests(), [login.to_string()]);
+
+    let redacted = server.url("/login?REDACTED");
+    assert_eq!(
+        status_line(&client, &response, &login, 200),
+        format!("{redacted} — 1 words, 1 min read")
+    );
+    assert_eq!(
+        prompt_text(&client, &response, &login),
+        format!("{redacted}> ")
+    );
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use std::time::Duration;
+
+use common::MockServer;
+use gemini::handlers::status_line;
+use gemini::headless::fetch;
+
+#[test]
+fn complete_responses_are_not_truncated() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini\r\n# Complete\n")
+        .start();
+    let mut client = server.client("/");
+
+    let (_, response) = fetch(&mut client, &server.url("/")).unwrap();
+    assert!(!response.truncated);
+}
+
+#[test]
+fn responses_cut_short_keep_what_was_received() {
+    let server = MockServer::builder()
+        .route("/", "20 text/gemini\r\n# Partial\nThe first half")
+        .stall(Duration::from_secs(3))
+        .start();
+    let mut client = server.client("/");
+    client.config.connection.timeout = Some(1);
+
+    let (url, response) = fetch(&mut client, &server.url("/")).unwrap();
+    assert!(response.truncated);
+    assert_eq!(response.body.as_deref(), Some("# Partial\nThe first half"));
+    assert!(status_line(&client, &response, &url, 200).ends_with(" — truncated"));
+}
@@ -0,0 +1,24 @@
+use gemini::version::{version_info, TLS_BACKEND, TLS_VERSIONS};
+
+#[test]
+fn version_info_names_the_version_tls_and_protocols() {
+    let info = version_info();
+    let lines: Vec<_> = info.lines().collect();
+
+    assert_eq!(
+        lines,
+        [
+            format!("gemini-client {}", env!("CARGO_PKG_VERSION")).as_str(),
+            format!("TLS backend: {TLS_BACKEND}").as_str(),
+            format!("TLS versions: {TLS_VERSIONS} selectable as minimum").as_str(),
+            "Protocols: gemini, spartan",
+        ]
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn linux_uses_openssl_with_tls_1_3() {
+    assert_eq!(TLS_BACKEND, "OpenSSL");
+    assert_eq!(TLS_VERSIONS, "1.0 to 1.3");
+}
@@ -0,0 +1,56 @@
+use gemini::client::Client;
+use gemini::config::Config;
+use gemini::models::{Link, Pager};
+use gemini::render::{compact_link_footer, link_footer, strip_ansi};
+use url::Url;
+
+fn links() -> Vec<Link> {
+    vec![
+        Link::new("/seen.gmi#part".to_string(), Some("Seen".to_string())),
+        Link::new("other.gmi".to_string(), Some("Other".to_string())),
+        Link::new("gemini://elsewhere.example/".to_string(), None),
+    ]
+}
+
+#[test]
+fn visits_are_matched_without_fragment() {
+    let url = Url::parse("gemini://example.org/docs/").unwrap();
+    let mut client = Client::new(&url, Pager::Less, Config::default());
+    assert_eq!(client.visited_links(&links()), [false, false, false]);
+
+    client.mark_visited(&Url::parse("gemini://example.org/seen.gmi#top").unwrap());
+    client.mark_visited(&Url::parse("gemini://elsewhere.example/").unwrap());
+    assert_eq!(client.visited_links(&links()), [true, false, true]);
+
+    // Relative links resolve against the current page
+    client.mark_visited(&Url::parse("gemini://example.org/other.gmi").unwrap());
+    assert_eq!(client.visited_links(&links()), [true, false, true]);
+    client.mark_visited(&Url::parse("gemini://example.org/docs/other.gmi").unwrap());
+    assert_eq!(client.visited_links(&links()), [true, true, true]);
+}
+
+#[test]
+fn visited_links_are_marked_in_the_footer() {
+    let footer =
+        |visited: &[bool]| strip_ansi(&link_footer(&links(), None, visited, &[]).join("\n"));
+
+    assert_eq!(
+        footer(&[]),
+        "0: Seen  (/seen.gmi#part)\n\
+         1: Other (other.gmi)\n\
+         2:       (gemini://elsewhere.example/)"
+    );
+    assert_eq!(
+        footer(&[true, false, false]),
+        "0: ✓ Seen  (/seen.gmi#part)\n\
+         1:   Other (other.gmi)\n\
+         2:         (gemini://elsewhere.example/)"
+    );
+
+    let compact =
+        strip_ansi(&compact_link_footer(&links(), None, &[false, true], &[], 80).join("\n"));
+    assert_eq!(
+        compact,
+        "[0] Seen  [1] ✓ Other  [2] gemini://elsewhere.example/"
+    );
+}